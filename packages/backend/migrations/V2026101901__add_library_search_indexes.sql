@@ -0,0 +1,3 @@
+CREATE INDEX IF NOT EXISTS library_user_completed_at ON library(user_id, completed_at);
+CREATE INDEX IF NOT EXISTS library_user_kind ON library(user_id, kind);
+CREATE INDEX IF NOT EXISTS activated_item_challenge_challenge ON activated_item_challenge(challenge_id);
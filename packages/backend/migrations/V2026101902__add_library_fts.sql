@@ -0,0 +1,27 @@
+-- Full-text index over library titles and people. Keyed by item id rather than
+-- rowid so that the index survives VACUUM and table rebuilds.
+CREATE VIRTUAL TABLE IF NOT EXISTS library_fts USING fts5(
+    item_id UNINDEXED,
+    title,
+    author,
+    translator,
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+INSERT INTO library_fts (item_id, title, author, translator)
+SELECT id, title, author, translator FROM library;
+
+CREATE TRIGGER IF NOT EXISTS library_fts_insert AFTER INSERT ON library BEGIN
+    INSERT INTO library_fts (item_id, title, author, translator)
+    VALUES (new.id, new.title, new.author, new.translator);
+END;
+
+CREATE TRIGGER IF NOT EXISTS library_fts_delete AFTER DELETE ON library BEGIN
+    DELETE FROM library_fts WHERE item_id = old.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS library_fts_update AFTER UPDATE OF title, author, translator ON library BEGIN
+    DELETE FROM library_fts WHERE item_id = old.id;
+    INSERT INTO library_fts (item_id, title, author, translator)
+    VALUES (new.id, new.title, new.author, new.translator);
+END;
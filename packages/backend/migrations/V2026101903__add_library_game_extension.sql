@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS library_game (
+    item_id TEXT PRIMARY KEY REFERENCES library(id) ON DELETE CASCADE,
+    platform TEXT,
+    playtime_minutes INTEGER
+);
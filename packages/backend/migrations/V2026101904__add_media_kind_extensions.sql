@@ -0,0 +1,22 @@
+CREATE TABLE IF NOT EXISTS library_movie (
+    item_id TEXT PRIMARY KEY REFERENCES library(id) ON DELETE CASCADE,
+    release_year INTEGER
+);
+
+CREATE TABLE IF NOT EXISTS library_series (
+    item_id TEXT PRIMARY KEY REFERENCES library(id) ON DELETE CASCADE,
+    season INTEGER,
+    episodes INTEGER
+);
+
+CREATE TABLE IF NOT EXISTS library_audiobook (
+    item_id TEXT PRIMARY KEY REFERENCES library(id) ON DELETE CASCADE,
+    narrator TEXT,
+    duration_minutes INTEGER
+);
+
+CREATE TABLE IF NOT EXISTS library_board_game (
+    item_id TEXT PRIMARY KEY REFERENCES library(id) ON DELETE CASCADE,
+    min_players INTEGER,
+    max_players INTEGER
+);
@@ -0,0 +1,43 @@
+-- Adds a reading status and makes completed_at nullable. SQLite cannot drop a
+-- NOT NULL constraint, so the table is rebuilt. Migrations run without foreign
+-- key enforcement, so rows referencing library are kept as they are.
+CREATE TABLE library_new (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    title TEXT NOT NULL,
+    author TEXT NOT NULL,
+    added_at TEXT NOT NULL,
+    completed_at TEXT,
+    favorite INTEGER NOT NULL DEFAULT 0,
+    translator TEXT,
+    status TEXT NOT NULL DEFAULT 'completed',
+    started_at TEXT
+);
+
+INSERT INTO library_new (id, user_id, kind, title, author, added_at, completed_at, favorite, translator, status)
+SELECT id, user_id, kind, title, author, added_at, completed_at, favorite, translator, 'completed'
+FROM library;
+
+DROP TABLE library;
+ALTER TABLE library_new RENAME TO library;
+
+CREATE INDEX IF NOT EXISTS library_user_completed_at ON library(user_id, completed_at);
+CREATE INDEX IF NOT EXISTS library_user_kind ON library(user_id, kind);
+CREATE INDEX IF NOT EXISTS library_user_status ON library(user_id, status);
+
+-- Triggers are dropped with the old table
+CREATE TRIGGER IF NOT EXISTS library_fts_insert AFTER INSERT ON library BEGIN
+    INSERT INTO library_fts (item_id, title, author, translator)
+    VALUES (new.id, new.title, new.author, new.translator);
+END;
+
+CREATE TRIGGER IF NOT EXISTS library_fts_delete AFTER DELETE ON library BEGIN
+    DELETE FROM library_fts WHERE item_id = old.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS library_fts_update AFTER UPDATE OF title, author, translator ON library BEGIN
+    DELETE FROM library_fts WHERE item_id = old.id;
+    INSERT INTO library_fts (item_id, title, author, translator)
+    VALUES (new.id, new.title, new.author, new.translator);
+END;
@@ -0,0 +1,3 @@
+ALTER TABLE library ADD COLUMN rating REAL;
+ALTER TABLE library ADD COLUMN review TEXT;
+ALTER TABLE library ADD COLUMN notes TEXT;
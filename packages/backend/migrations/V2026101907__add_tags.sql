@@ -0,0 +1,16 @@
+CREATE TABLE IF NOT EXISTS tag (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    -- Lowercased name, unique per user so that tags are case-insensitive
+    normalized_name TEXT NOT NULL,
+    UNIQUE (user_id, normalized_name)
+);
+
+CREATE TABLE IF NOT EXISTS item_tag (
+    item_id TEXT NOT NULL REFERENCES library(id) ON DELETE CASCADE,
+    tag_id TEXT NOT NULL REFERENCES tag(id) ON DELETE CASCADE,
+    PRIMARY KEY (item_id, tag_id)
+);
+
+CREATE INDEX IF NOT EXISTS item_tag_tag ON item_tag(tag_id);
@@ -0,0 +1,31 @@
+-- Each time an item has been finished. library.completed_at is kept as the
+-- latest completion by the triggers below.
+CREATE TABLE IF NOT EXISTS completion (
+    id TEXT PRIMARY KEY,
+    item_id TEXT NOT NULL REFERENCES library(id) ON DELETE CASCADE,
+    completed_at TEXT NOT NULL,
+    note TEXT
+);
+
+CREATE INDEX IF NOT EXISTS completion_item ON completion(item_id, completed_at);
+
+INSERT INTO completion (id, item_id, completed_at)
+SELECT lower(hex(randomblob(16))), id, completed_at FROM library WHERE completed_at IS NOT NULL;
+
+CREATE TRIGGER IF NOT EXISTS completion_sync_insert AFTER INSERT ON completion BEGIN
+    UPDATE library SET completed_at = (
+        SELECT MAX(completed_at) FROM completion WHERE item_id = new.item_id
+    ) WHERE id = new.item_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS completion_sync_update AFTER UPDATE ON completion BEGIN
+    UPDATE library SET completed_at = (
+        SELECT MAX(completed_at) FROM completion WHERE item_id = new.item_id
+    ) WHERE id IN (new.item_id, old.item_id);
+END;
+
+CREATE TRIGGER IF NOT EXISTS completion_sync_delete AFTER DELETE ON completion BEGIN
+    UPDATE library SET completed_at = (
+        SELECT MAX(completed_at) FROM completion WHERE item_id = old.item_id
+    ) WHERE id = old.item_id;
+END;
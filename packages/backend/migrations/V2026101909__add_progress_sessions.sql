@@ -0,0 +1,14 @@
+-- Total length of an item, used to compute reading or playing progress
+ALTER TABLE library ADD COLUMN page_count INTEGER;
+ALTER TABLE library ADD COLUMN expected_hours REAL;
+
+CREATE TABLE IF NOT EXISTS progress_session (
+    id TEXT PRIMARY KEY,
+    item_id TEXT NOT NULL REFERENCES library(id) ON DELETE CASCADE,
+    date TEXT NOT NULL,
+    pages INTEGER,
+    minutes INTEGER,
+    note TEXT
+);
+
+CREATE INDEX IF NOT EXISTS progress_session_item ON progress_session(item_id, date);
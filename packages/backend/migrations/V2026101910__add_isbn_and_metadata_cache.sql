@@ -0,0 +1,12 @@
+ALTER TABLE library ADD COLUMN isbn TEXT;
+
+CREATE INDEX IF NOT EXISTS library_user_isbn ON library(user_id, isbn);
+
+-- Successful metadata lookups per provider, keyed by ISBN-13
+CREATE TABLE IF NOT EXISTS metadata_cache (
+    provider TEXT NOT NULL,
+    isbn TEXT NOT NULL,
+    data TEXT NOT NULL,
+    fetched_at TEXT NOT NULL,
+    PRIMARY KEY (provider, isbn)
+);
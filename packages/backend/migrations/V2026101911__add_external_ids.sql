@@ -0,0 +1,11 @@
+-- Identifier of an item in the service it was imported from, so that
+-- repeated imports update the item instead of creating a new one
+CREATE TABLE IF NOT EXISTS external_id (
+    user_id TEXT NOT NULL,
+    source TEXT NOT NULL,
+    external_id TEXT NOT NULL,
+    item_id TEXT NOT NULL REFERENCES library(id) ON DELETE CASCADE,
+    PRIMARY KEY (user_id, source, external_id)
+);
+
+CREATE INDEX IF NOT EXISTS external_id_item ON external_id(item_id);
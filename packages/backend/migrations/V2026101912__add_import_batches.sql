@@ -0,0 +1,25 @@
+-- One run of an importer. Undoing a batch deletes the items it created.
+CREATE TABLE IF NOT EXISTS import_batch (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    source TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS import_batch_user ON import_batch(user_id, created_at);
+
+CREATE TABLE IF NOT EXISTS import_batch_item (
+    batch_id TEXT NOT NULL REFERENCES import_batch(id) ON DELETE CASCADE,
+    item_id TEXT NOT NULL REFERENCES library(id) ON DELETE CASCADE,
+    PRIMARY KEY (batch_id, item_id)
+);
+
+CREATE INDEX IF NOT EXISTS import_batch_item_item ON import_batch_item(item_id);
+
+-- CSV files uploaded for the column mapping step of the generic importer
+CREATE TABLE IF NOT EXISTS import_upload (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    content TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
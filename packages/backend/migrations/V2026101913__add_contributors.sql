@@ -0,0 +1,58 @@
+-- People credited on library items. library.author, library.translator and
+-- library_audiobook.narrator stay as comma separated copies of the
+-- contributor names so existing clients and the full text index keep working.
+CREATE TABLE IF NOT EXISTS contributor (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    -- Trimmed and lowercased with SQLite lower(), unique per user
+    normalized_name TEXT NOT NULL,
+    UNIQUE (user_id, normalized_name)
+);
+
+CREATE TABLE IF NOT EXISTS item_contributor (
+    item_id TEXT NOT NULL REFERENCES library(id) ON DELETE CASCADE,
+    contributor_id TEXT NOT NULL REFERENCES contributor(id) ON DELETE CASCADE,
+    role TEXT NOT NULL,
+    -- Order of the contributors within a role, such as the author order
+    position INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (item_id, contributor_id, role)
+);
+
+CREATE INDEX IF NOT EXISTS item_contributor_contributor ON item_contributor(contributor_id);
+
+INSERT OR IGNORE INTO contributor (id, user_id, name, normalized_name)
+SELECT lower(hex(randomblob(16))), user_id, name, lower(name)
+FROM (
+    SELECT user_id, trim(author) AS name FROM library WHERE trim(author) != ''
+    UNION
+    SELECT user_id, trim(translator) AS name FROM library WHERE trim(translator) != ''
+    UNION
+    SELECT l.user_id, trim(a.narrator) AS name FROM library l
+    JOIN library_audiobook a ON a.item_id = l.id WHERE trim(a.narrator) != ''
+);
+
+INSERT OR IGNORE INTO item_contributor (item_id, contributor_id, role, position)
+SELECT l.id, c.id,
+    CASE l.kind
+        WHEN 'Book' THEN 'author'
+        WHEN 'Audiobook' THEN 'author'
+        WHEN 'Game' THEN 'developer'
+        WHEN 'Movie' THEN 'director'
+        WHEN 'BoardGame' THEN 'designer'
+        ELSE 'creator'
+    END,
+    0
+FROM library l
+JOIN contributor c ON c.user_id = l.user_id AND c.normalized_name = lower(trim(l.author));
+
+INSERT OR IGNORE INTO item_contributor (item_id, contributor_id, role, position)
+SELECT l.id, c.id, 'translator', 0
+FROM library l
+JOIN contributor c ON c.user_id = l.user_id AND c.normalized_name = lower(trim(l.translator));
+
+INSERT OR IGNORE INTO item_contributor (item_id, contributor_id, role, position)
+SELECT l.id, c.id, 'narrator', 0
+FROM library l
+JOIN library_audiobook a ON a.item_id = l.id
+JOIN contributor c ON c.user_id = l.user_id AND c.normalized_name = lower(trim(a.narrator));
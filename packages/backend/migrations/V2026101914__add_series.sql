@@ -0,0 +1,23 @@
+-- A series of books or games, such as a trilogy. Not to be confused with
+-- the Series kind of library items, which are TV series.
+CREATE TABLE IF NOT EXISTS series (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    -- Lowercased name, unique per user
+    normalized_name TEXT NOT NULL,
+    -- Number of entries in the whole series, NULL when unknown or ongoing
+    total_count INTEGER,
+    UNIQUE (user_id, normalized_name)
+);
+
+CREATE TABLE IF NOT EXISTS series_entry (
+    series_id TEXT NOT NULL REFERENCES series(id) ON DELETE CASCADE,
+    item_id TEXT NOT NULL REFERENCES library(id) ON DELETE CASCADE,
+    -- Position in reading order. Fractions are allowed for novellas
+    -- between numbered entries.
+    position REAL NOT NULL,
+    PRIMARY KEY (series_id, item_id)
+);
+
+CREATE INDEX IF NOT EXISTS series_entry_item ON series_entry(item_id);
@@ -0,0 +1,27 @@
+-- Cover image of a library item. The image files live under DATA_DIR in a
+-- directory named after the cover id, which changes with every upload.
+CREATE TABLE IF NOT EXISTS cover (
+    item_id TEXT PRIMARY KEY REFERENCES library(id) ON DELETE CASCADE,
+    id TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    width INTEGER NOT NULL,
+    height INTEGER NOT NULL,
+    byte_size INTEGER NOT NULL,
+    uploaded_at TEXT NOT NULL
+);
+
+-- Covers whose files are still on disk but no longer referenced. Filled by
+-- the triggers below so that every way of deleting an item, including
+-- cascades, leaves a record for the file cleanup.
+CREATE TABLE IF NOT EXISTS cover_cleanup (
+    id TEXT PRIMARY KEY
+);
+
+CREATE TRIGGER IF NOT EXISTS cover_cleanup_delete AFTER DELETE ON cover BEGIN
+    INSERT OR IGNORE INTO cover_cleanup (id) VALUES (old.id);
+END;
+
+CREATE TRIGGER IF NOT EXISTS cover_cleanup_replace AFTER UPDATE OF id ON cover
+WHEN old.id != new.id BEGIN
+    INSERT OR IGNORE INTO cover_cleanup (id) VALUES (old.id);
+END;
@@ -0,0 +1,6 @@
+-- ISO 639-1 codes of the language the book was read in and the language it
+-- was originally written in
+ALTER TABLE library ADD COLUMN language TEXT;
+ALTER TABLE library ADD COLUMN original_language TEXT;
+
+CREATE INDEX IF NOT EXISTS library_user_original_language ON library(user_id, original_language);
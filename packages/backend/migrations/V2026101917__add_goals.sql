@@ -0,0 +1,12 @@
+-- Target number of items or pages to complete during a year, per kind.
+-- Goals of past years are kept as history.
+CREATE TABLE IF NOT EXISTS goal (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    year INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    -- 'items' or 'pages'
+    unit TEXT NOT NULL,
+    target INTEGER NOT NULL,
+    UNIQUE (user_id, year, kind, unit)
+);
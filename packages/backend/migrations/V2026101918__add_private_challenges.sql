@@ -0,0 +1,6 @@
+-- Private challenges belong to the user who created them. Shared challenges
+-- have no owner and stay editable by everyone.
+ALTER TABLE challenge ADD COLUMN owner_user_id TEXT;
+ALTER TABLE challenge ADD COLUMN visible_to_household INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS challenge_owner ON challenge(owner_user_id);
@@ -0,0 +1,33 @@
+-- Logging a completion finishes the item. An item whose last completion is
+-- removed is no longer completed: it goes back to in progress or planned
+-- and leaves its challenges, like a status change would. Updates that move
+-- a completion resync both items.
+DROP TRIGGER IF EXISTS completion_sync_insert;
+DROP TRIGGER IF EXISTS completion_sync_update;
+DROP TRIGGER IF EXISTS completion_sync_delete;
+
+CREATE TRIGGER completion_sync_insert AFTER INSERT ON completion BEGIN
+    UPDATE library SET status = 'completed', completed_at = (
+        SELECT MAX(completed_at) FROM completion WHERE item_id = new.item_id
+    ) WHERE id = new.item_id;
+END;
+
+CREATE TRIGGER completion_sync_update AFTER UPDATE ON completion BEGIN
+    UPDATE library SET completed_at = (
+        SELECT MAX(completed_at) FROM completion WHERE item_id = library.id
+    ) WHERE id IN (new.item_id, old.item_id);
+    UPDATE library SET status = CASE WHEN started_at IS NULL THEN 'planned' ELSE 'inProgress' END
+        WHERE id = old.item_id AND status = 'completed' AND completed_at IS NULL;
+    DELETE FROM activated_item_challenge WHERE item_id = old.item_id
+        AND NOT EXISTS (SELECT 1 FROM library WHERE id = old.item_id AND status = 'completed');
+END;
+
+CREATE TRIGGER completion_sync_delete AFTER DELETE ON completion BEGIN
+    UPDATE library SET completed_at = (
+        SELECT MAX(completed_at) FROM completion WHERE item_id = old.item_id
+    ) WHERE id = old.item_id;
+    UPDATE library SET status = CASE WHEN started_at IS NULL THEN 'planned' ELSE 'inProgress' END
+        WHERE id = old.item_id AND status = 'completed' AND completed_at IS NULL;
+    DELETE FROM activated_item_challenge WHERE item_id = old.item_id
+        AND NOT EXISTS (SELECT 1 FROM library WHERE id = old.item_id AND status = 'completed');
+END;
@@ -0,0 +1,35 @@
+-- The contributor backfill kept comma separated author, translator and
+-- narrator fields as a single contributor. Split them into one contributor
+-- per name, in the order they were listed.
+CREATE TEMP TABLE split_contributor AS
+WITH RECURSIVE part(item_id, user_id, role, position, name, rest, idx) AS (
+    SELECT ic.item_id, c.user_id, ic.role, ic.position, '', c.name || ',', -1
+    FROM item_contributor ic
+    JOIN contributor c ON c.id = ic.contributor_id
+    WHERE instr(c.name, ',') > 0
+    UNION ALL
+    SELECT item_id, user_id, role, position,
+        trim(substr(rest, 1, instr(rest, ',') - 1)),
+        substr(rest, instr(rest, ',') + 1),
+        idx + 1
+    FROM part
+    WHERE rest != ''
+)
+SELECT item_id, user_id, role, position + idx AS position, name
+FROM part
+WHERE idx >= 0 AND name != '';
+
+INSERT OR IGNORE INTO contributor (id, user_id, name, normalized_name)
+SELECT lower(hex(randomblob(16))), user_id, name, lower(name)
+FROM (SELECT DISTINCT user_id, name FROM split_contributor);
+
+DELETE FROM item_contributor WHERE contributor_id IN
+    (SELECT id FROM contributor WHERE instr(name, ',') > 0);
+DELETE FROM contributor WHERE instr(name, ',') > 0;
+
+INSERT OR IGNORE INTO item_contributor (item_id, contributor_id, role, position)
+SELECT s.item_id, c.id, s.role, s.position
+FROM split_contributor s
+JOIN contributor c ON c.user_id = s.user_id AND c.normalized_name = lower(s.name);
+
+DROP TABLE split_contributor;
@@ -0,0 +1,21 @@
+-- Series progress lists every position up to the total count, so the count
+-- gets an upper bound. SQLite cannot add a CHECK constraint to an existing
+-- column, so the table is rebuilt. Counts out of range become unknown.
+CREATE TABLE series_new (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    -- Lowercased name, unique per user
+    normalized_name TEXT NOT NULL,
+    -- Number of entries in the whole series, NULL when unknown or ongoing
+    total_count INTEGER CHECK (total_count BETWEEN 1 AND 1000),
+    UNIQUE (user_id, normalized_name)
+);
+
+INSERT INTO series_new (id, user_id, name, normalized_name, total_count)
+SELECT id, user_id, name, normalized_name,
+    CASE WHEN total_count BETWEEN 1 AND 1000 THEN total_count END
+FROM series;
+
+DROP TABLE series;
+ALTER TABLE series_new RENAME TO series;
@@ -0,0 +1,27 @@
+-- Only completed items have a completion date. Items that went back to
+-- planned, in progress or abandoned keep their completions as history, but
+-- moving or removing one of those completions must not date the item again.
+DROP TRIGGER IF EXISTS completion_sync_update;
+DROP TRIGGER IF EXISTS completion_sync_delete;
+
+UPDATE library SET completed_at = NULL WHERE status != 'completed';
+
+CREATE TRIGGER completion_sync_update AFTER UPDATE ON completion BEGIN
+    UPDATE library SET completed_at = (
+        SELECT MAX(completed_at) FROM completion WHERE item_id = library.id
+    ) WHERE id IN (new.item_id, old.item_id) AND status = 'completed';
+    UPDATE library SET status = CASE WHEN started_at IS NULL THEN 'planned' ELSE 'inProgress' END
+        WHERE id = old.item_id AND status = 'completed' AND completed_at IS NULL;
+    DELETE FROM activated_item_challenge WHERE item_id = old.item_id
+        AND NOT EXISTS (SELECT 1 FROM library WHERE id = old.item_id AND status = 'completed');
+END;
+
+CREATE TRIGGER completion_sync_delete AFTER DELETE ON completion BEGIN
+    UPDATE library SET completed_at = (
+        SELECT MAX(completed_at) FROM completion WHERE item_id = old.item_id
+    ) WHERE id = old.item_id AND status = 'completed';
+    UPDATE library SET status = CASE WHEN started_at IS NULL THEN 'planned' ELSE 'inProgress' END
+        WHERE id = old.item_id AND status = 'completed' AND completed_at IS NULL;
+    DELETE FROM activated_item_challenge WHERE item_id = old.item_id
+        AND NOT EXISTS (SELECT 1 FROM library WHERE id = old.item_id AND status = 'completed');
+END;
@@ -0,0 +1,26 @@
+-- SQLite lower() only lowercases ASCII, so names such as 'ÄIJÄLÄ' and
+-- 'Äijälä' became separate contributors. normalize_contributor_name() is
+-- registered by the migrator and lowercases like the application does.
+-- Contributors that now share a name are merged into the one with the
+-- smallest id.
+CREATE TEMP TABLE contributor_merge AS
+SELECT c.id AS source_id, (
+    SELECT MIN(k.id) FROM contributor k
+    WHERE k.user_id = c.user_id
+        AND normalize_contributor_name(k.name) = normalize_contributor_name(c.name)
+) AS target_id
+FROM contributor c;
+
+DELETE FROM contributor_merge WHERE source_id = target_id;
+
+INSERT OR IGNORE INTO item_contributor (item_id, contributor_id, role, position)
+SELECT ic.item_id, m.target_id, ic.role, ic.position
+FROM item_contributor ic
+JOIN contributor_merge m ON m.source_id = ic.contributor_id;
+
+DELETE FROM item_contributor WHERE contributor_id IN (SELECT source_id FROM contributor_merge);
+DELETE FROM contributor WHERE id IN (SELECT source_id FROM contributor_merge);
+
+UPDATE contributor SET normalized_name = normalize_contributor_name(name);
+
+DROP TABLE contributor_merge;
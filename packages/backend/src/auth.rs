@@ -68,7 +68,7 @@ impl FromRequestParts<AppState> for User {
 
         return match validate_jwt(token, &state.jwks, &state.required_audience) {
             Ok(sub) => {
-                return match convert_claim_to_user_id(&sub, &state) {
+                return match convert_claim_to_user_id(&sub, state) {
                     Ok(user_id) => Ok(User::new(user_id)),
                     Err(err) => {
                         println!("{}", err);
@@ -92,7 +92,7 @@ pub fn convert_claim_to_user_id(
     let mut db = Database::new(&state.database_path)?;
     let user: Option<String> = db
         .conn
-        .query_one("SELECT id FROM user WHERE id_claim = ?", &[sub], |f| {
+        .query_one("SELECT id FROM user WHERE id_claim = ?", [sub], |f| {
             f.get(0)
         })
         .optional()?;
@@ -106,7 +106,7 @@ pub fn convert_claim_to_user_id(
     let tx = db.conn.transaction()?;
     let insert = tx.execute(
         "REPLACE INTO user(id, id_claim) VALUES(?,?)",
-        &[&new_id, sub],
+        [&new_id, sub],
     )?;
 
     if insert == 1 {
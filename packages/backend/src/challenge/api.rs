@@ -12,11 +12,13 @@ use crate::{
     challenge::{
         NewSharedChallenge, SharedChallenge,
         domain::{
-            create_challenge, delete_challenge, get_challenge_by_id, get_challenges,
-            update_challenge,
+            activate_challenge_items, create_challenge, delete_challenge, get_challenge_by_id,
+            get_challenges, update_challenge,
         },
     },
+    library::{ActivationRange, ActivationReport},
     utils::map_to_internal_error,
+    validation::{ApiError, ValidJson, map_validation_error},
 };
 
 pub fn routes() -> Router<AppState> {
@@ -26,13 +28,14 @@ pub fn routes() -> Router<AppState> {
         .route("/challenge/{id}", get(get_challenge))
         .route("/challenge/{id}", put(update_existing_challenge))
         .route("/challenge/{id}", delete(delete_existing_challenge))
+        .route("/challenge/{id}/activate", post(activate_challenge_route))
 }
 
 async fn get_all_challenges(
     State(state): State<AppState>,
-    _user: User,
+    user: User,
 ) -> Result<Json<Vec<SharedChallenge>>, StatusCode> {
-    match get_challenges(&state) {
+    match get_challenges(&user, &state) {
         Ok(items) => Ok(Json(items)),
         Err(err) => Err(map_to_internal_error(err)),
     }
@@ -41,9 +44,9 @@ async fn get_all_challenges(
 async fn get_challenge(
     State(state): State<AppState>,
     Path(id): Path<String>,
-    _user: User,
+    user: User,
 ) -> Result<Json<SharedChallenge>, StatusCode> {
-    match get_challenge_by_id(&state, &id) {
+    match get_challenge_by_id(&user, &state, &id) {
         Ok(Some(item)) => Ok(Json(item)),
         Ok(None) => Err(StatusCode::NOT_FOUND),
         Err(err) => Err(map_to_internal_error(err)),
@@ -59,11 +62,12 @@ struct IdResponse {
 async fn create_new_challenge(
     State(state): State<AppState>,
     user: User,
-    Json(challenge): Json<NewSharedChallenge>,
-) -> Result<Json<IdResponse>, StatusCode> {
+    ValidJson(challenge): ValidJson<NewSharedChallenge>,
+) -> Result<Json<IdResponse>, ApiError> {
+    challenge.validate().into_result()?;
     match create_challenge(&user, &state, &challenge) {
         Ok(id) => Ok(Json(IdResponse { id })),
-        Err(err) => Err(map_to_internal_error(err)),
+        Err(err) => Err(map_validation_error(err, map_to_internal_error)),
     }
 }
 
@@ -71,19 +75,20 @@ async fn update_existing_challenge(
     State(state): State<AppState>,
     user: User,
     Path(id): Path<String>,
-    Json(challenge): Json<NewSharedChallenge>,
-) -> Result<StatusCode, StatusCode> {
+    ValidJson(challenge): ValidJson<NewSharedChallenge>,
+) -> Result<StatusCode, ApiError> {
+    challenge.validate().into_result()?;
     match update_challenge(&user, &state, &id, &challenge) {
         Ok(_) => Ok(StatusCode::NO_CONTENT),
-        Err(err) => {
+        Err(err) => Err(map_validation_error(err, |err| {
             if err.to_string().contains("Not authorized") {
-                Err(StatusCode::FORBIDDEN)
+                StatusCode::FORBIDDEN
             } else if err.to_string().contains("not found") {
-                Err(StatusCode::NOT_FOUND)
+                StatusCode::NOT_FOUND
             } else {
-                Err(map_to_internal_error(err))
+                map_to_internal_error(err)
             }
-        }
+        })),
     }
 }
 
@@ -105,3 +110,23 @@ async fn delete_existing_challenge(
         }
     }
 }
+
+/// Activates the challenge for the caller's items that were added before it
+/// became active
+async fn activate_challenge_route(
+    State(state): State<AppState>,
+    user: User,
+    Path(id): Path<String>,
+    ValidJson(range): ValidJson<ActivationRange>,
+) -> Result<Json<ActivationReport>, ApiError> {
+    let report = activate_challenge_items(&user, &state, &id, &range).map_err(|err| {
+        map_validation_error(err, |err| {
+            if err.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                map_to_internal_error(err)
+            }
+        })
+    })?;
+    Ok(Json(report))
+}
@@ -6,30 +6,47 @@ use crate::{
         repository::{ChallengeFilter, ChallengeRepository},
     },
     database::{Database, Repository},
+    library::{ActivationRange, ActivationReport, LibraryRepository},
+    validation::ValidationErrors,
 };
+use chrono::Datelike;
 
+/// Shared challenges, the user's own private ones and the private ones
+/// other users have made visible to the household
 pub fn get_challenges(
+    user: &User,
     state: &AppState,
 ) -> Result<Vec<SharedChallenge>, Box<dyn std::error::Error>> {
     let db = Database::new(&state.database_path)?;
     let mut repo = ChallengeRepository::new(db);
-    Ok(repo.search(ChallengeFilter::new())?)
+    Ok(repo.search(ChallengeFilter {
+        include_household: true,
+        ..ChallengeFilter::new(&user.id)
+    })?)
 }
 
 pub fn get_challenge_by_id(
+    user: &User,
     state: &AppState,
     id: &str,
 ) -> Result<Option<SharedChallenge>, Box<dyn std::error::Error>> {
     let db = Database::new(&state.database_path)?;
     let mut repo = ChallengeRepository::new(db);
-    Ok(repo.read_by_id(id)?)
+    Ok(repo
+        .read_by_id(id)?
+        .filter(|challenge| challenge.is_visible_to(&user.id)))
 }
 
 pub fn create_challenge(
-    _user: &User, // Not used as shared challenges don't track ownership
+    user: &User,
     state: &AppState,
     challenge: &NewSharedChallenge,
 ) -> Result<String, Box<dyn std::error::Error>> {
+    let visible_to_household = challenge.visible_to_household.unwrap_or(false);
+    let mut errors = ValidationErrors::new();
+    validate_visibility(&mut errors, challenge.kind(), visible_to_household);
+    errors.into_result()?;
+
     let db = Database::new(&state.database_path)?;
     let mut repo = ChallengeRepository::new(db);
 
@@ -39,20 +56,40 @@ pub fn create_challenge(
         status: challenge.status.clone(),
         target_media: challenge.target_media.clone(),
         questions: challenge.questions.clone(),
-        kind: "shared".to_string(),
+        kind: challenge.kind().to_string(),
+        owner_user_id: (challenge.kind() == "private").then(|| user.id.clone()),
+        visible_to_household,
     };
 
-    Ok(repo.create(&challenge)?)
+    let id = repo.create(&challenge)?;
+    if challenge.status == "active" {
+        activate_for_this_year(state, &challenge)?;
+    }
+    Ok(id)
 }
 
 pub fn update_challenge(
-    _user: &User, // Not used as anyone can update shared challenges
+    user: &User,
     state: &AppState,
     id: &str,
     challenge: &NewSharedChallenge,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let db = Database::new(&state.database_path)?;
     let mut repo = ChallengeRepository::new(db);
+    let existing = read_editable_challenge(&mut repo, user, id)?;
+    let visible_to_household = challenge
+        .visible_to_household
+        .unwrap_or(existing.visible_to_household);
+    let mut errors = ValidationErrors::new();
+    if challenge
+        .kind
+        .as_deref()
+        .is_some_and(|kind| kind != existing.kind)
+    {
+        errors.add("kind", "cannot be changed");
+    }
+    validate_visibility(&mut errors, &existing.kind, visible_to_household);
+    errors.into_result()?;
 
     let challenge = SharedChallenge {
         id: id.to_string(),
@@ -60,10 +97,15 @@ pub fn update_challenge(
         status: challenge.status.clone(),
         target_media: challenge.target_media.clone(),
         questions: challenge.questions.clone(),
-        kind: "shared".to_string(),
+        kind: existing.kind.clone(),
+        owner_user_id: existing.owner_user_id.clone(),
+        visible_to_household,
     };
 
     if repo.update(id, &challenge)? {
+        if existing.status != "active" && challenge.status == "active" {
+            activate_for_this_year(state, &challenge)?;
+        }
         Ok(())
     } else {
         Err("Challenge not found".into())
@@ -71,12 +113,13 @@ pub fn update_challenge(
 }
 
 pub fn delete_challenge(
-    _user: &User, // Not used as anyone can delete shared challenges
+    user: &User,
     state: &AppState,
     id: &str,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let db = Database::new(&state.database_path)?;
     let mut repo = ChallengeRepository::new(db);
+    read_editable_challenge(&mut repo, user, id)?;
 
     if repo.delete(id)? {
         Ok(())
@@ -84,3 +127,105 @@ pub fn delete_challenge(
         Err("Challenge not found".into())
     }
 }
+
+/// Activates the challenge for the user's completed items of the kind it
+/// targets, optionally limited to a completion date range
+pub fn activate_challenge_items(
+    user: &User,
+    state: &AppState,
+    id: &str,
+    range: &ActivationRange,
+) -> Result<ActivationReport, Box<dyn std::error::Error>> {
+    let (completed_after, completed_before) = range.to_bounds()?;
+    let mut repo = ChallengeRepository::new(Database::new(&state.database_path)?);
+    let challenge = repo
+        .read_by_id(id)?
+        .filter(|challenge| challenge.is_open_to(&user.id))
+        .ok_or("Challenge not found")?;
+
+    let mut library_repo = LibraryRepository::new(Database::new(&state.database_path)?);
+    let linked = library_repo.activate_challenge(
+        &challenge.id,
+        &challenge.target_media,
+        Some(&user.id),
+        completed_after.as_deref(),
+        completed_before.as_deref(),
+    )?;
+    Ok(ActivationReport { linked })
+}
+
+/// Items created while a challenge is active are activated when saved. A
+/// challenge that becomes active later also picks up the items finished
+/// earlier in the year, only the owner's items for private challenges.
+fn activate_for_this_year(
+    state: &AppState,
+    challenge: &SharedChallenge,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let range = ActivationRange::from_start_of_year(chrono::Utc::now().year());
+    let (completed_after, completed_before) = range.to_bounds()?;
+    let mut library_repo = LibraryRepository::new(Database::new(&state.database_path)?);
+    library_repo.activate_challenge(
+        &challenge.id,
+        &challenge.target_media,
+        challenge.owner_user_id.as_deref(),
+        completed_after.as_deref(),
+        completed_before.as_deref(),
+    )?;
+    Ok(())
+}
+
+/// Shared challenges are visible to everyone already, so only private ones
+/// can be made visible to the household
+fn validate_visibility(errors: &mut ValidationErrors, kind: &str, visible_to_household: bool) {
+    if visible_to_household && kind != "private" {
+        errors.add("visibleToHousehold", "only applies to private challenges");
+    }
+}
+
+/// Private challenges of other users are not found unless visible to the
+/// household, in which case they can be seen but not edited
+fn read_editable_challenge(
+    repo: &mut ChallengeRepository,
+    user: &User,
+    id: &str,
+) -> Result<SharedChallenge, Box<dyn std::error::Error>> {
+    match repo.read_by_id(id)? {
+        Some(challenge) if challenge.is_open_to(&user.id) => Ok(challenge),
+        Some(challenge) if challenge.is_visible_to(&user.id) => {
+            Err("Not authorized to edit the challenge".into())
+        }
+        _ => Err("Challenge not found".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::challenge::repository::tests::repository;
+
+    #[test]
+    fn only_the_owner_can_edit_private_challenges() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut repo = repository(&dir);
+        let me = User {
+            id: "me".to_string(),
+        };
+        let read = |repo: &mut ChallengeRepository, id| {
+            read_editable_challenge(repo, &me, id).map_err(|e| e.to_string())
+        };
+
+        assert_eq!(read(&mut repo, "shared").unwrap().id, "shared");
+        assert_eq!(read(&mut repo, "own").unwrap().id, "own");
+        assert!(
+            read(&mut repo, "household")
+                .unwrap_err()
+                .contains("Not authorized")
+        );
+        assert!(read(&mut repo, "other").unwrap_err().contains("not found"));
+        assert!(
+            read(&mut repo, "missing")
+                .unwrap_err()
+                .contains("not found")
+        );
+    }
+}
@@ -1,5 +1,9 @@
+use std::collections::HashSet;
+
 use serde::{Deserialize, Serialize};
 
+use crate::{library::ItemDetails, validation::ValidationErrors};
+
 mod api;
 mod domain;
 mod repository;
@@ -23,6 +27,24 @@ pub struct SharedChallenge {
     pub target_media: String,
     pub questions: Vec<Question>,
     pub kind: String,
+    /// Set for private challenges only
+    pub owner_user_id: Option<String>,
+    /// Whether other users can see a private challenge
+    pub visible_to_household: bool,
+}
+
+impl SharedChallenge {
+    /// Everyone can edit and take part in shared challenges, only the owner
+    /// in private ones
+    pub fn is_open_to(&self, user_id: &str) -> bool {
+        self.owner_user_id
+            .as_deref()
+            .is_none_or(|owner| owner == user_id)
+    }
+
+    pub fn is_visible_to(&self, user_id: &str) -> bool {
+        self.visible_to_household || self.is_open_to(user_id)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,7 +54,83 @@ pub struct NewSharedChallenge {
     pub status: String,
     pub target_media: String,
     pub questions: Vec<Question>,
+    /// "shared" when not given. Cannot be changed after creation.
+    pub kind: Option<String>,
+    /// Not visible when creating, unchanged when updating if not given
+    pub visible_to_household: Option<bool>,
+}
+
+pub const CHALLENGE_STATUSES: &[&str] = &["active", "inactive"];
+pub const CHALLENGE_KINDS: &[&str] = &["shared", "private"];
+pub const QUESTION_KINDS: &[&str] = &["Boolean", "TextInput"];
+
+impl NewSharedChallenge {
+    pub fn validate(&self) -> ValidationErrors {
+        let mut errors = ValidationErrors::new();
+        errors.require("name", &self.name);
+        errors.one_of("status", &self.status, CHALLENGE_STATUSES);
+        errors.one_of("targetMedia", &self.target_media, ItemDetails::KINDS);
+        errors.one_of("kind", self.kind(), CHALLENGE_KINDS);
+
+        let mut question_ids = HashSet::new();
+        for (index, question) in self.questions.iter().enumerate() {
+            let field = |name: &str| format!("questions[{}].{}", index, name);
+            errors.require(&field("id"), &question.id);
+            if !question_ids.insert(question.id.as_str()) {
+                errors.add(field("id"), "is used by another question");
+            }
+            errors.one_of(&field("kind"), &question.kind, QUESTION_KINDS);
+            errors.require(&field("question"), &question.question);
+            if question.number < 1 {
+                errors.add(field("number"), "must be positive");
+            }
+            if question.question_cluster_size < 1 {
+                errors.add(field("questionClusterSize"), "must be positive");
+            }
+        }
+        errors
+    }
+
+    pub fn kind(&self) -> &str {
+        self.kind.as_deref().unwrap_or("shared")
+    }
 }
 
 pub use api::routes;
 pub use repository::{ChallengeFilter, ChallengeRepository};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn challenge(owner_user_id: Option<&str>, visible_to_household: bool) -> SharedChallenge {
+        SharedChallenge {
+            id: "c".to_string(),
+            name: "Challenge".to_string(),
+            status: "active".to_string(),
+            target_media: "Book".to_string(),
+            questions: vec![],
+            kind: if owner_user_id.is_some() {
+                "private"
+            } else {
+                "shared"
+            }
+            .to_string(),
+            owner_user_id: owner_user_id.map(str::to_string),
+            visible_to_household,
+        }
+    }
+
+    #[test]
+    fn private_challenges_are_open_to_the_owner_only() {
+        let shared = challenge(None, false);
+        assert!(shared.is_open_to("a") && shared.is_visible_to("a"));
+
+        let private = challenge(Some("owner"), false);
+        assert!(private.is_open_to("owner"));
+        assert!(!private.is_open_to("a") && !private.is_visible_to("a"));
+
+        let household = challenge(Some("owner"), true);
+        assert!(!household.is_open_to("a") && household.is_visible_to("a"));
+    }
+}
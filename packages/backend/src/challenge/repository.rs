@@ -3,14 +3,20 @@ use rusqlite::Transaction;
 use crate::challenge::{Question, SharedChallenge};
 use crate::database::{Database, Repository, query_in_transation, query_singe_in_transation};
 
+/// Shared challenges and the user's own private ones, optionally with the
+/// private challenges other users have made visible to the household
 pub struct ChallengeFilter {
+    pub user_id: String,
+    pub include_household: bool,
     pub status: Option<String>,
     pub media_type: Option<String>,
 }
 
 impl ChallengeFilter {
-    pub fn new() -> Self {
+    pub fn new(user_id: &str) -> Self {
         ChallengeFilter {
+            user_id: user_id.to_string(),
+            include_household: false,
             status: None,
             media_type: None,
         }
@@ -35,13 +41,16 @@ impl Repository<SharedChallenge, ChallengeFilter> for ChallengeRepository {
         let tx = self.transaction()?;
 
         tx.execute(
-            "INSERT INTO challenge (id, name, status, target_media, kind) VALUES (?1, ?2, ?3, ?4, ?5)",
+            "INSERT INTO challenge (id, name, status, target_media, kind, owner_user_id, visible_to_household)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
             rusqlite::params![
                 challenge.id,
                 challenge.name,
                 challenge.status,
                 challenge.target_media,
                 challenge.kind,
+                challenge.owner_user_id,
+                challenge.visible_to_household,
             ],
         )?;
 
@@ -67,7 +76,9 @@ impl Repository<SharedChallenge, ChallengeFilter> for ChallengeRepository {
     fn read_by_id(&mut self, id: &str) -> rusqlite::Result<Option<SharedChallenge>> {
         let transaction = self.transaction()?;
 
-        let query = "SELECT id, name, status, target_media, kind FROM challenge WHERE id = ?1";
+        let query =
+            "SELECT id, name, status, target_media, kind, owner_user_id, visible_to_household
+            FROM challenge WHERE id = ?1";
         let params = rusqlite::params![id];
         let challenge = query_singe_in_transation(&transaction, query, params, challenge_from_row)?;
 
@@ -89,7 +100,7 @@ impl Repository<SharedChallenge, ChallengeFilter> for ChallengeRepository {
         let (where_clause, params) = to_sql_params(&filter);
         let query = format!(
             "
-            SELECT id, name, status, target_media, kind 
+            SELECT id, name, status, target_media, kind, owner_user_id, visible_to_household
             FROM challenge
             WHERE {}",
             where_clause
@@ -111,14 +122,16 @@ impl Repository<SharedChallenge, ChallengeFilter> for ChallengeRepository {
         let tx = self.transaction()?;
 
         let rows_affected = tx.execute(
-            "UPDATE challenge SET name = ?2, status = ?3, target_media = ?4, kind = ?5 
+            "UPDATE challenge SET name = ?2, status = ?3, target_media = ?4, kind = ?5,
+                visible_to_household = ?6
                 WHERE id = ?1",
             rusqlite::params![
                 id,
                 challenge.name,
                 challenge.status,
                 challenge.target_media,
-                challenge.kind
+                challenge.kind,
+                challenge.visible_to_household
             ],
         )?;
 
@@ -179,13 +192,19 @@ fn challenge_from_row(row: &rusqlite::Row) -> rusqlite::Result<SharedChallenge>
         status: row.get(2)?,
         target_media: row.get(3)?,
         kind: row.get(4)?,
+        owner_user_id: row.get(5)?,
+        visible_to_household: row.get(6)?,
         questions: Vec::new(), // Will be populated separately
     })
 }
 
-fn to_sql_params<'a>(filter: &'a ChallengeFilter) -> (String, Vec<&'a dyn rusqlite::ToSql>) {
-    let mut params: Vec<&dyn rusqlite::ToSql> = Vec::new();
-    let mut conditions: Vec<String> = Vec::new();
+fn to_sql_params(filter: &ChallengeFilter) -> (String, Vec<&dyn rusqlite::ToSql>) {
+    let mut params: Vec<&dyn rusqlite::ToSql> = vec![&filter.user_id];
+    let mut conditions: Vec<String> = vec![if filter.include_household {
+        "(owner_user_id IS NULL OR owner_user_id = ? OR visible_to_household = 1)".to_string()
+    } else {
+        "(owner_user_id IS NULL OR owner_user_id = ?)".to_string()
+    }];
 
     if let Some(media_type) = &filter.media_type {
         conditions.push("target_media = ?".to_string());
@@ -196,10 +215,6 @@ fn to_sql_params<'a>(filter: &'a ChallengeFilter) -> (String, Vec<&'a dyn rusqli
         params.push(status);
     }
 
-    if conditions.len() == 0 {
-        return (" 1 = 1 ".to_string(), Vec::new());
-    }
-
     let conditions = conditions.join(" AND ");
 
     (conditions, params)
@@ -212,7 +227,7 @@ fn read_questions_for_challenge_id(
     let query = "SELECT id, kind, question, number, question_cluster_size 
                      FROM question WHERE challenge_id = ?1";
 
-    let questions = query_in_transation(&tx, query, rusqlite::params![challenge_id], |row| {
+    let questions = query_in_transation(tx, query, rusqlite::params![challenge_id], |row| {
         Ok(Question {
             id: row.get(0)?,
             kind: row.get(1)?,
@@ -224,3 +239,80 @@ fn read_questions_for_challenge_id(
 
     Ok(questions)
 }
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+    use crate::database::migrated_test_database;
+
+    fn challenge(id: &str, owner_user_id: Option<&str>, visible: bool) -> SharedChallenge {
+        SharedChallenge {
+            id: id.to_string(),
+            name: id.to_string(),
+            status: "active".to_string(),
+            target_media: "Book".to_string(),
+            questions: vec![],
+            kind: if owner_user_id.is_some() {
+                "private"
+            } else {
+                "shared"
+            }
+            .to_string(),
+            owner_user_id: owner_user_id.map(str::to_string),
+            visible_to_household: visible,
+        }
+    }
+
+    /// Repository over a migrated database holding a shared challenge, a
+    /// private challenge of "me" and of "other", and a private challenge of
+    /// "other" visible to the household
+    pub fn repository(dir: &tempfile::TempDir) -> ChallengeRepository {
+        let db_path = migrated_test_database(dir);
+        let mut repo = ChallengeRepository::new(Database::new(&db_path).unwrap());
+        for challenge in [
+            challenge("shared", None, false),
+            challenge("own", Some("me"), false),
+            challenge("other", Some("other"), false),
+            challenge("household", Some("other"), true),
+        ] {
+            repo.create(&challenge).unwrap();
+        }
+        repo
+    }
+
+    fn search_ids(repo: &mut ChallengeRepository, filter: ChallengeFilter) -> Vec<String> {
+        let mut ids: Vec<String> = repo
+            .search(filter)
+            .unwrap()
+            .into_iter()
+            .map(|challenge| challenge.id)
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn filters_private_challenges_of_other_users() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut repo = repository(&dir);
+
+        assert_eq!(
+            search_ids(&mut repo, ChallengeFilter::new("me")),
+            vec!["own", "shared"]
+        );
+        assert_eq!(
+            search_ids(
+                &mut repo,
+                ChallengeFilter {
+                    include_household: true,
+                    ..ChallengeFilter::new("me")
+                }
+            ),
+            vec!["household", "own", "shared"]
+        );
+        assert_eq!(
+            search_ids(&mut repo, ChallengeFilter::new("other")),
+            vec!["household", "other", "shared"]
+        );
+    }
+}
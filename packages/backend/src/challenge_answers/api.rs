@@ -15,6 +15,7 @@ use crate::{
         domain::{get_challenge_answers, upsert_answers},
     },
     utils::map_to_internal_error,
+    validation::{ApiError, ValidJson, map_validation_error},
 };
 
 pub fn routes() -> Router<AppState> {
@@ -97,8 +98,8 @@ async fn upsert_answers_route(
     user: User,
     State(state): State<AppState>,
     Path((item_id, challenge_id)): Path<(Uuid, Uuid)>,
-    Json(answer_list): Json<AnswersList>,
-) -> Result<Json<AnswersList>, StatusCode> {
+    ValidJson(answer_list): ValidJson<AnswersList>,
+) -> Result<Json<AnswersList>, ApiError> {
     let domain_awnsers: Vec<Answer> = answer_list
         .answers
         .iter()
@@ -111,13 +112,21 @@ async fn upsert_answers_route(
         &challenge_id.to_string(),
         &domain_awnsers,
     )
-    .map_err(|err| map_to_internal_error(Box::new(err)))?;
+    .map_err(|err| map_validation_error(err, map_answer_error))?;
 
     Ok(Json(AnswersList {
         answers: convert_to_api_answers(answers),
     }))
 }
 
+fn map_answer_error(err: Box<dyn std::error::Error>) -> StatusCode {
+    if err.to_string().contains("not found") {
+        StatusCode::NOT_FOUND
+    } else {
+        map_to_internal_error(err)
+    }
+}
+
 fn convert_to_api_answers(answers: Vec<Answer>) -> Vec<ApiAnswer> {
     answers.into_iter().map(|a| ApiAnswer::from(&a)).collect()
 }
@@ -1,9 +1,11 @@
 use crate::{
     AppState,
     auth::User,
+    challenge::{ChallengeRepository, QUESTION_KINDS, SharedChallenge},
     challenge_answers::repository::ChallengeAnswerRepository,
     database::{Database, Repository},
     library::LibraryRepository,
+    validation::ValidationErrors,
 };
 use rusqlite::Result;
 use std::collections::HashSet;
@@ -25,18 +27,16 @@ impl AnswerFilter<'_> {
     }
 
     pub fn with_item_id<'a>(&'a self, item_id: &'a str) -> AnswerFilter<'a> {
-        let new = AnswerFilter {
+        AnswerFilter {
             item_id: Some(item_id),
             ..self.clone()
-        };
-        new
+        }
     }
     pub fn with_challenge_id<'a>(&'a self, challenge_id: &'a str) -> AnswerFilter<'a> {
-        let new = AnswerFilter {
+        AnswerFilter {
             challenge_id: Some(challenge_id),
             ..self.clone()
-        };
-        new
+        }
     }
 }
 
@@ -68,23 +68,26 @@ pub fn upsert_answers(
     item_id: &str,
     challenge_id: &str,
     answer_set: &[Answer],
-) -> Result<Vec<Answer>> {
+) -> std::result::Result<Vec<Answer>, Box<dyn std::error::Error>> {
     // These should most likely use the same db
     let db = Database::new(&state.database_path)?;
     let mut repo = ChallengeAnswerRepository::new(db);
     let mut library_repo = LibraryRepository::new(Database::new(&state.database_path)?);
-    if let Some(item) = library_repo.read_by_id(item_id)? {
-        if item.user_id != user.id {
-            return Err(rusqlite::Error::InvalidQuery); // Unauthorized
-        }
-    } else {
-        return Err(rusqlite::Error::QueryReturnedNoRows); // Item not found
+    match library_repo.read_by_id(item_id)? {
+        Some(item) if item.user_id == user.id => {}
+        _ => return Err("Library item not found".into()),
     }
+    let mut challenge_repo = ChallengeRepository::new(Database::new(&state.database_path)?);
+    let challenge = challenge_repo
+        .read_by_id(challenge_id)?
+        .filter(|challenge| challenge.is_open_to(&user.id))
+        .ok_or("Challenge not found")?;
+    validate_answers(&challenge, answer_set)?;
 
     let current_answers = repo.search(
         AnswerFilter::new(&user.id)
-            .with_item_id(&item_id)
-            .with_challenge_id(&challenge_id),
+            .with_item_id(item_id)
+            .with_challenge_id(challenge_id),
     )?;
 
     let current_answer_ids: HashSet<String> = current_answers.into_iter().map(|a| a.id).collect();
@@ -111,10 +114,40 @@ pub fn upsert_answers(
         repo.update(&answer.id, &answer)?;
     }
 
-    let result = repo.search(AnswerFilter::new(&user.id).with_item_id(&item_id))?;
+    let result = repo.search(AnswerFilter::new(&user.id).with_item_id(item_id))?;
     Ok(result)
 }
 
+/// Answers must be to questions of the challenge and of the same kind
+fn validate_answers(
+    challenge: &SharedChallenge,
+    answers: &[Answer],
+) -> std::result::Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::new();
+    for (index, answer) in answers.iter().enumerate() {
+        let field = |name: &str| format!("answers[{}].{}", index, name);
+        errors.one_of(&field("kind"), &answer.kind, QUESTION_KINDS);
+        match challenge
+            .questions
+            .iter()
+            .find(|question| question.id == answer.question_id)
+        {
+            Some(question)
+                if question.kind != answer.kind
+                    && QUESTION_KINDS.contains(&answer.kind.as_str()) =>
+            {
+                errors.add(
+                    field("kind"),
+                    format!("must be {} like the question", question.kind),
+                )
+            }
+            Some(_) => {}
+            None => errors.add(field("questionId"), "is not a question of the challenge"),
+        }
+    }
+    errors.into_result()
+}
+
 pub fn get_challenge_answers(database_path: &str, filter: AnswerFilter) -> Result<Vec<Answer>> {
     let db = Database::new(database_path)?;
     let mut repo = ChallengeAnswerRepository::new(db);
@@ -3,4 +3,4 @@ mod domain;
 mod repository;
 
 pub use api::routes;
-pub use domain::{Answer, AnswerFilter};
+pub use domain::{Answer, AnswerFilter, get_challenge_answers};
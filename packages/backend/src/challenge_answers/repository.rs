@@ -52,9 +52,7 @@ impl Repository<Answer, super::domain::AnswerFilter<'_>> for ChallengeAnswerRepo
             FROM answer 
             WHERE id = ?";
         let connection = self.conn();
-        let answer = connection
-            .query_row(sql, &[&id], row_to_answer)
-            .optional()?;
+        let answer = connection.query_row(sql, [&id], row_to_answer).optional()?;
         Ok(answer)
     }
 
@@ -103,7 +101,7 @@ impl Repository<Answer, super::domain::AnswerFilter<'_>> for ChallengeAnswerRepo
     fn delete(&mut self, id: &str) -> Result<bool> {
         let sql = "DELETE FROM answer WHERE id = ?";
         let tx = self.transaction()?;
-        let result = tx.execute(&sql, &[&id])?;
+        let result = tx.execute(sql, [&id])?;
         if result == 1 {
             tx.commit()?;
         }
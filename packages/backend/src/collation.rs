@@ -0,0 +1,57 @@
+use std::cmp::Ordering;
+
+/// Name of the collation registered on every connection by `Database::new`.
+pub const FINNISH: &str = "FINNISH";
+
+/// Compares strings using Finnish alphabetical order: case-insensitive,
+/// with å, ä and ö sorted after z. Other accented latin letters sort with
+/// their base letter. Ties are broken by plain byte order so that the
+/// ordering is total.
+pub fn finnish_cmp(a: &str, b: &str) -> Ordering {
+    let primary = a.chars().map(sort_key).cmp(b.chars().map(sort_key));
+    primary.then_with(|| a.cmp(b))
+}
+
+fn sort_key(c: char) -> u32 {
+    let lower = c.to_lowercase().next().unwrap_or(c);
+    let base = match lower {
+        'å' => return 'z' as u32 + 1,
+        'ä' | 'æ' => return 'z' as u32 + 2,
+        'ö' | 'ø' | 'œ' => return 'z' as u32 + 3,
+        'á' | 'à' | 'â' | 'ã' => 'a',
+        'ç' | 'č' => 'c',
+        'é' | 'è' | 'ê' | 'ë' => 'e',
+        'í' | 'ì' | 'î' | 'ï' => 'i',
+        'ñ' => 'n',
+        'ó' | 'ò' | 'ô' | 'õ' => 'o',
+        'š' => 's',
+        'ú' | 'ù' | 'û' => 'u',
+        'ü' | 'ý' => 'y',
+        'ž' => 'z',
+        other => other,
+    };
+    base as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorts_scandinavian_letters_after_z() {
+        let mut words = vec!["Öljy", "Äiti", "Zeta", "Åbo", "aamu", "Väinö", "Vaino"];
+        words.sort_by(|a, b| finnish_cmp(a, b));
+
+        assert_eq!(
+            words,
+            vec!["aamu", "Vaino", "Väinö", "Zeta", "Åbo", "Äiti", "Öljy"]
+        );
+    }
+
+    #[test]
+    fn ignores_case_before_tiebreak() {
+        assert_eq!(finnish_cmp("b", "A"), Ordering::Greater);
+        assert_eq!(finnish_cmp("abc", "ABC"), Ordering::Greater);
+        assert_eq!(finnish_cmp("sama", "sama"), Ordering::Equal);
+    }
+}
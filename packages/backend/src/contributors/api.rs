@@ -0,0 +1,108 @@
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{get, post, put},
+};
+use serde::Deserialize;
+
+use crate::{
+    AppState,
+    auth::User,
+    contributors::{
+        Contributor, ItemContributor, NewContributor, NewItemContributor,
+        domain::{
+            get_contributor_items, get_contributors, get_item_contributors, merge_contributors,
+            rename_contributor, set_item_contributors,
+        },
+    },
+    library::LibraryItem,
+    utils::map_to_internal_error,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/contributors", get(get_all_contributors))
+        .route("/contributors/{id}", put(rename_existing_contributor))
+        .route("/contributors/{id}/merge", post(merge_contributor))
+        .route("/contributors/{id}/items", get(get_items_by_contributor))
+        .route("/library/{id}/contributors", get(get_contributors_of_item))
+        .route("/library/{id}/contributors", put(set_contributors_of_item))
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct MergeRequest {
+    target_contributor_id: String,
+}
+
+fn map_contributor_error(err: Box<dyn std::error::Error>) -> StatusCode {
+    let message = err.to_string();
+    if message.contains("not found") {
+        StatusCode::NOT_FOUND
+    } else if message.contains("already exists") {
+        StatusCode::CONFLICT
+    } else if message.contains("Invalid") {
+        StatusCode::UNPROCESSABLE_ENTITY
+    } else {
+        map_to_internal_error(err)
+    }
+}
+
+async fn get_all_contributors(
+    State(state): State<AppState>,
+    user: User,
+) -> Result<Json<Vec<Contributor>>, StatusCode> {
+    let contributors = get_contributors(&user, &state).map_err(map_to_internal_error)?;
+    Ok(Json(contributors))
+}
+
+async fn rename_existing_contributor(
+    State(state): State<AppState>,
+    user: User,
+    Path(id): Path<String>,
+    Json(contributor): Json<NewContributor>,
+) -> Result<StatusCode, StatusCode> {
+    rename_contributor(&user, &state, &id, &contributor).map_err(map_contributor_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn merge_contributor(
+    State(state): State<AppState>,
+    user: User,
+    Path(id): Path<String>,
+    Json(request): Json<MergeRequest>,
+) -> Result<Json<Contributor>, StatusCode> {
+    let contributor = merge_contributors(&user, &state, &id, &request.target_contributor_id)
+        .map_err(map_contributor_error)?;
+    Ok(Json(contributor))
+}
+
+async fn get_items_by_contributor(
+    State(state): State<AppState>,
+    user: User,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<LibraryItem>>, StatusCode> {
+    let items = get_contributor_items(&user, &state, &id).map_err(map_contributor_error)?;
+    Ok(Json(items))
+}
+
+async fn get_contributors_of_item(
+    State(state): State<AppState>,
+    user: User,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<ItemContributor>>, StatusCode> {
+    let contributors = get_item_contributors(&user, &state, &id).map_err(map_contributor_error)?;
+    Ok(Json(contributors))
+}
+
+async fn set_contributors_of_item(
+    State(state): State<AppState>,
+    user: User,
+    Path(id): Path<String>,
+    Json(contributors): Json<Vec<NewItemContributor>>,
+) -> Result<Json<Vec<ItemContributor>>, StatusCode> {
+    let contributors =
+        set_item_contributors(&user, &state, &id, &contributors).map_err(map_contributor_error)?;
+    Ok(Json(contributors))
+}
@@ -0,0 +1,162 @@
+use crate::{
+    AppState,
+    auth::User,
+    contributors::{
+        Contributor, ItemContributor, NewContributor, NewItemContributor,
+        normalize_contributor_name,
+        repository::{ContributorFilter, ContributorRepository},
+    },
+    database::{Database, Repository},
+    library::{LibraryFilter, LibraryItem, LibraryRepository},
+};
+
+pub fn get_contributors(
+    user: &User,
+    state: &AppState,
+) -> Result<Vec<Contributor>, Box<dyn std::error::Error>> {
+    let db = Database::new(&state.database_path)?;
+    let mut repo = ContributorRepository::new(db);
+    Ok(repo.search(ContributorFilter::new(&user.id))?)
+}
+
+/// Every item the person is credited on, in any role
+pub fn get_contributor_items(
+    user: &User,
+    state: &AppState,
+    id: &str,
+) -> Result<Vec<LibraryItem>, Box<dyn std::error::Error>> {
+    let db = Database::new(&state.database_path)?;
+    let mut repo = ContributorRepository::new(db);
+    read_owned_contributor(&mut repo, user, id)?;
+
+    let mut library_repo = LibraryRepository::new(Database::new(&state.database_path)?);
+    let filter = LibraryFilter {
+        contributor_id: Some(id.to_string()),
+        ..LibraryFilter::new(&user.id)
+    };
+    Ok(library_repo.search(filter)?)
+}
+
+pub fn rename_contributor(
+    user: &User,
+    state: &AppState,
+    id: &str,
+    contributor: &NewContributor,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db = Database::new(&state.database_path)?;
+    let mut repo = ContributorRepository::new(db);
+    let existing = read_owned_contributor(&mut repo, user, id)?;
+    ensure_name_available(&mut repo, user, &contributor.name, Some(id))?;
+
+    repo.update(
+        id,
+        &Contributor {
+            name: contributor.name.clone(),
+            ..existing
+        },
+    )?;
+    Ok(())
+}
+
+/// Credits the items of `source_id` to `target_id` and removes the source,
+/// unifying a person whose name was spelled two ways
+pub fn merge_contributors(
+    user: &User,
+    state: &AppState,
+    source_id: &str,
+    target_id: &str,
+) -> Result<Contributor, Box<dyn std::error::Error>> {
+    if source_id == target_id {
+        return Err("Invalid merge: a contributor cannot be merged into itself".into());
+    }
+
+    let db = Database::new(&state.database_path)?;
+    let mut repo = ContributorRepository::new(db);
+    read_owned_contributor(&mut repo, user, source_id)?;
+    read_owned_contributor(&mut repo, user, target_id)?;
+
+    repo.merge(source_id, target_id)?;
+    repo.read_by_id(target_id)?
+        .ok_or_else(|| "Contributor not found".into())
+}
+
+pub fn get_item_contributors(
+    user: &User,
+    state: &AppState,
+    item_id: &str,
+) -> Result<Vec<ItemContributor>, Box<dyn std::error::Error>> {
+    read_owned_item(user, state, item_id)?;
+    let db = Database::new(&state.database_path)?;
+    let mut repo = ContributorRepository::new(db);
+    Ok(repo.item_contributors(item_id)?)
+}
+
+pub fn set_item_contributors(
+    user: &User,
+    state: &AppState,
+    item_id: &str,
+    contributors: &[NewItemContributor],
+) -> Result<Vec<ItemContributor>, Box<dyn std::error::Error>> {
+    if contributors
+        .iter()
+        .any(|c| normalize_contributor_name(&c.name).is_empty())
+    {
+        return Err("Invalid contributor name".into());
+    }
+    read_owned_item(user, state, item_id)?;
+
+    let db = Database::new(&state.database_path)?;
+    let mut repo = ContributorRepository::new(db);
+    repo.set_item_contributors(item_id, &user.id, contributors)?;
+    Ok(repo.item_contributors(item_id)?)
+}
+
+fn read_owned_item(
+    user: &User,
+    state: &AppState,
+    item_id: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut repo = LibraryRepository::new(Database::new(&state.database_path)?);
+    match repo.read_by_id(item_id)? {
+        Some(item) if item.user_id == user.id => Ok(()),
+        _ => Err("Library item not found".into()),
+    }
+}
+
+fn read_owned_contributor(
+    repo: &mut ContributorRepository,
+    user: &User,
+    id: &str,
+) -> Result<Contributor, Box<dyn std::error::Error>> {
+    match repo.read_by_id(id)? {
+        Some(contributor) if contributor.user_id == user.id => Ok(contributor),
+        _ => Err("Contributor not found".into()),
+    }
+}
+
+fn ensure_name_available(
+    repo: &mut ContributorRepository,
+    user: &User,
+    name: &str,
+    own_id: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let normalized_name = normalize_contributor_name(name);
+    if normalized_name.is_empty() {
+        return Err("Invalid contributor name".into());
+    }
+
+    let filter = ContributorFilter {
+        normalized_name: Some(normalized_name),
+        ..ContributorFilter::new(&user.id)
+    };
+    let taken = repo
+        .search(filter)?
+        .into_iter()
+        .any(|contributor| Some(contributor.id.as_str()) != own_id);
+
+    if taken {
+        Err("Contributor already exists, merge the contributors instead".into())
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+
+mod api;
+mod domain;
+mod repository;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Contributor {
+    pub id: String,
+    pub user_id: String,
+    pub name: String,
+    pub item_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewContributor {
+    pub name: String,
+}
+
+/// A contributor credited on an item
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemContributor {
+    pub contributor_id: String,
+    pub name: String,
+    pub role: ContributorRole,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewItemContributor {
+    pub name: String,
+    pub role: ContributorRole,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ContributorRole {
+    Author,
+    Translator,
+    Illustrator,
+    Narrator,
+    Developer,
+    Director,
+    Designer,
+    Creator,
+}
+
+impl ContributorRole {
+    /// Roles shown through the legacy `author` field, one for each kind
+    pub const CREATORS: &'static [ContributorRole] = &[
+        ContributorRole::Author,
+        ContributorRole::Developer,
+        ContributorRole::Director,
+        ContributorRole::Designer,
+        ContributorRole::Creator,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContributorRole::Author => "author",
+            ContributorRole::Translator => "translator",
+            ContributorRole::Illustrator => "illustrator",
+            ContributorRole::Narrator => "narrator",
+            ContributorRole::Developer => "developer",
+            ContributorRole::Director => "director",
+            ContributorRole::Designer => "designer",
+            ContributorRole::Creator => "creator",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "author" => Some(ContributorRole::Author),
+            "translator" => Some(ContributorRole::Translator),
+            "illustrator" => Some(ContributorRole::Illustrator),
+            "narrator" => Some(ContributorRole::Narrator),
+            "developer" => Some(ContributorRole::Developer),
+            "director" => Some(ContributorRole::Director),
+            "designer" => Some(ContributorRole::Designer),
+            "creator" => Some(ContributorRole::Creator),
+            _ => None,
+        }
+    }
+
+    /// Role of the person in the `author` column of an item of the kind
+    pub fn creator_of(kind: &str) -> Self {
+        match kind {
+            "Book" | "Audiobook" => ContributorRole::Author,
+            "Game" => ContributorRole::Developer,
+            "Movie" => ContributorRole::Director,
+            "BoardGame" => ContributorRole::Designer,
+            _ => ContributorRole::Creator,
+        }
+    }
+}
+
+/// Contributor names are unique per user regardless of case
+pub fn normalize_contributor_name(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+pub use api::routes;
+pub use repository::{delete_unused_contributors, refresh_item_names, sync_legacy_contributors};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn creator_role_follows_kind() {
+        assert_eq!(ContributorRole::creator_of("Book"), ContributorRole::Author);
+        assert_eq!(
+            ContributorRole::creator_of("Game"),
+            ContributorRole::Developer
+        );
+        assert_eq!(
+            ContributorRole::creator_of("Series"),
+            ContributorRole::Creator
+        );
+        assert!(!ContributorRole::CREATORS.contains(&ContributorRole::Narrator));
+        for role in ContributorRole::CREATORS {
+            assert_eq!(ContributorRole::parse(role.as_str()), Some(*role));
+        }
+    }
+}
@@ -0,0 +1,419 @@
+use rusqlite::{Connection, OptionalExtension, Result};
+
+use crate::contributors::{
+    Contributor, ContributorRole, ItemContributor, NewItemContributor, normalize_contributor_name,
+};
+use crate::database::{Database, Repository, query_in_transation};
+use crate::library::ItemDetails;
+
+pub struct ContributorFilter {
+    pub user_id: String,
+    pub normalized_name: Option<String>,
+}
+
+impl ContributorFilter {
+    pub fn new(user_id: &str) -> Self {
+        ContributorFilter {
+            user_id: user_id.to_string(),
+            normalized_name: None,
+        }
+    }
+}
+
+pub struct ContributorRepository {
+    db: Database,
+}
+
+impl ContributorRepository {
+    pub fn new(db: Database) -> Self {
+        ContributorRepository { db }
+    }
+
+    pub fn item_contributors(&mut self, item_id: &str) -> Result<Vec<ItemContributor>> {
+        let tx = self.transaction()?;
+        let contributors = query_in_transation(
+            &tx,
+            "SELECT c.id, c.name, ic.role FROM item_contributor ic
+                JOIN contributor c ON c.id = ic.contributor_id
+                WHERE ic.item_id = ?
+                ORDER BY ic.position, c.name COLLATE FINNISH",
+            &[&item_id],
+            |row| {
+                let role: String = row.get(2)?;
+                Ok(ItemContributor {
+                    contributor_id: row.get(0)?,
+                    name: row.get(1)?,
+                    role: ContributorRole::parse(&role).unwrap_or(ContributorRole::Creator),
+                })
+            },
+        )?;
+        tx.commit()?;
+        Ok(contributors)
+    }
+
+    /// Replaces the contributors of the item, creating missing contributors,
+    /// and rewrites the derived author and translator
+    pub fn set_item_contributors(
+        &mut self,
+        item_id: &str,
+        user_id: &str,
+        contributors: &[NewItemContributor],
+    ) -> Result<()> {
+        let tx = self.transaction()?;
+        tx.execute("DELETE FROM item_contributor WHERE item_id = ?", [item_id])?;
+        for (position, contributor) in contributors.iter().enumerate() {
+            let contributor_id = ensure_contributor(&tx, user_id, &contributor.name)?;
+            link_contributor(&tx, item_id, &contributor_id, contributor.role, position)?;
+        }
+        refresh_item_names(&tx, item_id)?;
+        delete_unused_contributors(&tx, user_id)?;
+        tx.commit()
+    }
+
+    /// Moves every item of `source_id` to `target_id`, deletes the source
+    /// contributor and rewrites the derived names of the affected items
+    pub fn merge(&mut self, source_id: &str, target_id: &str) -> Result<()> {
+        let tx = self.transaction()?;
+        tx.execute(
+            "INSERT OR IGNORE INTO item_contributor (item_id, contributor_id, role, position)
+                SELECT item_id, ?2, role, position FROM item_contributor WHERE contributor_id = ?1",
+            [source_id, target_id],
+        )?;
+        tx.execute("DELETE FROM contributor WHERE id = ?", [source_id])?;
+        refresh_contributor_items(&tx, target_id)?;
+        tx.commit()
+    }
+}
+
+/// Id of the user's contributor with the name, creating it when missing
+pub fn ensure_contributor(conn: &Connection, user_id: &str, name: &str) -> Result<String> {
+    let normalized_name = normalize_contributor_name(name);
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT id FROM contributor WHERE user_id = ? AND normalized_name = ?",
+            [user_id, &normalized_name],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+
+    let id = uuid::Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT INTO contributor (id, user_id, name, normalized_name) VALUES (?, ?, ?, ?)",
+        [&id, user_id, name.trim(), &normalized_name],
+    )?;
+    Ok(id)
+}
+
+/// Keeps the contributors in line with the legacy `author`, `translator`
+/// and audiobook `narrator` fields written through the item endpoints and
+/// importers. Contributors of a role are only replaced when the field no
+/// longer matches the names derived from them, so multiple authors set
+/// through the contributor endpoints survive an unchanged item update.
+/// Comma separated names become one contributor each.
+pub fn sync_legacy_contributors(
+    conn: &Connection,
+    item_id: &str,
+    user_id: &str,
+    details: &ItemDetails,
+) -> Result<()> {
+    let creator_role = ContributorRole::creator_of(details.kind());
+    let mut fields = vec![(ContributorRole::CREATORS, creator_role, details.creator())];
+    match details {
+        ItemDetails::Book { translator, .. } => {
+            fields.push((
+                TRANSLATORS,
+                ContributorRole::Translator,
+                name_or_empty(translator),
+            ));
+        }
+        ItemDetails::Audiobook {
+            translator,
+            narrator,
+            ..
+        } => {
+            fields.push((
+                TRANSLATORS,
+                ContributorRole::Translator,
+                name_or_empty(translator),
+            ));
+            fields.push((
+                NARRATORS,
+                ContributorRole::Narrator,
+                name_or_empty(narrator),
+            ));
+        }
+        _ => {}
+    }
+
+    for (roles, role, name) in fields {
+        let name = name.trim();
+        if joined_names(conn, item_id, roles)? != name {
+            replace_role(conn, item_id, user_id, roles, role, name)?;
+        }
+    }
+
+    delete_unused_contributors(conn, user_id)
+}
+
+const TRANSLATORS: &[ContributorRole] = &[ContributorRole::Translator];
+const NARRATORS: &[ContributorRole] = &[ContributorRole::Narrator];
+
+fn name_or_empty(name: &Option<String>) -> &str {
+    name.as_deref().unwrap_or_default()
+}
+
+fn replace_role(
+    conn: &Connection,
+    item_id: &str,
+    user_id: &str,
+    replaced: &[ContributorRole],
+    role: ContributorRole,
+    name: &str,
+) -> Result<()> {
+    let roles: Vec<String> = replaced
+        .iter()
+        .map(|r| format!("'{}'", r.as_str()))
+        .collect();
+    conn.execute(
+        &format!(
+            "DELETE FROM item_contributor WHERE item_id = ? AND role IN ({})",
+            roles.join(", ")
+        ),
+        [item_id],
+    )?;
+    let names = name
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty());
+    for (position, name) in names.enumerate() {
+        let contributor_id = ensure_contributor(conn, user_id, name)?;
+        link_contributor(conn, item_id, &contributor_id, role, position)?;
+    }
+    Ok(())
+}
+
+fn link_contributor(
+    conn: &Connection,
+    item_id: &str,
+    contributor_id: &str,
+    role: ContributorRole,
+    position: usize,
+) -> Result<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO item_contributor (item_id, contributor_id, role, position)
+            VALUES (?, ?, ?, ?)",
+        rusqlite::params![item_id, contributor_id, role.as_str(), position as i64],
+    )?;
+    Ok(())
+}
+
+/// Names with any of the roles in credit order, joined with commas
+fn joined_names(conn: &Connection, item_id: &str, roles: &[ContributorRole]) -> Result<String> {
+    let roles: Vec<String> = roles.iter().map(|r| format!("'{}'", r.as_str())).collect();
+    let sql = format!(
+        "SELECT c.name FROM item_contributor ic
+            JOIN contributor c ON c.id = ic.contributor_id
+            WHERE ic.item_id = ? AND ic.role IN ({})
+            ORDER BY ic.position, c.name",
+        roles.join(", ")
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let names = stmt
+        .query_map([item_id], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(names.join(", "))
+}
+
+/// Rewrites library.author, library.translator and the audiobook narrator
+/// from the contributors
+pub fn refresh_item_names(conn: &Connection, item_id: &str) -> Result<()> {
+    let author = joined_names(conn, item_id, ContributorRole::CREATORS)?;
+    let translator = joined_names(conn, item_id, TRANSLATORS)?;
+    let narrator = joined_names(conn, item_id, NARRATORS)?;
+    conn.execute(
+        "UPDATE library SET author = ?, translator = ? WHERE id = ?",
+        rusqlite::params![author, Some(translator).filter(|t| !t.is_empty()), item_id],
+    )?;
+    conn.execute(
+        "UPDATE library_audiobook SET narrator = ? WHERE item_id = ?",
+        rusqlite::params![Some(narrator).filter(|n| !n.is_empty()), item_id],
+    )?;
+    Ok(())
+}
+
+fn refresh_contributor_items(conn: &Connection, contributor_id: &str) -> Result<()> {
+    let mut stmt =
+        conn.prepare("SELECT DISTINCT item_id FROM item_contributor WHERE contributor_id = ?")?;
+    let item_ids = stmt
+        .query_map([contributor_id], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>>>()?;
+    for item_id in item_ids {
+        refresh_item_names(conn, &item_id)?;
+    }
+    Ok(())
+}
+
+/// Contributors are only reachable through items, so unlinked ones are
+/// removed instead of piling up after every corrected typo
+pub fn delete_unused_contributors(conn: &Connection, user_id: &str) -> Result<()> {
+    conn.execute(
+        "DELETE FROM contributor WHERE user_id = ?
+            AND NOT EXISTS (SELECT 1 FROM item_contributor ic WHERE ic.contributor_id = contributor.id)",
+        [user_id],
+    )?;
+    Ok(())
+}
+
+impl Repository<Contributor, ContributorFilter> for ContributorRepository {
+    fn conn(&mut self) -> &mut rusqlite::Connection {
+        &mut self.db.conn
+    }
+
+    fn create(&mut self, contributor: &Contributor) -> Result<String> {
+        let tx = self.transaction()?;
+        tx.execute(
+            "INSERT INTO contributor (id, user_id, name, normalized_name) VALUES (?, ?, ?, ?)",
+            rusqlite::params![
+                contributor.id,
+                contributor.user_id,
+                contributor.name.trim(),
+                normalize_contributor_name(&contributor.name)
+            ],
+        )?;
+        tx.commit()?;
+        Ok(contributor.id.clone())
+    }
+
+    fn read_by_id(&mut self, id: &str) -> Result<Option<Contributor>> {
+        let sql = "SELECT c.id, c.user_id, c.name,
+                (SELECT COUNT(DISTINCT ic.item_id) FROM item_contributor ic WHERE ic.contributor_id = c.id)
+            FROM contributor c
+            WHERE c.id = ?";
+        self.conn()
+            .query_row(sql, [id], row_to_contributor)
+            .optional()
+    }
+
+    fn search(&mut self, filter: ContributorFilter) -> Result<Vec<Contributor>> {
+        let mut conditions = vec!["c.user_id = ?"];
+        let mut params: Vec<&dyn rusqlite::ToSql> = vec![&filter.user_id];
+        if let Some(normalized_name) = &filter.normalized_name {
+            conditions.push("c.normalized_name = ?");
+            params.push(normalized_name);
+        }
+
+        let sql = format!(
+            "SELECT c.id, c.user_id, c.name,
+                (SELECT COUNT(DISTINCT ic.item_id) FROM item_contributor ic WHERE ic.contributor_id = c.id)
+            FROM contributor c
+            WHERE {}
+            ORDER BY c.name COLLATE FINNISH",
+            conditions.join(" AND ")
+        );
+
+        let tx = self.transaction()?;
+        let contributors = query_in_transation(&tx, &sql, &params, row_to_contributor)?;
+        tx.commit()?;
+        Ok(contributors)
+    }
+
+    fn update(&mut self, id: &str, contributor: &Contributor) -> Result<bool> {
+        let tx = self.transaction()?;
+        let result = tx.execute(
+            "UPDATE contributor SET name = ?, normalized_name = ? WHERE id = ?",
+            rusqlite::params![
+                contributor.name.trim(),
+                normalize_contributor_name(&contributor.name),
+                id
+            ],
+        )?;
+        refresh_contributor_items(&tx, id)?;
+        tx.commit()?;
+        Ok(result == 1)
+    }
+
+    fn delete(&mut self, id: &str) -> Result<bool> {
+        let tx = self.transaction()?;
+        let item_ids: Vec<String> = {
+            let mut stmt = tx.prepare(
+                "SELECT DISTINCT item_id FROM item_contributor WHERE contributor_id = ?",
+            )?;
+            stmt.query_map([id], |row| row.get(0))?
+                .collect::<Result<Vec<_>>>()?
+        };
+        let result = tx.execute("DELETE FROM contributor WHERE id = ?", [id])?;
+        for item_id in item_ids {
+            refresh_item_names(&tx, &item_id)?;
+        }
+        tx.commit()?;
+        Ok(result == 1)
+    }
+}
+
+fn row_to_contributor(row: &rusqlite::Row) -> Result<Contributor> {
+    Ok(Contributor {
+        id: row.get(0)?,
+        user_id: row.get(1)?,
+        name: row.get(2)?,
+        item_count: row.get(3)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::library::{LibraryRepository, test_support::book};
+    use crate::migrations::Migrator;
+
+    #[test]
+    fn merges_names_equal_after_unicode_lowercasing() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("test.db").to_str().unwrap().to_string();
+        let migrations_dir = dir.path().join("migrations");
+        std::fs::create_dir(&migrations_dir).unwrap();
+        for entry in std::fs::read_dir("migrations").unwrap() {
+            let path = entry.unwrap().path();
+            let file_name = path.file_name().unwrap().to_str().unwrap();
+            if file_name < "V2026101923" {
+                std::fs::copy(&path, migrations_dir.join(file_name)).unwrap();
+            }
+        }
+        Migrator::new(db_path.as_str(), migrations_dir.to_str().unwrap())
+            .unwrap()
+            .run_migrations()
+            .unwrap();
+
+        let mut library = LibraryRepository::new(Database::new(&db_path).unwrap());
+        library.create(&book("a", "me", "a", "Äijälä")).unwrap();
+        library.create(&book("b", "me", "b", "Äijälä")).unwrap();
+        // What SQLite lower() made of an upper case spelling
+        library
+            .conn()
+            .execute_batch(
+                "INSERT INTO contributor (id, user_id, name, normalized_name)
+                    VALUES ('upper', 'me', 'ÄIJÄLÄ', 'ÄijÄlÄ');
+                UPDATE item_contributor SET contributor_id = 'upper' WHERE item_id = 'b';",
+            )
+            .unwrap();
+
+        Migrator::new(db_path.as_str(), "migrations")
+            .unwrap()
+            .run_migrations()
+            .unwrap();
+
+        let mut repo = ContributorRepository::new(Database::new(&db_path).unwrap());
+        let contributors = repo.search(ContributorFilter::new("me")).unwrap();
+        assert_eq!(contributors.len(), 1);
+        assert_eq!(
+            (contributors[0].name.as_str(), contributors[0].item_count),
+            ("Äijälä", 2)
+        );
+        assert_eq!(
+            ensure_contributor(repo.conn(), "me", " ÄIJÄLÄ ").unwrap(),
+            contributors[0].id
+        );
+    }
+}
@@ -0,0 +1,137 @@
+use axum::{
+    Json, Router,
+    body::Body,
+    extract::{DefaultBodyLimit, Multipart, Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::Response,
+    routing::{delete, get, put},
+};
+use serde::Deserialize;
+
+use crate::{
+    AppState,
+    auth::User,
+    covers::{
+        COVER_CONTENT_TYPES, Cover, CoverSize, MAX_COVER_BYTES,
+        domain::{delete_cover, read_cover, upload_cover},
+    },
+    utils::map_to_internal_error,
+};
+
+/// Room for the multipart boundaries and headers around the file
+const MULTIPART_OVERHEAD: usize = 64 * 1024;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/library/{id}/cover",
+            put(upload_cover_route)
+                .layer(DefaultBodyLimit::max(MAX_COVER_BYTES + MULTIPART_OVERHEAD)),
+        )
+        .route("/library/{id}/cover", get(get_cover_route))
+        .route("/library/{id}/cover", delete(delete_cover_route))
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct CoverQuery {
+    #[serde(default)]
+    size: CoverSize,
+}
+
+fn map_cover_error(err: Box<dyn std::error::Error>) -> StatusCode {
+    let message = err.to_string();
+    if message.contains("not found") {
+        StatusCode::NOT_FOUND
+    } else if message.contains("Unsupported content type") {
+        StatusCode::UNSUPPORTED_MEDIA_TYPE
+    } else if message.contains("too large") {
+        StatusCode::PAYLOAD_TOO_LARGE
+    } else if message.contains("Invalid") {
+        StatusCode::UNPROCESSABLE_ENTITY
+    } else {
+        map_to_internal_error(err)
+    }
+}
+
+/// Accepts the image as the multipart field `file`
+async fn upload_cover_route(
+    State(state): State<AppState>,
+    user: User,
+    Path(id): Path<String>,
+    mut multipart: Multipart,
+) -> Result<Json<Cover>, StatusCode> {
+    let mut upload = None;
+    while let Some(field) = multipart.next_field().await.map_err(|e| e.status())? {
+        if field.name() != Some("file") {
+            continue;
+        }
+        let content_type = field.content_type().unwrap_or_default().to_string();
+        if !COVER_CONTENT_TYPES.contains(&content_type.as_str()) {
+            return Err(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        }
+        let bytes = field.bytes().await.map_err(|e| e.status())?;
+        if bytes.len() > MAX_COVER_BYTES {
+            return Err(StatusCode::PAYLOAD_TOO_LARGE);
+        }
+        upload = Some((content_type, bytes));
+        break;
+    }
+    let (content_type, bytes) = upload.ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
+
+    // Decoding and resizing the image would otherwise block the runtime
+    let cover = tokio::task::spawn_blocking(move || {
+        upload_cover(&user, &state, &id, &content_type, &bytes).map_err(map_cover_error)
+    })
+    .await
+    .map_err(|e| map_to_internal_error(e.into()))??;
+
+    Ok(Json(cover))
+}
+
+/// Serves the cover image. The cover id changes with every upload, so the
+/// files behind an ETag never change and can be cached.
+async fn get_cover_route(
+    State(state): State<AppState>,
+    user: User,
+    Path(id): Path<String>,
+    Query(query): Query<CoverQuery>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    let (cover, bytes) = read_cover(&user, &state, &id, query.size).map_err(map_cover_error)?;
+
+    let etag = format!("\"{}-{}\"", cover.id, query.size.file_name());
+    let not_modified = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.split(',').any(|tag| tag.trim() == etag));
+
+    let content_type = match query.size {
+        CoverSize::Original => cover.content_type.as_str(),
+        _ => "image/jpeg",
+    };
+    // The URL stays the same when the cover is replaced, so browsers must
+    // revalidate with the ETag instead of serving a stale image
+    let response = Response::builder()
+        .header(header::ETAG, &etag)
+        .header(header::CACHE_CONTROL, "private, no-cache");
+    let response = if not_modified {
+        response
+            .status(StatusCode::NOT_MODIFIED)
+            .body(Body::empty())
+    } else {
+        response
+            .header(header::CONTENT_TYPE, content_type)
+            .body(Body::from(bytes))
+    };
+    response.map_err(|e| map_to_internal_error(e.into()))
+}
+
+async fn delete_cover_route(
+    State(state): State<AppState>,
+    user: User,
+    Path(id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    delete_cover(&user, &state, &id).map_err(map_cover_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,107 @@
+use crate::{
+    AppState,
+    auth::User,
+    covers::{
+        Cover, CoverSize, images::process_cover, repository::CoverRepository, storage::CoverStorage,
+    },
+    database::{Database, Repository},
+    library::LibraryRepository,
+};
+
+/// Stores a new cover for the item, replacing the previous one. Decoding
+/// and resizing are CPU bound, so callers should run this on a blocking
+/// thread.
+pub fn upload_cover(
+    user: &User,
+    state: &AppState,
+    item_id: &str,
+    content_type: &str,
+    bytes: &[u8],
+) -> Result<Cover, Box<dyn std::error::Error>> {
+    read_owned_item(user, state, item_id)?;
+    let processed = process_cover(bytes, content_type)?;
+
+    let cover = Cover {
+        item_id: item_id.to_string(),
+        id: uuid::Uuid::new_v4().to_string(),
+        content_type: content_type.to_string(),
+        width: processed.width,
+        height: processed.height,
+        byte_size: bytes.len() as i64,
+        uploaded_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    let storage = CoverStorage::new(&state.data_dir);
+    let mut files: Vec<(CoverSize, &[u8])> = vec![(CoverSize::Original, bytes)];
+    for (size, thumbnail) in &processed.thumbnails {
+        files.push((*size, thumbnail));
+    }
+    storage.write(&cover.id, &files)?;
+
+    let mut repo = CoverRepository::new(Database::new(&state.database_path)?);
+    if let Err(err) = repo.save(&cover) {
+        storage.remove(&cover.id)?;
+        return Err(err.into());
+    }
+    purge_deleted_covers(state);
+    Ok(cover)
+}
+
+/// Reads the cover and the image file of the requested size
+pub fn read_cover(
+    user: &User,
+    state: &AppState,
+    item_id: &str,
+    size: CoverSize,
+) -> Result<(Cover, Vec<u8>), Box<dyn std::error::Error>> {
+    read_owned_item(user, state, item_id)?;
+    let mut repo = CoverRepository::new(Database::new(&state.database_path)?);
+    let cover = repo.read(item_id)?.ok_or("Cover not found")?;
+    let bytes = CoverStorage::new(&state.data_dir).read(&cover.id, size)?;
+    Ok((cover, bytes))
+}
+
+pub fn delete_cover(
+    user: &User,
+    state: &AppState,
+    item_id: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    read_owned_item(user, state, item_id)?;
+    let mut repo = CoverRepository::new(Database::new(&state.database_path)?);
+    if !repo.delete(item_id)? {
+        return Err("Cover not found".into());
+    }
+    purge_deleted_covers(state);
+    Ok(())
+}
+
+/// Removes the files of covers that were replaced or whose items were
+/// deleted. Failures are only logged; the covers stay queued and are
+/// retried on the next purge.
+pub fn purge_deleted_covers(state: &AppState) {
+    if let Err(err) = try_purge_deleted_covers(state) {
+        println!("Cover cleanup failed: {}", err);
+    }
+}
+
+fn try_purge_deleted_covers(state: &AppState) -> Result<(), Box<dyn std::error::Error>> {
+    let mut repo = CoverRepository::new(Database::new(&state.database_path)?);
+    let storage = CoverStorage::new(&state.data_dir);
+    for cover_id in repo.pending_cleanup()? {
+        storage.remove(&cover_id)?;
+        repo.finish_cleanup(&cover_id)?;
+    }
+    Ok(())
+}
+
+fn read_owned_item(
+    user: &User,
+    state: &AppState,
+    item_id: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut repo = LibraryRepository::new(Database::new(&state.database_path)?);
+    match repo.read_by_id(item_id)? {
+        Some(item) if item.user_id == user.id => Ok(()),
+        _ => Err("Library item not found".into()),
+    }
+}
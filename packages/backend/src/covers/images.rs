@@ -0,0 +1,106 @@
+use std::io::Cursor;
+
+use image::{DynamicImage, ImageReader, Limits, codecs::jpeg::JpegEncoder};
+
+use crate::covers::{COVER_CONTENT_TYPES, CoverSize, MAX_COVER_BYTES, MAX_COVER_DIMENSION};
+
+const THUMBNAIL_QUALITY: u8 = 85;
+
+/// An upload that decoded as the image type it claimed to be
+pub struct ProcessedCover {
+    pub width: u32,
+    pub height: u32,
+    pub thumbnails: Vec<(CoverSize, Vec<u8>)>,
+}
+
+/// Checks that the bytes are an image of the declared type within the size
+/// limits and renders the thumbnails as JPEG.
+pub fn process_cover(bytes: &[u8], content_type: &str) -> Result<ProcessedCover, String> {
+    if !COVER_CONTENT_TYPES.contains(&content_type) {
+        return Err(format!("Unsupported content type {}", content_type));
+    }
+    if bytes.len() > MAX_COVER_BYTES {
+        return Err("Invalid cover: file is too large".to_string());
+    }
+
+    let format = image::guess_format(bytes).map_err(|_| "Invalid cover: not an image")?;
+    if format.to_mime_type() != content_type {
+        return Err(format!(
+            "Invalid cover: content is {} but was sent as {}",
+            format.to_mime_type(),
+            content_type
+        ));
+    }
+
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_COVER_DIMENSION);
+    limits.max_image_height = Some(MAX_COVER_DIMENSION);
+    let mut reader = ImageReader::with_format(Cursor::new(bytes), format);
+    reader.limits(limits);
+    let image = reader
+        .decode()
+        .map_err(|e| format!("Invalid cover: {}", e))?;
+
+    let thumbnails = CoverSize::THUMBNAILS
+        .iter()
+        .map(|size| Ok((*size, render_thumbnail(&image, *size)?)))
+        .collect::<Result<Vec<_>, String>>()?;
+
+    Ok(ProcessedCover {
+        width: image.width(),
+        height: image.height(),
+        thumbnails,
+    })
+}
+
+fn render_thumbnail(image: &DynamicImage, size: CoverSize) -> Result<Vec<u8>, String> {
+    let max_width = size.max_width().unwrap_or(image.width());
+    let max_height = max_width * 3 / 2;
+    // Small images are only re-encoded, never scaled up
+    let resized = if image.width() > max_width || image.height() > max_height {
+        image.thumbnail(max_width, max_height)
+    } else {
+        image.clone()
+    };
+
+    let mut bytes = Vec::new();
+    let encoder = JpegEncoder::new_with_quality(&mut bytes, THUMBNAIL_QUALITY);
+    DynamicImage::ImageRgb8(resized.to_rgb8())
+        .write_with_encoder(encoder)
+        .map_err(|e| e.to_string())?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::ImageFormat;
+
+    use super::*;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        DynamicImage::new_rgba8(width, height)
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn renders_thumbnails_within_bounds() {
+        let cover = process_cover(&png(1000, 1600), "image/png").unwrap();
+
+        assert_eq!((cover.width, cover.height), (1000, 1600));
+        let small = image::load_from_memory(&cover.thumbnails[0].1).unwrap();
+        assert_eq!(cover.thumbnails[0].0, CoverSize::Small);
+        assert!(small.width() <= 160 && small.height() <= 240);
+        let medium = image::load_from_memory(&cover.thumbnails[1].1).unwrap();
+        assert!(medium.width() <= 480 && medium.height() <= 720);
+    }
+
+    #[test]
+    fn rejects_content_not_matching_the_declared_type() {
+        assert!(process_cover(&png(10, 10), "image/jpeg").is_err());
+        assert!(process_cover(b"not an image", "image/png").is_err());
+        assert!(process_cover(&png(10, 10), "image/gif").is_err());
+    }
+}
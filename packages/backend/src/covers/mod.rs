@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+mod api;
+mod domain;
+mod images;
+mod repository;
+mod storage;
+
+/// Largest accepted upload
+pub const MAX_COVER_BYTES: usize = 10 * 1024 * 1024;
+/// Larger images are rejected before decoding to keep memory use bounded
+pub const MAX_COVER_DIMENSION: u32 = 8000;
+pub const COVER_CONTENT_TYPES: &[&str] = &["image/jpeg", "image/png", "image/webp"];
+
+/// Cover image of a library item
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Cover {
+    pub item_id: String,
+    /// Changes with every upload, usable for cache busting
+    pub id: String,
+    pub content_type: String,
+    pub width: u32,
+    pub height: u32,
+    pub byte_size: i64,
+    pub uploaded_at: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CoverSize {
+    Small,
+    Medium,
+    /// The uploaded file as it was
+    #[default]
+    Original,
+}
+
+impl CoverSize {
+    pub const THUMBNAILS: &'static [CoverSize] = &[CoverSize::Small, CoverSize::Medium];
+
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            CoverSize::Small => "small.jpg",
+            CoverSize::Medium => "medium.jpg",
+            CoverSize::Original => "original",
+        }
+    }
+
+    /// Thumbnails fit in a box of this width and one and a half times the
+    /// height, the usual proportions of a book cover
+    pub fn max_width(&self) -> Option<u32> {
+        match self {
+            CoverSize::Small => Some(160),
+            CoverSize::Medium => Some(480),
+            CoverSize::Original => None,
+        }
+    }
+}
+
+pub use api::routes;
+pub use domain::purge_deleted_covers;
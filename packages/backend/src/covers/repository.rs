@@ -0,0 +1,80 @@
+use rusqlite::{OptionalExtension, Result};
+
+use crate::{covers::Cover, database::Database};
+
+pub struct CoverRepository {
+    db: Database,
+}
+
+impl CoverRepository {
+    pub fn new(db: Database) -> Self {
+        CoverRepository { db }
+    }
+
+    pub fn read(&mut self, item_id: &str) -> Result<Option<Cover>> {
+        self.db
+            .conn
+            .query_row(
+                "SELECT item_id, id, content_type, width, height, byte_size, uploaded_at
+                    FROM cover WHERE item_id = ?",
+                [item_id],
+                |row| {
+                    Ok(Cover {
+                        item_id: row.get(0)?,
+                        id: row.get(1)?,
+                        content_type: row.get(2)?,
+                        width: row.get(3)?,
+                        height: row.get(4)?,
+                        byte_size: row.get(5)?,
+                        uploaded_at: row.get(6)?,
+                    })
+                },
+            )
+            .optional()
+    }
+
+    /// Saves the cover, replacing the previous cover of the item. The
+    /// replaced cover is queued for file cleanup by a trigger.
+    pub fn save(&mut self, cover: &Cover) -> Result<()> {
+        self.db.conn.execute(
+            "INSERT INTO cover (item_id, id, content_type, width, height, byte_size, uploaded_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                ON CONFLICT (item_id) DO UPDATE SET id = ?2, content_type = ?3, width = ?4,
+                    height = ?5, byte_size = ?6, uploaded_at = ?7",
+            rusqlite::params![
+                cover.item_id,
+                cover.id,
+                cover.content_type,
+                cover.width,
+                cover.height,
+                cover.byte_size,
+                cover.uploaded_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete(&mut self, item_id: &str) -> Result<bool> {
+        let result = self
+            .db
+            .conn
+            .execute("DELETE FROM cover WHERE item_id = ?", [item_id])?;
+        Ok(result == 1)
+    }
+
+    /// Ids of covers whose files should be removed
+    pub fn pending_cleanup(&mut self) -> Result<Vec<String>> {
+        let mut stmt = self.db.conn.prepare("SELECT id FROM cover_cleanup")?;
+        let ids = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<String>>>()?;
+        Ok(ids)
+    }
+
+    pub fn finish_cleanup(&mut self, cover_id: &str) -> Result<()> {
+        self.db
+            .conn
+            .execute("DELETE FROM cover_cleanup WHERE id = ?", [cover_id])?;
+        Ok(())
+    }
+}
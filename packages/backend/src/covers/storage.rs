@@ -0,0 +1,44 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::covers::CoverSize;
+
+/// Cover files under `<DATA_DIR>/covers/<cover id>/`
+pub struct CoverStorage {
+    root: PathBuf,
+}
+
+impl CoverStorage {
+    pub fn new(data_dir: &Path) -> Self {
+        CoverStorage {
+            root: data_dir.join("covers"),
+        }
+    }
+
+    /// Writes all files of a cover. The files are written to a temporary
+    /// directory first so that a cover is never served half written.
+    pub fn write(&self, cover_id: &str, files: &[(CoverSize, &[u8])]) -> io::Result<()> {
+        std::fs::create_dir_all(&self.root)?;
+        let staging = tempfile::Builder::new()
+            .prefix(".upload-")
+            .tempdir_in(&self.root)?;
+        for (size, bytes) in files {
+            std::fs::write(staging.path().join(size.file_name()), bytes)?;
+        }
+        std::fs::rename(staging.keep(), self.root.join(cover_id))
+    }
+
+    pub fn read(&self, cover_id: &str, size: CoverSize) -> io::Result<Vec<u8>> {
+        std::fs::read(self.root.join(cover_id).join(size.file_name()))
+    }
+
+    /// Removes the files of a cover. Removing a missing cover succeeds.
+    pub fn remove(&self, cover_id: &str) -> io::Result<()> {
+        match std::fs::remove_dir_all(self.root.join(cover_id)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use rusqlite::{Connection, Result, Row, ToSql, Transaction};
 
+use crate::collation;
+
 pub struct Database {
     pub conn: Connection,
 }
@@ -56,6 +58,19 @@ impl Database {
             rusqlite::config::DbConfig::SQLITE_DBCONFIG_ENABLE_FKEY,
             true,
         )?;
+        conn.create_collation(collation::FINNISH, collation::finnish_cmp)?;
         Ok(Database { conn })
     }
 }
+
+/// Path of a fully migrated database in the directory, for tests that need
+/// the real schema and triggers
+#[cfg(test)]
+pub fn migrated_test_database(dir: &tempfile::TempDir) -> String {
+    let path = dir.path().join("test.db").to_str().unwrap().to_string();
+    crate::migrations::Migrator::new(path.as_str(), "migrations")
+        .unwrap()
+        .run_migrations()
+        .unwrap();
+    path
+}
@@ -0,0 +1,78 @@
+use axum::{
+    Router,
+    body::Body,
+    extract::{Query, State},
+    http::{StatusCode, header},
+    response::Response,
+    routing::get,
+};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+use crate::{
+    AppState,
+    auth::User,
+    database::Database,
+    export::{
+        ExportFormat, ExportQuery,
+        domain::{ChunkWriter, load_export_extras, write_export},
+    },
+    library::LibraryRepository,
+    utils::map_to_internal_error,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/export/library", get(export_library_route))
+}
+
+/// Streams the export while it's being read from the database
+async fn export_library_route(
+    user: User,
+    state: State<AppState>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, StatusCode> {
+    if query.format != ExportFormat::Json && (query.include_answers || query.include_solutions) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let extras = load_export_extras(
+        &user,
+        &state,
+        query.include_answers,
+        query.include_solutions,
+    )
+    .map_err(map_to_internal_error)?;
+    let db = Database::new(&state.database_path).map_err(|e| map_to_internal_error(e.into()))?;
+
+    let (content_type, extension) = match query.format {
+        ExportFormat::Json => ("application/json", "json"),
+        ExportFormat::Csv | ExportFormat::Goodreads => ("text/csv; charset=utf-8", "csv"),
+    };
+    let filename = format!(
+        "haasteikko-library-{}.{}",
+        chrono::Utc::now().format("%Y-%m-%d"),
+        extension
+    );
+
+    let (sender, receiver) = mpsc::channel(8);
+    let user_id = user.id.clone();
+    tokio::task::spawn_blocking(move || {
+        let repo = LibraryRepository::new(db);
+        write_export(
+            repo,
+            &user_id,
+            query.format,
+            extras,
+            ChunkWriter::new(sender),
+        );
+    });
+
+    Response::builder()
+        .header(header::CONTENT_TYPE, content_type)
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", filename),
+        )
+        .body(Body::from_stream(ReceiverStream::new(receiver)))
+        .map_err(|e| map_to_internal_error(e.into()))
+}
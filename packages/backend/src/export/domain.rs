@@ -0,0 +1,426 @@
+use std::collections::HashMap;
+
+use axum::body::Bytes;
+use serde::Serialize;
+use tokio::sync::mpsc;
+
+use crate::{
+    AppState,
+    auth::User,
+    challenge_answers::{AnswerFilter, get_challenge_answers},
+    export::{EXPORT_VERSION, ExportAnswer, ExportFormat, ExportSolution},
+    library::{ItemStatus, LibraryItem, LibraryRepository},
+    solution::{SolutionFilter, get_solutions},
+    tags::{Tag, get_tags},
+};
+
+/// Bytes collected before a chunk is sent to the client
+const CHUNK_SIZE: usize = 64 * 1024;
+
+const CSV_COLUMNS: &[&str] = &[
+    "id",
+    "kind",
+    "title",
+    "creator",
+    "translator",
+    "status",
+    "startedAt",
+    "completedAt",
+    "completionCount",
+    "addedAt",
+    "favorite",
+    "rating",
+    "isbn",
+    "pageCount",
+    "tags",
+    "review",
+    "notes",
+];
+
+const GOODREADS_COLUMNS: &[&str] = &[
+    "Title",
+    "Author",
+    "ISBN",
+    "ISBN13",
+    "My Rating",
+    "Number of Pages",
+    "Date Read",
+    "Date Added",
+    "Bookshelves",
+    "Exclusive Shelf",
+    "My Review",
+    "Private Notes",
+];
+
+/// Everything exported next to the items, small enough to read up front
+pub struct ExportExtras {
+    pub exported_at: String,
+    pub tags: Vec<Tag>,
+    pub answers: Option<Vec<ExportAnswer>>,
+    pub solutions: Option<Vec<ExportSolution>>,
+}
+
+pub fn load_export_extras(
+    user: &User,
+    state: &AppState,
+    include_answers: bool,
+    include_solutions: bool,
+) -> Result<ExportExtras, Box<dyn std::error::Error>> {
+    let tags = get_tags(user, state)?;
+
+    let answers = if include_answers {
+        let answers = get_challenge_answers(&state.database_path, AnswerFilter::new(&user.id))?;
+        Some(
+            answers
+                .into_iter()
+                .map(|a| ExportAnswer {
+                    id: a.id,
+                    challenge_id: a.challenge_id,
+                    question_id: a.question_id,
+                    item_id: a.item_id,
+                    kind: a.kind,
+                    answer: a.answer,
+                    answered: a.answered,
+                })
+                .collect(),
+        )
+    } else {
+        None
+    };
+
+    let solutions = if include_solutions {
+        let solutions = get_solutions(&state.database_path, SolutionFilter::new(&user.id))?;
+        Some(
+            solutions
+                .into_iter()
+                .map(|s| ExportSolution {
+                    id: s.id,
+                    challenge_id: s.challenge_id,
+                    question_id: s.question_id,
+                    kind: s.kind,
+                    single_answer_item_id: s.single_answer_item_id,
+                    multiple_answer_item_ids: s.multiple_answer_item_ids,
+                })
+                .collect(),
+        )
+    } else {
+        None
+    };
+
+    Ok(ExportExtras {
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        tags,
+        answers,
+        solutions,
+    })
+}
+
+/// Collects output into chunks sent to the response body
+pub struct ChunkWriter {
+    sender: mpsc::Sender<Result<Bytes, std::io::Error>>,
+    buffer: Vec<u8>,
+}
+
+impl ChunkWriter {
+    pub fn new(sender: mpsc::Sender<Result<Bytes, std::io::Error>>) -> Self {
+        ChunkWriter {
+            sender,
+            buffer: Vec::with_capacity(CHUNK_SIZE),
+        }
+    }
+
+    /// Returns false once the client has gone away
+    fn write(&mut self, data: &[u8]) -> bool {
+        self.buffer.extend_from_slice(data);
+        if self.buffer.len() >= CHUNK_SIZE {
+            return self.flush();
+        }
+        true
+    }
+
+    fn flush(&mut self) -> bool {
+        if self.buffer.is_empty() {
+            return true;
+        }
+        let chunk = Bytes::from(std::mem::take(&mut self.buffer));
+        self.sender.blocking_send(Ok(chunk)).is_ok()
+    }
+
+    /// Aborts the response, the client sees a broken download
+    fn fail(self, err: impl std::fmt::Display) {
+        println!("Export failed: {}", err);
+        let _ = self
+            .sender
+            .blocking_send(Err(std::io::Error::other(err.to_string())));
+    }
+}
+
+/// Writes the export of the user's library. Runs on a blocking thread and
+/// reads the items one row at a time.
+pub fn write_export(
+    mut repo: LibraryRepository,
+    user_id: &str,
+    format: ExportFormat,
+    extras: ExportExtras,
+    mut out: ChunkWriter,
+) {
+    let result = match format {
+        ExportFormat::Json => write_json(&mut repo, user_id, &extras, &mut out),
+        ExportFormat::Csv => write_csv(
+            &mut repo,
+            user_id,
+            &extras,
+            &mut out,
+            CSV_COLUMNS,
+            |item, tags| Some(csv_record(item, tags)),
+        ),
+        ExportFormat::Goodreads => write_csv(
+            &mut repo,
+            user_id,
+            &extras,
+            &mut out,
+            GOODREADS_COLUMNS,
+            goodreads_record,
+        ),
+    };
+
+    match result {
+        Ok(()) => {
+            out.flush();
+        }
+        Err(e) => out.fail(e),
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonHeader<'a> {
+    version: u32,
+    exported_at: &'a str,
+    tags: &'a [Tag],
+}
+
+fn write_json(
+    repo: &mut LibraryRepository,
+    user_id: &str,
+    extras: &ExportExtras,
+    out: &mut ChunkWriter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // The header object is written without its closing brace so that the
+    // items can be streamed into it
+    let header = serde_json::to_string(&JsonHeader {
+        version: EXPORT_VERSION,
+        exported_at: &extras.exported_at,
+        tags: &extras.tags,
+    })?;
+    out.write(header.trim_end_matches('}').as_bytes());
+    out.write(b",\"items\":[");
+
+    let mut first = true;
+    let mut serialize_error = None;
+    repo.for_each_item(user_id, |item| {
+        let json = match serde_json::to_vec(&item) {
+            Ok(json) => json,
+            Err(e) => {
+                serialize_error = Some(e);
+                return false;
+            }
+        };
+        let separator: &[u8] = if first { b"\n" } else { b",\n" };
+        first = false;
+        out.write(separator) && out.write(&json)
+    })?;
+    if let Some(e) = serialize_error {
+        return Err(e.into());
+    }
+    out.write(b"\n]");
+
+    if let Some(answers) = &extras.answers {
+        out.write(b",\"answers\":");
+        out.write(&serde_json::to_vec(answers)?);
+    }
+    if let Some(solutions) = &extras.solutions {
+        out.write(b",\"solutions\":");
+        out.write(&serde_json::to_vec(solutions)?);
+    }
+    out.write(b"}\n");
+    Ok(())
+}
+
+fn write_csv<F>(
+    repo: &mut LibraryRepository,
+    user_id: &str,
+    extras: &ExportExtras,
+    out: &mut ChunkWriter,
+    columns: &[&str],
+    to_record: F,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    F: Fn(&LibraryItem, &HashMap<&str, &str>) -> Option<Vec<String>>,
+{
+    let tag_names: HashMap<&str, &str> = extras
+        .tags
+        .iter()
+        .map(|tag| (tag.id.as_str(), tag.name.as_str()))
+        .collect();
+
+    out.write(&csv_line(columns)?);
+
+    let mut write_error = None;
+    repo.for_each_item(user_id, |item| {
+        let Some(record) = to_record(&item, &tag_names) else {
+            return true;
+        };
+        match csv_line(&record) {
+            Ok(line) => out.write(&line),
+            Err(e) => {
+                write_error = Some(e);
+                false
+            }
+        }
+    })?;
+
+    match write_error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+fn csv_line<T: AsRef<[u8]>>(fields: &[T]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(Vec::new());
+    writer.write_record(fields)?;
+    Ok(writer.into_inner()?)
+}
+
+fn item_tags(item: &LibraryItem, tag_names: &HashMap<&str, &str>) -> Vec<String> {
+    item.tag_ids
+        .iter()
+        .filter_map(|id| tag_names.get(id.as_str()))
+        .map(|name| name.to_string())
+        .collect()
+}
+
+fn csv_record(item: &LibraryItem, tag_names: &HashMap<&str, &str>) -> Vec<String> {
+    let optional = |value: Option<String>| value.unwrap_or_default();
+    vec![
+        item.id.clone(),
+        item.details.kind().to_string(),
+        item.title.clone(),
+        item.details.creator().to_string(),
+        optional(item.details.translator().map(str::to_string)),
+        item.status.as_str().to_string(),
+        optional(item.started_at.clone()),
+        optional(item.completed_at.clone()),
+        item.completion_count.to_string(),
+        item.added_at.clone(),
+        item.favorite.to_string(),
+        optional(item.rating.map(|r| r.to_string())),
+        optional(item.isbn.clone()),
+        optional(item.page_count.map(|p| p.to_string())),
+        item_tags(item, tag_names).join("; "),
+        optional(item.review.clone()),
+        optional(item.notes.clone()),
+    ]
+}
+
+/// Goodreads only knows books, other kinds are left out. Ratings are whole
+/// stars and dates use slashes.
+fn goodreads_record(item: &LibraryItem, tag_names: &HashMap<&str, &str>) -> Option<Vec<String>> {
+    if !matches!(item.details.kind(), "Book" | "Audiobook") {
+        return None;
+    }
+
+    let date = |value: &Option<String>| {
+        value
+            .as_deref()
+            .and_then(|v| v.get(..10))
+            .map(|v| v.replace('-', "/"))
+            .unwrap_or_default()
+    };
+    let shelf = match item.status {
+        ItemStatus::Completed => "read",
+        ItemStatus::InProgress => "currently-reading",
+        ItemStatus::Planned => "to-read",
+        ItemStatus::Abandoned => "abandoned",
+    };
+
+    Some(vec![
+        item.title.clone(),
+        item.details.creator().to_string(),
+        String::new(),
+        item.isbn.clone().unwrap_or_default(),
+        item.rating
+            .map(|r| (r.round() as i64).max(1).to_string())
+            .unwrap_or_else(|| "0".to_string()),
+        item.page_count.map(|p| p.to_string()).unwrap_or_default(),
+        date(&item.completed_at),
+        date(&Some(item.added_at.clone())),
+        item_tags(item, tag_names).join(", "),
+        shelf.to_string(),
+        item.review.clone().unwrap_or_default(),
+        item.notes.clone().unwrap_or_default(),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::library::ItemDetails;
+
+    fn item(details: ItemDetails) -> LibraryItem {
+        LibraryItem {
+            id: "i".to_string(),
+            user_id: "u".to_string(),
+            title: "Sinuhe egyptiläinen".to_string(),
+            added_at: "2024-01-02T10:00:00+00:00".to_string(),
+            status: ItemStatus::Completed,
+            started_at: None,
+            completed_at: Some("2024-03-04".to_string()),
+            completion_count: 1,
+            isbn: Some("9789510412497".to_string()),
+            page_count: Some(780),
+            expected_hours: None,
+            language: None,
+            original_language: None,
+            favorite: false,
+            rating: Some(3.5),
+            review: None,
+            review_html: None,
+            notes: None,
+            activated_challenge_ids: Vec::new(),
+            tag_ids: vec!["t".to_string()],
+            cover_id: None,
+            details,
+        }
+    }
+
+    #[test]
+    fn maps_books_to_goodreads_columns() {
+        let tags = HashMap::from([("t", "historical")]);
+        let book = item(ItemDetails::Book {
+            author: "Mika Waltari".to_string(),
+            translator: None,
+        });
+
+        let record = goodreads_record(&book, &tags).unwrap();
+
+        assert_eq!(record.len(), GOODREADS_COLUMNS.len());
+        assert_eq!(record[4], "4");
+        assert_eq!(record[6], "2024/03/04");
+        assert_eq!(record[7], "2024/01/02");
+        assert_eq!(record[8], "historical");
+        assert_eq!(record[9], "read");
+    }
+
+    #[test]
+    fn leaves_other_kinds_out_of_goodreads_export() {
+        let movie = item(ItemDetails::Movie {
+            director: "Edvin Laine".to_string(),
+            release_year: None,
+        });
+
+        assert_eq!(goodreads_record(&movie, &HashMap::new()), None);
+    }
+}
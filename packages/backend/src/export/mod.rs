@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+
+mod api;
+mod domain;
+
+/// Version of the JSON export format, bumped on incompatible changes
+pub const EXPORT_VERSION: u32 = 1;
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    /// One row per item with every common field
+    Csv,
+    /// Books in the columns Goodreads imports
+    Goodreads,
+}
+
+/// Answers and solutions are only included in the JSON format
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+    #[serde(default)]
+    pub include_answers: bool,
+    #[serde(default)]
+    pub include_solutions: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportAnswer {
+    pub id: String,
+    pub challenge_id: String,
+    pub question_id: String,
+    pub item_id: String,
+    pub kind: String,
+    pub answer: String,
+    pub answered: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportSolution {
+    pub id: String,
+    pub challenge_id: String,
+    pub question_id: String,
+    pub kind: String,
+    pub single_answer_item_id: Option<String>,
+    pub multiple_answer_item_ids: Option<Vec<String>>,
+}
+
+pub use api::routes;
@@ -0,0 +1,97 @@
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{delete, get, post, put},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppState,
+    auth::User,
+    goals::{
+        GoalProgress, NewGoal,
+        domain::{create_goal, delete_goal, get_goal, get_goals, update_goal},
+    },
+    utils::map_to_internal_error,
+    validation::{ApiError, ValidJson, map_validation_error},
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/goals", get(get_all_goals))
+        .route("/goals", post(create_new_goal))
+        .route("/goals/{id}", get(get_single_goal))
+        .route("/goals/{id}", put(update_existing_goal))
+        .route("/goals/{id}", delete(delete_existing_goal))
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct IdResponse {
+    id: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct GoalsQuery {
+    year: Option<i32>,
+}
+
+fn map_goal_error(err: Box<dyn std::error::Error>) -> StatusCode {
+    let message = err.to_string();
+    if message.contains("not found") {
+        StatusCode::NOT_FOUND
+    } else if message.contains("already exists") {
+        StatusCode::CONFLICT
+    } else {
+        map_to_internal_error(err)
+    }
+}
+
+async fn get_all_goals(
+    State(state): State<AppState>,
+    user: User,
+    Query(query): Query<GoalsQuery>,
+) -> Result<Json<Vec<GoalProgress>>, StatusCode> {
+    let goals = get_goals(&user, &state, query.year).map_err(map_to_internal_error)?;
+    Ok(Json(goals))
+}
+
+async fn get_single_goal(
+    State(state): State<AppState>,
+    user: User,
+    Path(id): Path<String>,
+) -> Result<Json<GoalProgress>, StatusCode> {
+    let goal = get_goal(&user, &state, &id).map_err(map_goal_error)?;
+    Ok(Json(goal))
+}
+
+async fn create_new_goal(
+    State(state): State<AppState>,
+    user: User,
+    ValidJson(goal): ValidJson<NewGoal>,
+) -> Result<Json<IdResponse>, ApiError> {
+    let id = create_goal(&user, &state, &goal)
+        .map_err(|err| map_validation_error(err, map_goal_error))?;
+    Ok(Json(IdResponse { id }))
+}
+
+async fn update_existing_goal(
+    State(state): State<AppState>,
+    user: User,
+    Path(id): Path<String>,
+    ValidJson(goal): ValidJson<NewGoal>,
+) -> Result<StatusCode, ApiError> {
+    update_goal(&user, &state, &id, &goal)
+        .map_err(|err| map_validation_error(err, map_goal_error))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn delete_existing_goal(
+    State(state): State<AppState>,
+    user: User,
+    Path(id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    delete_goal(&user, &state, &id).map_err(map_goal_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,132 @@
+use crate::{
+    AppState,
+    auth::User,
+    database::{Database, Repository},
+    goals::{
+        Goal, GoalProgress, NewGoal, calculate_goal_progress,
+        repository::{GoalFilter, GoalRepository},
+    },
+};
+
+/// Goals with their progress, newest year first. All years are returned
+/// when `year` is not given.
+pub fn get_goals(
+    user: &User,
+    state: &AppState,
+    year: Option<i32>,
+) -> Result<Vec<GoalProgress>, Box<dyn std::error::Error>> {
+    let db = Database::new(&state.database_path)?;
+    let mut repo = GoalRepository::new(db);
+    let goals = repo.search(GoalFilter {
+        year,
+        ..GoalFilter::new(&user.id)
+    })?;
+
+    let today = chrono::Utc::now().date_naive();
+    let mut progress = Vec::with_capacity(goals.len());
+    for goal in goals {
+        let current = repo.completed_total(&user.id, &goal)?;
+        progress.push(calculate_goal_progress(goal, current, today));
+    }
+    Ok(progress)
+}
+
+pub fn get_goal(
+    user: &User,
+    state: &AppState,
+    id: &str,
+) -> Result<GoalProgress, Box<dyn std::error::Error>> {
+    let db = Database::new(&state.database_path)?;
+    let mut repo = GoalRepository::new(db);
+    let goal = read_owned_goal(&mut repo, user, id)?;
+    let current = repo.completed_total(&user.id, &goal)?;
+    Ok(calculate_goal_progress(
+        goal,
+        current,
+        chrono::Utc::now().date_naive(),
+    ))
+}
+
+pub fn create_goal(
+    user: &User,
+    state: &AppState,
+    goal: &NewGoal,
+) -> Result<String, Box<dyn std::error::Error>> {
+    goal.validate().into_result()?;
+    let db = Database::new(&state.database_path)?;
+    let mut repo = GoalRepository::new(db);
+
+    let goal = Goal {
+        id: uuid::Uuid::new_v4().to_string(),
+        user_id: user.id.clone(),
+        year: goal.year,
+        kind: goal.kind.clone(),
+        unit: goal.unit,
+        target: goal.target,
+    };
+    ensure_goal_available(&mut repo, &goal)?;
+    Ok(repo.create(&goal)?)
+}
+
+pub fn update_goal(
+    user: &User,
+    state: &AppState,
+    id: &str,
+    goal: &NewGoal,
+) -> Result<(), Box<dyn std::error::Error>> {
+    goal.validate().into_result()?;
+    let db = Database::new(&state.database_path)?;
+    let mut repo = GoalRepository::new(db);
+    let existing = read_owned_goal(&mut repo, user, id)?;
+
+    let goal = Goal {
+        year: goal.year,
+        kind: goal.kind.clone(),
+        unit: goal.unit,
+        target: goal.target,
+        ..existing
+    };
+    ensure_goal_available(&mut repo, &goal)?;
+    repo.update(id, &goal)?;
+    Ok(())
+}
+
+pub fn delete_goal(
+    user: &User,
+    state: &AppState,
+    id: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db = Database::new(&state.database_path)?;
+    let mut repo = GoalRepository::new(db);
+    read_owned_goal(&mut repo, user, id)?;
+    repo.delete(id)?;
+    Ok(())
+}
+
+fn read_owned_goal(
+    repo: &mut GoalRepository,
+    user: &User,
+    id: &str,
+) -> Result<Goal, Box<dyn std::error::Error>> {
+    match repo.read_by_id(id)? {
+        Some(goal) if goal.user_id == user.id => Ok(goal),
+        _ => Err("Goal not found".into()),
+    }
+}
+
+/// There is one goal per year, kind and unit
+fn ensure_goal_available(
+    repo: &mut GoalRepository,
+    goal: &Goal,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match repo.find_existing(goal)? {
+        Some(existing_id) if existing_id != goal.id => Err(format!(
+            "Goal for {} {} in {} already exists",
+            goal.kind,
+            goal.unit.as_str(),
+            goal.year
+        )
+        .into()),
+        _ => Ok(()),
+    }
+}
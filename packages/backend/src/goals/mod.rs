@@ -0,0 +1,187 @@
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::{library::ItemDetails, validation::ValidationErrors};
+
+mod api;
+mod domain;
+mod repository;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GoalUnit {
+    /// Completed items
+    #[default]
+    Items,
+    /// Pages of the completed items
+    Pages,
+}
+
+impl GoalUnit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GoalUnit::Items => "items",
+            GoalUnit::Pages => "pages",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "items" => Some(GoalUnit::Items),
+            "pages" => Some(GoalUnit::Pages),
+            _ => None,
+        }
+    }
+}
+
+/// Number of items or pages of a kind to complete during a year
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Goal {
+    pub id: String,
+    pub user_id: String,
+    pub year: i32,
+    pub kind: String,
+    pub unit: GoalUnit,
+    pub target: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewGoal {
+    pub year: i32,
+    pub kind: String,
+    #[serde(default)]
+    pub unit: GoalUnit,
+    pub target: i64,
+}
+
+impl NewGoal {
+    pub fn validate(&self) -> ValidationErrors {
+        let mut errors = ValidationErrors::new();
+        errors.year("year", Some(self.year));
+        errors.one_of("kind", &self.kind, ItemDetails::KINDS);
+        if self.target < 1 {
+            errors.add("target", "must be positive");
+        }
+        errors
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GoalStatus {
+    Achieved,
+    Ahead,
+    OnTrack,
+    Behind,
+    /// The year ended before the target was reached
+    Missed,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GoalProgress {
+    pub goal: Goal,
+    /// Completed so far
+    pub current: i64,
+    /// What should be completed by today to reach the target on schedule
+    pub expected: i64,
+    pub status: GoalStatus,
+    /// Year-end total if the pace so far continues
+    pub projected: i64,
+}
+
+/// Compares the progress to an even pace through the year
+pub fn calculate_goal_progress(goal: Goal, current: i64, today: NaiveDate) -> GoalProgress {
+    let days_in_year = if NaiveDate::from_ymd_opt(goal.year, 2, 29).is_some() {
+        366.0
+    } else {
+        365.0
+    };
+    // Today counts as elapsed so that a goal is not behind in the morning
+    let elapsed = match today.year().cmp(&goal.year) {
+        std::cmp::Ordering::Less => 0.0,
+        std::cmp::Ordering::Equal => today.ordinal() as f64 / days_in_year,
+        std::cmp::Ordering::Greater => 1.0,
+    };
+
+    let expected = goal.target as f64 * elapsed;
+    let projected = if elapsed > 0.0 {
+        (current as f64 / elapsed).round() as i64
+    } else {
+        current
+    };
+    let status = if current >= goal.target {
+        GoalStatus::Achieved
+    } else if elapsed >= 1.0 {
+        GoalStatus::Missed
+    } else if (current as f64) < expected.floor() {
+        GoalStatus::Behind
+    } else if (current as f64) > expected.ceil() {
+        GoalStatus::Ahead
+    } else {
+        GoalStatus::OnTrack
+    };
+
+    GoalProgress {
+        goal,
+        current,
+        expected: expected.floor() as i64,
+        status,
+        projected,
+    }
+}
+
+pub use api::routes;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn goal(year: i32, target: i64) -> Goal {
+        Goal {
+            id: "g".to_string(),
+            user_id: "u".to_string(),
+            year,
+            kind: "Book".to_string(),
+            unit: GoalUnit::Items,
+            target,
+        }
+    }
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn compares_progress_to_an_even_pace() {
+        // Day 73 of 365 is a fifth of the year
+        let progress = calculate_goal_progress(goal(2025, 40), 8, date("2025-03-14"));
+        assert_eq!(progress.expected, 8);
+        assert_eq!(progress.status, GoalStatus::OnTrack);
+        assert_eq!(progress.projected, 40);
+
+        let progress = calculate_goal_progress(goal(2025, 40), 4, date("2025-03-14"));
+        assert_eq!(progress.status, GoalStatus::Behind);
+        assert_eq!(progress.projected, 20);
+
+        let progress = calculate_goal_progress(goal(2025, 40), 12, date("2025-03-14"));
+        assert_eq!(progress.status, GoalStatus::Ahead);
+    }
+
+    #[test]
+    fn past_and_future_years() {
+        let past = calculate_goal_progress(goal(2024, 40), 30, date("2025-03-14"));
+        assert_eq!(
+            (past.status, past.expected, past.projected),
+            (GoalStatus::Missed, 40, 30)
+        );
+
+        let achieved = calculate_goal_progress(goal(2024, 40), 41, date("2025-03-14"));
+        assert_eq!(achieved.status, GoalStatus::Achieved);
+
+        let future = calculate_goal_progress(goal(2026, 40), 0, date("2025-03-14"));
+        assert_eq!((future.status, future.expected), (GoalStatus::OnTrack, 0));
+    }
+}
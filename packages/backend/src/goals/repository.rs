@@ -0,0 +1,206 @@
+use rusqlite::{OptionalExtension, Result};
+
+use crate::database::{Database, Repository, query_in_transation};
+use crate::goals::{Goal, GoalUnit};
+
+pub struct GoalFilter {
+    pub user_id: String,
+    pub year: Option<i32>,
+}
+
+impl GoalFilter {
+    pub fn new(user_id: &str) -> Self {
+        GoalFilter {
+            user_id: user_id.to_string(),
+            year: None,
+        }
+    }
+}
+
+pub struct GoalRepository {
+    db: Database,
+}
+
+impl GoalRepository {
+    pub fn new(db: Database) -> Self {
+        GoalRepository { db }
+    }
+
+    /// Completions of items of the kind during the year, or their pages.
+    /// Re-reads count in the year of each completion, so a later re-read
+    /// doesn't change the result of a past year.
+    pub fn completed_total(&mut self, user_id: &str, goal: &Goal) -> Result<i64> {
+        let total = match goal.unit {
+            GoalUnit::Items => "COUNT(*)",
+            GoalUnit::Pages => "COALESCE(SUM(l.page_count), 0)",
+        };
+        let sql = format!(
+            "SELECT {} FROM completion c
+                JOIN library l ON l.id = c.item_id
+                WHERE l.user_id = ?1 AND l.kind = ?2
+                    AND c.completed_at >= ?3 AND c.completed_at < ?4",
+            total
+        );
+        self.conn().query_row(
+            &sql,
+            rusqlite::params![
+                user_id,
+                goal.kind,
+                format!("{:04}-01-01", goal.year),
+                format!("{:04}-01-01", goal.year + 1)
+            ],
+            |row| row.get(0),
+        )
+    }
+
+    /// Id of the user's goal for the same year, kind and unit
+    pub fn find_existing(&mut self, goal: &Goal) -> Result<Option<String>> {
+        self.conn()
+            .query_row(
+                "SELECT id FROM goal WHERE user_id = ? AND year = ? AND kind = ? AND unit = ?",
+                rusqlite::params![goal.user_id, goal.year, goal.kind, goal.unit.as_str()],
+                |row| row.get(0),
+            )
+            .optional()
+    }
+}
+
+impl Repository<Goal, GoalFilter> for GoalRepository {
+    fn conn(&mut self) -> &mut rusqlite::Connection {
+        &mut self.db.conn
+    }
+
+    fn create(&mut self, goal: &Goal) -> Result<String> {
+        let tx = self.transaction()?;
+        tx.execute(
+            "INSERT INTO goal (id, user_id, year, kind, unit, target) VALUES (?, ?, ?, ?, ?, ?)",
+            rusqlite::params![
+                goal.id,
+                goal.user_id,
+                goal.year,
+                goal.kind,
+                goal.unit.as_str(),
+                goal.target
+            ],
+        )?;
+        tx.commit()?;
+        Ok(goal.id.clone())
+    }
+
+    fn read_by_id(&mut self, id: &str) -> Result<Option<Goal>> {
+        self.conn()
+            .query_row(
+                "SELECT id, user_id, year, kind, unit, target FROM goal WHERE id = ?",
+                [id],
+                row_to_goal,
+            )
+            .optional()
+    }
+
+    fn search(&mut self, filter: GoalFilter) -> Result<Vec<Goal>> {
+        let sql = "SELECT id, user_id, year, kind, unit, target FROM goal
+            WHERE user_id = ?1 AND (?2 IS NULL OR year = ?2)
+            ORDER BY year DESC, kind, unit";
+
+        let tx = self.transaction()?;
+        let goals = query_in_transation(
+            &tx,
+            sql,
+            rusqlite::params![filter.user_id, filter.year],
+            row_to_goal,
+        )?;
+        tx.commit()?;
+        Ok(goals)
+    }
+
+    fn update(&mut self, id: &str, goal: &Goal) -> Result<bool> {
+        let tx = self.transaction()?;
+        let result = tx.execute(
+            "UPDATE goal SET year = ?, kind = ?, unit = ?, target = ? WHERE id = ?",
+            rusqlite::params![goal.year, goal.kind, goal.unit.as_str(), goal.target, id],
+        )?;
+        tx.commit()?;
+        Ok(result == 1)
+    }
+
+    fn delete(&mut self, id: &str) -> Result<bool> {
+        let tx = self.transaction()?;
+        let result = tx.execute("DELETE FROM goal WHERE id = ?", [id])?;
+        tx.commit()?;
+        Ok(result == 1)
+    }
+}
+
+fn row_to_goal(row: &rusqlite::Row) -> Result<Goal> {
+    let unit: String = row.get(4)?;
+    Ok(Goal {
+        id: row.get(0)?,
+        user_id: row.get(1)?,
+        year: row.get(2)?,
+        kind: row.get(3)?,
+        unit: GoalUnit::parse(&unit).ok_or_else(|| {
+            rusqlite::Error::FromSqlConversionFailure(
+                4,
+                rusqlite::types::Type::Text,
+                format!("Unknown goal unit {}", unit).into(),
+            )
+        })?,
+        target: row.get(5)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::migrated_test_database;
+    use crate::library::{Completion, LibraryItem, LibraryRepository, test_support};
+
+    fn goal(year: i32, unit: GoalUnit) -> Goal {
+        Goal {
+            id: format!("{}-{}", year, unit.as_str()),
+            user_id: "user".to_string(),
+            year,
+            kind: "Book".to_string(),
+            unit,
+            target: 10,
+        }
+    }
+
+    #[test]
+    fn rereads_keep_past_years() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = migrated_test_database(&dir);
+        let mut library = LibraryRepository::new(Database::new(&path).unwrap());
+        library
+            .create(&LibraryItem {
+                page_count: Some(300),
+                ..test_support::completed_book("a", "user", "2024-05-01")
+            })
+            .unwrap();
+        library
+            .create(&test_support::completed_book(
+                "other",
+                "other",
+                "2024-05-01",
+            ))
+            .unwrap();
+        library
+            .create_completion(&Completion {
+                id: "reread".to_string(),
+                item_id: "a".to_string(),
+                completed_at: "2026-02-01".to_string(),
+                note: None,
+            })
+            .unwrap();
+
+        let mut repo = GoalRepository::new(Database::new(&path).unwrap());
+        let total = |repo: &mut GoalRepository, year, unit| {
+            repo.completed_total("user", &goal(year, unit)).unwrap()
+        };
+        assert_eq!(total(&mut repo, 2024, GoalUnit::Items), 1);
+        assert_eq!(total(&mut repo, 2024, GoalUnit::Pages), 300);
+        assert_eq!(total(&mut repo, 2025, GoalUnit::Items), 0);
+        assert_eq!(total(&mut repo, 2026, GoalUnit::Items), 1);
+        assert_eq!(total(&mut repo, 2026, GoalUnit::Pages), 300);
+    }
+}
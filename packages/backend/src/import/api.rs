@@ -0,0 +1,156 @@
+use axum::{
+    Json, Router,
+    body::Bytes,
+    extract::{DefaultBodyLimit, Path, Query, State},
+    http::StatusCode,
+    routing::{delete, get, post},
+};
+use serde::Serialize;
+
+use crate::{
+    AppState,
+    auth::User,
+    import::{
+        IMPORT_BODY_LIMIT, ImportBatchSummary, ImportQuery, ImportReport, calibre,
+        csv_mapping::{self, CsvMapping, CsvPreview},
+        domain::{
+            get_import_batches, import_calibre_books, import_csv_upload, import_rows,
+            save_csv_upload, undo_import_batch,
+        },
+        goodreads, playnite,
+    },
+    utils::map_to_internal_error,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/import/goodreads", post(import_goodreads_route))
+        .route("/import/calibre", post(import_calibre_route))
+        .route("/import/playnite", post(import_playnite_route))
+        .route("/import/csv", post(upload_csv_route))
+        .route("/import/csv/{uploadId}", post(import_csv_route))
+        .route("/import/batches", get(get_import_batches_route))
+        .route("/import/batches/{id}", delete(undo_import_batch_route))
+        .layer(DefaultBodyLimit::max(IMPORT_BODY_LIMIT))
+}
+
+async fn import_goodreads_route(
+    user: User,
+    state: State<AppState>,
+    Query(query): Query<ImportQuery>,
+    body: String,
+) -> Result<Json<ImportReport>, StatusCode> {
+    let rows = goodreads::parse(&body).map_err(|e| {
+        println!("Invalid Goodreads export: {}", e);
+        StatusCode::UNPROCESSABLE_ENTITY
+    })?;
+
+    let report = import_rows(&user, &state, goodreads::SOURCE, rows, query.dry_run)
+        .map_err(map_to_internal_error)?;
+
+    Ok(Json(report))
+}
+
+/// Accepts the `metadata.db` file of a Calibre library as the request body
+async fn import_calibre_route(
+    user: User,
+    state: State<AppState>,
+    Query(query): Query<ImportQuery>,
+    body: Bytes,
+) -> Result<Json<ImportReport>, StatusCode> {
+    // SQLite can only open files, so the upload is written to a temporary
+    // file that is removed when dropped
+    let mut file = tempfile::NamedTempFile::new().map_err(|e| map_to_internal_error(e.into()))?;
+    std::io::Write::write_all(&mut file, &body).map_err(|e| map_to_internal_error(e.into()))?;
+
+    let books = calibre::read_file(file.path()).map_err(|e| {
+        println!("Invalid Calibre library: {}", e);
+        StatusCode::UNPROCESSABLE_ENTITY
+    })?;
+
+    let report =
+        import_calibre_books(&user, &state, books, query.dry_run).map_err(map_to_internal_error)?;
+
+    Ok(Json(report))
+}
+
+async fn import_playnite_route(
+    user: User,
+    state: State<AppState>,
+    Query(query): Query<ImportQuery>,
+    body: String,
+) -> Result<Json<ImportReport>, StatusCode> {
+    let rows = playnite::parse(&body).map_err(|e| {
+        println!("Invalid Playnite export: {}", e);
+        StatusCode::UNPROCESSABLE_ENTITY
+    })?;
+
+    let report = import_rows(&user, &state, playnite::SOURCE, rows, query.dry_run)
+        .map_err(map_to_internal_error)?;
+
+    Ok(Json(report))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UndoResponse {
+    deleted: usize,
+}
+
+/// First step of a generic CSV import, returns the columns to map
+async fn upload_csv_route(
+    user: User,
+    state: State<AppState>,
+    body: String,
+) -> Result<Json<CsvPreview>, StatusCode> {
+    let (columns, row_count) = csv_mapping::preview(&body).map_err(|e| {
+        println!("Invalid CSV file: {}", e);
+        StatusCode::UNPROCESSABLE_ENTITY
+    })?;
+
+    let preview =
+        save_csv_upload(&user, &state, body, columns, row_count).map_err(map_to_internal_error)?;
+
+    Ok(Json(preview))
+}
+
+async fn import_csv_route(
+    user: User,
+    state: State<AppState>,
+    Path(upload_id): Path<String>,
+    Query(query): Query<ImportQuery>,
+    Json(mapping): Json<CsvMapping>,
+) -> Result<Json<ImportReport>, StatusCode> {
+    let report = import_csv_upload(&user, &state, &upload_id, &mapping, query.dry_run)
+        .map_err(map_import_error)?;
+
+    Ok(Json(report))
+}
+
+async fn get_import_batches_route(
+    user: User,
+    state: State<AppState>,
+) -> Result<Json<Vec<ImportBatchSummary>>, StatusCode> {
+    let batches = get_import_batches(&user, &state).map_err(map_to_internal_error)?;
+    Ok(Json(batches))
+}
+
+async fn undo_import_batch_route(
+    user: User,
+    state: State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<UndoResponse>, StatusCode> {
+    let deleted = undo_import_batch(&user, &state, &id).map_err(map_import_error)?;
+    Ok(Json(UndoResponse { deleted }))
+}
+
+fn map_import_error(err: Box<dyn std::error::Error>) -> StatusCode {
+    let message = err.to_string();
+    if message.contains("not found") {
+        StatusCode::NOT_FOUND
+    } else if message.starts_with("Invalid") {
+        StatusCode::UNPROCESSABLE_ENTITY
+    } else {
+        map_to_internal_error(err)
+    }
+}
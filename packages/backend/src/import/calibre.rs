@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+
+use rusqlite::{Connection, OpenFlags, Result};
+
+use crate::metadata::isbn;
+
+pub const SOURCE: &str = "calibre";
+
+/// A book in a Calibre library
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalibreBook {
+    /// The book's uuid, stable across re-imports
+    pub external_id: String,
+    pub title: String,
+    pub authors: Vec<String>,
+    pub isbn: Option<String>,
+    pub series: Option<String>,
+    /// Position in the series, fractional for novellas between books
+    pub series_index: Option<f64>,
+    pub tags: Vec<String>,
+}
+
+impl CalibreBook {
+    pub fn author(&self) -> String {
+        self.authors.join(", ")
+    }
+}
+
+/// Reads the books of a Calibre `metadata.db` without modifying it
+pub fn read_file(path: &std::path::Path) -> Result<Vec<CalibreBook>> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    read_books(&conn)
+}
+
+pub fn read_books(conn: &Connection) -> Result<Vec<CalibreBook>> {
+    let authors = read_links(
+        conn,
+        "SELECT bal.book, a.name FROM books_authors_link bal 
+            JOIN authors a ON a.id = bal.author ORDER BY bal.id",
+    )?;
+    let mut series = read_links(
+        conn,
+        "SELECT bsl.book, s.name FROM books_series_link bsl 
+            JOIN series s ON s.id = bsl.series",
+    )?;
+    let mut tags = read_links(
+        conn,
+        "SELECT btl.book, t.name FROM books_tags_link btl 
+            JOIN tags t ON t.id = btl.tag ORDER BY t.name",
+    )?;
+    let mut isbns = read_links(
+        conn,
+        "SELECT book, val FROM identifiers WHERE type = 'isbn'",
+    )?;
+
+    let mut stmt =
+        conn.prepare("SELECT id, uuid, title, isbn, series_index FROM books ORDER BY id")?;
+    let books = stmt
+        .query_map([], |row| {
+            let id: i64 = row.get(0)?;
+            let uuid: Option<String> = row.get(1)?;
+            let legacy_isbn: Option<String> = row.get(3)?;
+            let series = series
+                .remove(&id)
+                .and_then(|names| names.into_iter().next());
+
+            let isbn = isbns
+                .remove(&id)
+                .unwrap_or_default()
+                .into_iter()
+                .chain(legacy_isbn)
+                .find_map(|value| isbn::normalize(&value));
+
+            Ok(CalibreBook {
+                external_id: uuid.unwrap_or_else(|| id.to_string()),
+                title: row.get(2)?,
+                authors: authors.get(&id).cloned().unwrap_or_default(),
+                isbn,
+                series_index: if series.is_some() { row.get(4)? } else { None },
+                series,
+                tags: tags.remove(&id).unwrap_or_default(),
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(books)
+}
+
+/// Values of a link table grouped by book id
+fn read_links(conn: &Connection, sql: &str) -> Result<HashMap<i64, Vec<String>>> {
+    let mut stmt = conn.prepare(sql)?;
+    let mut links: HashMap<i64, Vec<String>> = HashMap::new();
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get(1)?)))?;
+    for row in rows {
+        let (book, value) = row?;
+        links.entry(book).or_default().push(value);
+    }
+    Ok(links)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_calibre_books() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE books (id INTEGER PRIMARY KEY, uuid TEXT, title TEXT, isbn TEXT, series_index REAL);
+            CREATE TABLE authors (id INTEGER PRIMARY KEY, name TEXT);
+            CREATE TABLE books_authors_link (id INTEGER PRIMARY KEY, book INTEGER, author INTEGER);
+            CREATE TABLE series (id INTEGER PRIMARY KEY, name TEXT);
+            CREATE TABLE books_series_link (id INTEGER PRIMARY KEY, book INTEGER, series INTEGER);
+            CREATE TABLE tags (id INTEGER PRIMARY KEY, name TEXT);
+            CREATE TABLE books_tags_link (id INTEGER PRIMARY KEY, book INTEGER, tag INTEGER);
+            CREATE TABLE identifiers (id INTEGER PRIMARY KEY, book INTEGER, type TEXT, val TEXT);
+            INSERT INTO books VALUES (1, 'b-1', 'Good Omens', '', 2.0);
+            INSERT INTO authors VALUES (1, 'Terry Pratchett'), (2, 'Neil Gaiman');
+            INSERT INTO books_authors_link VALUES (1, 1, 1), (2, 1, 2);
+            INSERT INTO series VALUES (1, 'Omens');
+            INSERT INTO books_series_link VALUES (1, 1, 1);
+            INSERT INTO tags VALUES (1, 'Fantasy'), (2, 'Comedy');
+            INSERT INTO books_tags_link VALUES (1, 1, 1), (2, 1, 2);
+            INSERT INTO identifiers VALUES (1, 1, 'isbn', '0-306-40615-2');",
+        )
+        .unwrap();
+
+        let books = read_books(&conn).unwrap();
+
+        assert_eq!(
+            books,
+            vec![CalibreBook {
+                external_id: "b-1".to_string(),
+                title: "Good Omens".to_string(),
+                authors: vec!["Terry Pratchett".to_string(), "Neil Gaiman".to_string()],
+                isbn: Some("9780306406157".to_string()),
+                series: Some("Omens".to_string()),
+                series_index: Some(2.0),
+                tags: vec!["Comedy".to_string(), "Fantasy".to_string()],
+            }]
+        );
+    }
+}
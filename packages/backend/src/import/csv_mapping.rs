@@ -0,0 +1,277 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    import::ImportRow,
+    library::{ItemDetails, NewLibraryItem},
+};
+
+pub const SOURCE: &str = "csv";
+
+const SAMPLE_ROWS: usize = 5;
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+const TRUTHY_VALUES: &[&str] = &["true", "1", "yes", "x", "kyllä", "k"];
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CsvColumn {
+    pub name: String,
+    pub samples: Vec<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CsvPreview {
+    /// Id to submit the column mapping for
+    pub upload_id: String,
+    pub columns: Vec<CsvColumn>,
+    pub row_count: usize,
+}
+
+/// Columns of the uploaded file for each item field, by header name
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CsvMapping {
+    /// Column with the kind of each row, `default_kind` is used when missing
+    pub kind: Option<String>,
+    #[serde(default = "default_kind")]
+    pub default_kind: String,
+    pub title: String,
+    pub author: String,
+    pub translator: Option<String>,
+    pub completed_at: Option<String>,
+    /// chrono format of `completed_at`, ISO 8601 dates by default
+    pub date_format: Option<String>,
+    pub favorite: Option<String>,
+}
+
+fn default_kind() -> String {
+    "Book".to_string()
+}
+
+/// Column indexes of a mapping in a file
+struct MappedColumns {
+    kind: Option<usize>,
+    title: usize,
+    author: usize,
+    translator: Option<usize>,
+    completed_at: Option<usize>,
+    favorite: Option<usize>,
+}
+
+/// Spreadsheets exported with a Finnish locale use semicolons
+fn detect_delimiter(csv: &str) -> u8 {
+    let header = csv.lines().next().unwrap_or_default();
+    [b',', b';', b'\t']
+        .into_iter()
+        .max_by_key(|delimiter| header.bytes().filter(|b| b == delimiter).count())
+        .unwrap_or(b',')
+}
+
+fn reader(csv: &str) -> csv::Reader<&[u8]> {
+    csv::ReaderBuilder::new()
+        .delimiter(detect_delimiter(csv))
+        .flexible(true)
+        .from_reader(csv.as_bytes())
+}
+
+/// Columns of the file with a few sample values each, and the number of rows
+pub fn preview(csv: &str) -> Result<(Vec<CsvColumn>, usize), String> {
+    let mut reader = reader(csv);
+    let headers = reader.headers().map_err(|e| e.to_string())?.clone();
+    if headers.iter().all(|h| h.trim().is_empty()) {
+        return Err("Missing header row".to_string());
+    }
+
+    let mut columns: Vec<CsvColumn> = headers
+        .iter()
+        .map(|name| CsvColumn {
+            name: name.trim().to_string(),
+            samples: Vec::new(),
+        })
+        .collect();
+
+    let mut row_count = 0;
+    for record in reader.records() {
+        let record = record.map_err(|e| e.to_string())?;
+        if row_count < SAMPLE_ROWS {
+            for (column, value) in columns.iter_mut().zip(record.iter()) {
+                column.samples.push(value.trim().to_string());
+            }
+        }
+        row_count += 1;
+    }
+
+    Ok((columns, row_count))
+}
+
+/// Maps the rows of the file to items. Problems with the mapping fail the
+/// whole file while problems with values fail only their row.
+pub fn parse(csv: &str, mapping: &CsvMapping) -> Result<Vec<ImportRow>, String> {
+    if canonical_kind(&mapping.default_kind).is_none() {
+        return Err(format!("Unknown kind {}", mapping.default_kind));
+    }
+
+    let mut reader = reader(csv);
+    let headers = reader.headers().map_err(|e| e.to_string())?.clone();
+    let index = |column: &str| {
+        headers
+            .iter()
+            .position(|h| h.trim() == column)
+            .ok_or_else(|| format!("Unknown column {}", column))
+    };
+    let optional_index = |column: &Option<String>| column.as_deref().map(index).transpose();
+
+    let columns = MappedColumns {
+        kind: optional_index(&mapping.kind)?,
+        title: index(&mapping.title)?,
+        author: index(&mapping.author)?,
+        translator: optional_index(&mapping.translator)?,
+        completed_at: optional_index(&mapping.completed_at)?,
+        favorite: optional_index(&mapping.favorite)?,
+    };
+
+    let rows = reader
+        .records()
+        .enumerate()
+        .map(|(i, record)| ImportRow {
+            row: i + 1,
+            external_id: None,
+            item: record
+                .map_err(|e| e.to_string())
+                .and_then(|record| to_new_item(&record, &columns, mapping)),
+        })
+        .collect();
+
+    Ok(rows)
+}
+
+fn to_new_item(
+    record: &csv::StringRecord,
+    columns: &MappedColumns,
+    mapping: &CsvMapping,
+) -> Result<NewLibraryItem, String> {
+    let value = |index: Option<usize>| {
+        index
+            .and_then(|i| record.get(i))
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+    };
+
+    let title = value(Some(columns.title)).ok_or("Missing title")?;
+    let author = value(Some(columns.author)).ok_or("Missing author")?;
+
+    let kind_value = value(columns.kind).unwrap_or(&mapping.default_kind);
+    let kind = canonical_kind(kind_value).ok_or_else(|| format!("Unknown kind {}", kind_value))?;
+    let translator = value(columns.translator).map(str::to_string);
+    let details = ItemDetails::from_parts(kind, author.to_string(), translator)
+        .ok_or_else(|| format!("Unknown kind {}", kind))?;
+
+    let date_format = mapping
+        .date_format
+        .as_deref()
+        .unwrap_or(DEFAULT_DATE_FORMAT);
+    let completed_at = value(columns.completed_at)
+        .map(|date| {
+            NaiveDate::parse_from_str(date, date_format)
+                .map(|date| date.to_string())
+                .map_err(|_| format!("Invalid date {}", date))
+        })
+        .transpose()?;
+
+    let favorite = value(columns.favorite)
+        .map(|v| TRUTHY_VALUES.contains(&v.to_lowercase().as_str()))
+        .unwrap_or(false);
+
+    Ok(NewLibraryItem {
+        title: title.to_string(),
+        status: None,
+        started_at: None,
+        completed_at,
+        isbn: None,
+        page_count: None,
+        expected_hours: None,
+        language: None,
+        original_language: None,
+        favorite,
+        rating: None,
+        review: None,
+        notes: None,
+        activated_challenge_ids: Vec::new(),
+        details,
+    })
+}
+
+/// Kind names are matched case-insensitively
+fn canonical_kind(kind: &str) -> Option<&'static str> {
+    ItemDetails::KINDS
+        .iter()
+        .find(|known| known.eq_ignore_ascii_case(kind.trim()))
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::library::ItemStatus;
+
+    const SHEET: &str = "Nimi;Kirjailija;Luettu;Tyyppi;Suosikki
+Sinuhe egyptiläinen;Mika Waltari;14.5.2023;kirja;x
+Alan Wake;Remedy;1.2.2024;game;
+Tuntematon;;;book;
+";
+
+    fn mapping() -> CsvMapping {
+        CsvMapping {
+            kind: None,
+            default_kind: "Book".to_string(),
+            title: "Nimi".to_string(),
+            author: "Kirjailija".to_string(),
+            translator: None,
+            completed_at: Some("Luettu".to_string()),
+            date_format: Some("%d.%m.%Y".to_string()),
+            favorite: Some("Suosikki".to_string()),
+        }
+    }
+
+    #[test]
+    fn previews_semicolon_separated_files() {
+        let (columns, row_count) = preview(SHEET).unwrap();
+
+        assert_eq!(row_count, 3);
+        assert_eq!(columns[0].name, "Nimi");
+        assert_eq!(columns[2].samples[0], "14.5.2023");
+    }
+
+    #[test]
+    fn maps_rows_with_per_row_errors() {
+        let rows = parse(SHEET, &mapping()).unwrap();
+
+        let book = rows[0].item.as_ref().unwrap();
+        assert_eq!(book.completed_at.as_deref(), Some("2023-05-14"));
+        assert!(book.favorite);
+        assert_eq!(book.status, None);
+        assert_eq!(
+            book.to_library_item("u", "now").status,
+            ItemStatus::Completed
+        );
+        assert_eq!(rows[2].item.as_ref().unwrap_err(), "Missing author");
+
+        let mapping = CsvMapping {
+            kind: Some("Tyyppi".to_string()),
+            ..mapping()
+        };
+        let rows = parse(SHEET, &mapping).unwrap();
+        assert!(rows[0].item.is_err());
+        assert_eq!(rows[1].item.as_ref().unwrap().details.kind(), "Game");
+    }
+
+    #[test]
+    fn rejects_unknown_columns() {
+        let mapping = CsvMapping {
+            title: "Title".to_string(),
+            ..mapping()
+        };
+        assert!(parse(SHEET, &mapping).is_err());
+    }
+}
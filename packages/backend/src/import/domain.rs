@@ -0,0 +1,412 @@
+use rusqlite::Connection;
+
+use crate::{
+    AppState,
+    auth::User,
+    contributors::sync_legacy_contributors,
+    covers::purge_deleted_covers,
+    database::Database,
+    import::{
+        ImportBatchSummary, ImportReport, ImportRow, RowOutcome,
+        calibre::{self, CalibreBook},
+        csv_mapping::{self, CsvColumn, CsvMapping, CsvPreview},
+        repository::{
+            ExistingItems, ImportRepository, add_batch_item, create_batch, find_external_item,
+            link_external_item,
+        },
+    },
+    library::{
+        ItemDetails, ItemStatus, NewLibraryItem, insert_library_item, read_library_item,
+        update_imported_item,
+    },
+    series::{ensure_series, link_series_entry},
+    tags::ensure_tag,
+};
+
+/// Creates library items from parsed rows in a single transaction, skipping
+/// items already in the library. Rows with an external id that were
+/// imported from `source` before update their item instead. Imported
+/// history doesn't activate challenges.
+pub fn import_rows(
+    user: &User,
+    state: &AppState,
+    source: &str,
+    rows: Vec<ImportRow>,
+    dry_run: bool,
+) -> Result<ImportReport, Box<dyn std::error::Error>> {
+    let mut db = Database::new(&state.database_path)?;
+    let mut tx = db.conn.transaction()?;
+    let mut existing = ExistingItems::load(&tx, &user.id)?;
+    let now = chrono::Utc::now().to_rfc3339();
+    let batch_id = create_batch(&tx, &user.id, source, &now)?;
+    let mut report = ImportReport::new(dry_run, &batch_id);
+
+    for row in rows {
+        let new_item = match row.item {
+            Ok(item) => item,
+            Err(reason) => {
+                report.record(row.row, None, RowOutcome::Failed, Some(reason));
+                continue;
+            }
+        };
+        let title = Some(new_item.title.as_str());
+
+        if !new_item.has_valid_rating() {
+            let reason = Some("Invalid rating".to_string());
+            report.record(row.row, title, RowOutcome::Failed, reason);
+            continue;
+        }
+        if !new_item.has_valid_isbn() {
+            let reason = Some("Invalid ISBN".to_string());
+            report.record(row.row, title, RowOutcome::Failed, reason);
+            continue;
+        }
+
+        let batch = ImportBatch {
+            id: &batch_id,
+            source,
+            external_id: row.external_id.as_deref(),
+        };
+
+        // A failing row is rolled back on its own without losing the others
+        let savepoint = tx.savepoint()?;
+        match import_row(&savepoint, user, &batch, &new_item, &mut existing, &now) {
+            Ok((outcome, reason)) => {
+                savepoint.commit()?;
+                report.record(row.row, title, outcome, reason);
+            }
+            Err(e) => {
+                drop(savepoint);
+                report.record(row.row, title, RowOutcome::Failed, Some(e.to_string()));
+            }
+        }
+    }
+
+    if !dry_run {
+        tx.commit()?;
+    }
+
+    Ok(report)
+}
+
+/// Where an imported row comes from
+struct ImportBatch<'a> {
+    id: &'a str,
+    source: &'a str,
+    external_id: Option<&'a str>,
+}
+
+fn import_row(
+    conn: &Connection,
+    user: &User,
+    batch: &ImportBatch,
+    new_item: &NewLibraryItem,
+    existing: &mut ExistingItems,
+    now: &str,
+) -> rusqlite::Result<(RowOutcome, Option<String>)> {
+    if let Some(external_id) = batch.external_id
+        && let Some(item_id) = find_external_item(conn, &user.id, batch.source, external_id)?
+    {
+        let changes = update_item(conn, &item_id, new_item, now)?;
+        return Ok(if changes.is_empty() {
+            (RowOutcome::Skipped, Some("Unchanged".to_string()))
+        } else {
+            (
+                RowOutcome::Updated,
+                Some(format!("Changed {}", changes.join(", "))),
+            )
+        });
+    }
+
+    let item = new_item.to_library_item(&user.id, now);
+    if let Some(item_id) = existing.find(&item) {
+        if let Some(external_id) = batch.external_id {
+            link_external_item(conn, &user.id, batch.source, external_id, item_id)?;
+        }
+        return Ok((RowOutcome::Skipped, Some("Already in library".to_string())));
+    }
+
+    insert_library_item(conn, &item)?;
+    add_batch_item(conn, batch.id, &item.id)?;
+    if let Some(external_id) = batch.external_id {
+        link_external_item(conn, &user.id, batch.source, external_id, &item.id)?;
+    }
+    existing.insert(&item);
+
+    Ok((RowOutcome::Created, None))
+}
+
+/// Applies an imported row to an item imported before and returns the names
+/// of the fields that changed. Dates already recorded are kept, so that
+/// playing a finished game again doesn't move its completion.
+fn update_item(
+    conn: &Connection,
+    item_id: &str,
+    new_item: &NewLibraryItem,
+    now: &str,
+) -> rusqlite::Result<Vec<String>> {
+    let current = read_library_item(conn, item_id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+
+    let mut item = new_item.to_library_item(&current.user_id, now);
+    item.id = current.id.clone();
+    if current.started_at.is_some() {
+        item.started_at = current.started_at.clone();
+    }
+    if current.status == item.status && current.completed_at.is_some() {
+        item.completed_at = current.completed_at.clone();
+    }
+
+    let mut changes = Vec::new();
+    if current.title != item.title {
+        changes.push("title".to_string());
+    }
+    if current.status != item.status {
+        changes.push("status".to_string());
+    }
+    if current.completed_at != item.completed_at {
+        changes.push("completedAt".to_string());
+    }
+    changes.extend(changed_details(&current.details, &item.details));
+
+    if !changes.is_empty() {
+        update_imported_item(conn, &item)?;
+    }
+    Ok(changes)
+}
+
+/// Names of the serialized detail fields that differ
+fn changed_details(current: &ItemDetails, updated: &ItemDetails) -> Vec<String> {
+    let (Ok(current), Ok(updated)) = (serde_json::to_value(current), serde_json::to_value(updated))
+    else {
+        return Vec::new();
+    };
+    let (Some(current), Some(updated)) = (current.as_object(), updated.as_object()) else {
+        return Vec::new();
+    };
+
+    updated
+        .iter()
+        .filter(|(key, value)| current.get(*key) != Some(*value))
+        .map(|(key, _)| key.clone())
+        .collect()
+}
+
+/// Imports Calibre books as planned books. Books imported before are found
+/// by their Calibre uuid and get their title, authors, ISBN and tags
+/// updated, leaving the user's own status, dates and ratings alone. Books
+/// in a Calibre series are added to the series of the same name.
+pub fn import_calibre_books(
+    user: &User,
+    state: &AppState,
+    books: Vec<CalibreBook>,
+    dry_run: bool,
+) -> Result<ImportReport, Box<dyn std::error::Error>> {
+    let mut db = Database::new(&state.database_path)?;
+    let mut tx = db.conn.transaction()?;
+    let mut existing = ExistingItems::load(&tx, &user.id)?;
+    let now = chrono::Utc::now().to_rfc3339();
+    let batch_id = create_batch(&tx, &user.id, calibre::SOURCE, &now)?;
+    let mut report = ImportReport::new(dry_run, &batch_id);
+
+    for (index, book) in books.iter().enumerate() {
+        let row = index + 1;
+        let title = Some(book.title.as_str());
+
+        let savepoint = tx.savepoint()?;
+        let result = import_calibre_book(&savepoint, user, &batch_id, book, &mut existing, &now);
+        match result {
+            Ok((outcome, reason)) => {
+                savepoint.commit()?;
+                report.record(row, title, outcome, reason);
+            }
+            Err(e) => {
+                drop(savepoint);
+                report.record(row, title, RowOutcome::Failed, Some(e.to_string()));
+            }
+        }
+    }
+
+    if !dry_run {
+        tx.commit()?;
+    }
+
+    Ok(report)
+}
+
+fn import_calibre_book(
+    conn: &Connection,
+    user: &User,
+    batch_id: &str,
+    book: &CalibreBook,
+    existing: &mut ExistingItems,
+    now: &str,
+) -> rusqlite::Result<(RowOutcome, Option<String>)> {
+    if let Some(item_id) = find_external_item(conn, &user.id, calibre::SOURCE, &book.external_id)? {
+        let mut changed = conn.execute(
+            "UPDATE library SET title = ?1, author = ?2, isbn = ?3 
+                WHERE id = ?4 AND (title IS NOT ?1 OR author IS NOT ?2 OR isbn IS NOT ?3)",
+            rusqlite::params![book.title, book.author(), book.isbn, item_id],
+        )?;
+        if changed > 0 {
+            let details = ItemDetails::Book {
+                author: book.author(),
+                translator: read_library_item(conn, &item_id)?
+                    .and_then(|item| item.details.translator().map(str::to_string)),
+            };
+            sync_legacy_contributors(conn, &item_id, &user.id, &details)?;
+        }
+        for name in &book.tags {
+            changed += tag_item(conn, &user.id, &item_id, name)?;
+        }
+        changed += add_to_series(conn, &user.id, &item_id, book)?;
+
+        return Ok(if changed > 0 {
+            (RowOutcome::Updated, None)
+        } else {
+            (RowOutcome::Skipped, Some("Unchanged".to_string()))
+        });
+    }
+
+    let item = NewLibraryItem {
+        title: book.title.clone(),
+        status: Some(ItemStatus::Planned),
+        started_at: None,
+        completed_at: None,
+        isbn: book.isbn.clone(),
+        page_count: None,
+        expected_hours: None,
+        language: None,
+        original_language: None,
+        favorite: false,
+        rating: None,
+        review: None,
+        notes: None,
+        activated_challenge_ids: Vec::new(),
+        details: ItemDetails::Book {
+            author: book.author(),
+            translator: None,
+        },
+    }
+    .to_library_item(&user.id, now);
+
+    // Link books added by hand so that later imports keep them up to date
+    if let Some(item_id) = existing.find(&item) {
+        link_external_item(conn, &user.id, calibre::SOURCE, &book.external_id, item_id)?;
+        return Ok((RowOutcome::Skipped, Some("Already in library".to_string())));
+    }
+
+    insert_library_item(conn, &item)?;
+    add_batch_item(conn, batch_id, &item.id)?;
+    link_external_item(conn, &user.id, calibre::SOURCE, &book.external_id, &item.id)?;
+    for name in &book.tags {
+        tag_item(conn, &user.id, &item.id, name)?;
+    }
+    add_to_series(conn, &user.id, &item.id, book)?;
+    existing.insert(&item);
+
+    Ok((RowOutcome::Created, None))
+}
+
+fn tag_item(
+    conn: &Connection,
+    user_id: &str,
+    item_id: &str,
+    name: &str,
+) -> rusqlite::Result<usize> {
+    if name.trim().is_empty() {
+        return Ok(0);
+    }
+    let tag_id = ensure_tag(conn, user_id, name)?;
+    conn.execute(
+        "INSERT OR IGNORE INTO item_tag (item_id, tag_id) VALUES (?, ?)",
+        [item_id, &tag_id],
+    )
+}
+
+fn add_to_series(
+    conn: &Connection,
+    user_id: &str,
+    item_id: &str,
+    book: &CalibreBook,
+) -> rusqlite::Result<usize> {
+    let Some(name) = book
+        .series
+        .as_deref()
+        .filter(|name| !name.trim().is_empty())
+    else {
+        return Ok(0);
+    };
+    let series_id = ensure_series(conn, user_id, name)?;
+    let position = book.series_index.filter(|i| *i > 0.0).unwrap_or(1.0);
+    link_series_entry(conn, &series_id, item_id, position)
+}
+
+/// Stores an uploaded CSV file for the column mapping step
+pub fn save_csv_upload(
+    user: &User,
+    state: &AppState,
+    content: String,
+    columns: Vec<CsvColumn>,
+    row_count: usize,
+) -> Result<CsvPreview, Box<dyn std::error::Error>> {
+    let db = Database::new(&state.database_path)?;
+    let mut repo = ImportRepository::new(db);
+    let upload_id = uuid::Uuid::new_v4().to_string();
+    repo.save_upload(&upload_id, &user.id, &content)?;
+
+    Ok(CsvPreview {
+        upload_id,
+        columns,
+        row_count,
+    })
+}
+
+/// Imports an uploaded CSV file with the given column mapping. The upload
+/// is removed once the import is saved.
+pub fn import_csv_upload(
+    user: &User,
+    state: &AppState,
+    upload_id: &str,
+    mapping: &CsvMapping,
+    dry_run: bool,
+) -> Result<ImportReport, Box<dyn std::error::Error>> {
+    let db = Database::new(&state.database_path)?;
+    let mut repo = ImportRepository::new(db);
+    let content = repo
+        .read_upload(upload_id, &user.id)?
+        .ok_or("Upload not found")?;
+
+    let rows =
+        csv_mapping::parse(&content, mapping).map_err(|e| format!("Invalid mapping: {}", e))?;
+    let report = import_rows(user, state, csv_mapping::SOURCE, rows, dry_run)?;
+
+    if !dry_run {
+        repo.delete_upload(upload_id)?;
+    }
+    Ok(report)
+}
+
+pub fn get_import_batches(
+    user: &User,
+    state: &AppState,
+) -> Result<Vec<ImportBatchSummary>, Box<dyn std::error::Error>> {
+    let db = Database::new(&state.database_path)?;
+    let mut repo = ImportRepository::new(db);
+    Ok(repo.batches(&user.id)?)
+}
+
+/// Deletes the items created by an import. Items that an import only
+/// updated keep their new values.
+pub fn undo_import_batch(
+    user: &User,
+    state: &AppState,
+    batch_id: &str,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let db = Database::new(&state.database_path)?;
+    let mut repo = ImportRepository::new(db);
+    let deleted = repo
+        .undo_batch(batch_id, &user.id)?
+        .ok_or("Import batch not found")?;
+    purge_deleted_covers(state);
+    Ok(deleted)
+}
@@ -0,0 +1,169 @@
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+use crate::{
+    import::ImportRow,
+    library::{ItemDetails, ItemStatus, NewLibraryItem},
+    metadata::isbn,
+};
+
+pub const SOURCE: &str = "goodreads";
+
+const REQUIRED_COLUMNS: &[&str] = &["Title", "Author", "Exclusive Shelf"];
+
+/// The columns of a Goodreads library export that are imported
+#[derive(Deserialize)]
+struct GoodreadsRow {
+    #[serde(rename = "Title")]
+    title: String,
+    #[serde(rename = "Author")]
+    author: String,
+    #[serde(rename = "ISBN", default)]
+    isbn: String,
+    #[serde(rename = "ISBN13", default)]
+    isbn13: String,
+    #[serde(rename = "My Rating", default)]
+    rating: String,
+    #[serde(rename = "Number of Pages", default)]
+    pages: String,
+    #[serde(rename = "Date Read", default)]
+    date_read: String,
+    #[serde(rename = "Date Added", default)]
+    date_added: String,
+    #[serde(rename = "Exclusive Shelf")]
+    shelf: String,
+    #[serde(rename = "My Review", default)]
+    review: String,
+    #[serde(rename = "Private Notes", default)]
+    notes: String,
+}
+
+/// Maps a Goodreads export to books. Books on the "read" shelf are
+/// completed on their read date, or the date they were added when it's
+/// missing.
+pub fn parse(csv: &str) -> Result<Vec<ImportRow>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(csv.as_bytes());
+
+    let headers = reader.headers().map_err(|e| e.to_string())?.clone();
+    if let Some(missing) = REQUIRED_COLUMNS
+        .iter()
+        .find(|column| !headers.iter().any(|h| h == **column))
+    {
+        return Err(format!("Missing column {}", missing));
+    }
+
+    let rows = reader
+        .deserialize::<GoodreadsRow>()
+        .enumerate()
+        .map(|(index, row)| ImportRow {
+            row: index + 1,
+            external_id: None,
+            item: row.map_err(|e| e.to_string()).and_then(to_new_item),
+        })
+        .collect();
+
+    Ok(rows)
+}
+
+fn to_new_item(row: GoodreadsRow) -> Result<NewLibraryItem, String> {
+    if row.title.trim().is_empty() {
+        return Err("Missing title".to_string());
+    }
+
+    let date_read = parse_date(&row.date_read)?;
+    let date_added = parse_date(&row.date_added)?;
+    let (status, completed_at) = match row.shelf.as_str() {
+        "read" => (ItemStatus::Completed, date_read.or(date_added)),
+        "currently-reading" => (ItemStatus::InProgress, None),
+        _ => (ItemStatus::Planned, None),
+    };
+
+    let rating = match row.rating.trim() {
+        "" | "0" => None,
+        value => Some(
+            value
+                .parse::<f64>()
+                .map_err(|_| format!("Invalid rating {}", value))?,
+        ),
+    };
+
+    // Goodreads writes ISBNs as spreadsheet formulas, ="0306406152"
+    let isbn = [&row.isbn13, &row.isbn]
+        .iter()
+        .map(|value| value.trim_matches(|c| c == '=' || c == '"'))
+        .find_map(isbn::normalize);
+
+    Ok(NewLibraryItem {
+        title: row.title.trim().to_string(),
+        status: Some(status),
+        started_at: None,
+        completed_at,
+        isbn,
+        page_count: row.pages.trim().parse().ok(),
+        expected_hours: None,
+        language: None,
+        original_language: None,
+        favorite: false,
+        rating,
+        review: non_empty(row.review),
+        notes: non_empty(row.notes),
+        activated_challenge_ids: Vec::new(),
+        details: ItemDetails::Book {
+            author: row.author.trim().to_string(),
+            translator: None,
+        },
+    })
+}
+
+/// Goodreads dates are formatted as 2024/03/31
+fn parse_date(value: &str) -> Result<Option<String>, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    NaiveDate::parse_from_str(value, "%Y/%m/%d")
+        .map(|date| Some(date.to_string()))
+        .map_err(|_| format!("Invalid date {}", value))
+}
+
+fn non_empty(value: String) -> Option<String> {
+    let trimmed = value.trim();
+    (!trimmed.is_empty()).then(|| trimmed.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPORT: &str = "Book Id,Title,Author,ISBN,ISBN13,My Rating,Number of Pages,Date Read,Date Added,Exclusive Shelf,My Review,Private Notes
+1,Sinuhe egyptiläinen,Mika Waltari,\"=\"\"0306406152\"\"\",\"=\"\"\"\"\",4,780,2023/05/14,2023/01/02,read,Loistava,
+2,Tuntematon sotilas,Väinö Linna,\"=\"\"\"\"\",\"=\"\"\"\"\",0,,,2024/02/01,to-read,,
+3,Broken,Someone,,,3,,14.5.2023,,read,,
+";
+
+    #[test]
+    fn maps_goodreads_rows() {
+        let rows = parse(EXPORT).unwrap();
+
+        let read = rows[0].item.as_ref().unwrap();
+        assert_eq!(read.status, Some(ItemStatus::Completed));
+        assert_eq!(read.completed_at.as_deref(), Some("2023-05-14"));
+        assert_eq!(read.isbn.as_deref(), Some("9780306406157"));
+        assert_eq!(read.rating, Some(4.0));
+        assert_eq!(read.page_count, Some(780));
+
+        let planned = rows[1].item.as_ref().unwrap();
+        assert_eq!(planned.status, Some(ItemStatus::Planned));
+        assert_eq!(planned.rating, None);
+        assert_eq!(planned.isbn, None);
+
+        assert!(rows[2].item.is_err());
+    }
+
+    #[test]
+    fn rejects_other_csv_files() {
+        assert!(parse("Name,Value\nfoo,bar\n").is_err());
+    }
+}
@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+
+use crate::library::NewLibraryItem;
+
+mod api;
+mod calibre;
+mod csv_mapping;
+mod domain;
+mod goodreads;
+mod playnite;
+mod repository;
+
+/// Largest import file accepted, Goodreads exports of long-time users are
+/// several megabytes
+pub const IMPORT_BODY_LIMIT: usize = 32 * 1024 * 1024;
+
+/// A row of an import file mapped to a library item. Rows are numbered
+/// from 1, not counting the header.
+pub struct ImportRow {
+    pub row: usize,
+    /// Id of the item in the source service, for incremental imports
+    pub external_id: Option<String>,
+    pub item: Result<NewLibraryItem, String>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum RowOutcome {
+    Created,
+    Updated,
+    Skipped,
+    Failed,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RowReport {
+    pub row: usize,
+    pub title: Option<String>,
+    pub outcome: RowOutcome,
+    pub reason: Option<String>,
+}
+
+/// Outcome of an import. A dry run reports the same outcomes without
+/// saving anything.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub dry_run: bool,
+    /// Batch to undo the import with, missing for dry runs
+    pub batch_id: Option<String>,
+    pub created: usize,
+    pub updated: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub rows: Vec<RowReport>,
+}
+
+impl ImportReport {
+    pub fn new(dry_run: bool, batch_id: &str) -> Self {
+        ImportReport {
+            dry_run,
+            batch_id: (!dry_run).then(|| batch_id.to_string()),
+            created: 0,
+            updated: 0,
+            skipped: 0,
+            failed: 0,
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn record(
+        &mut self,
+        row: usize,
+        title: Option<&str>,
+        outcome: RowOutcome,
+        reason: Option<String>,
+    ) {
+        match outcome {
+            RowOutcome::Created => self.created += 1,
+            RowOutcome::Updated => self.updated += 1,
+            RowOutcome::Skipped => self.skipped += 1,
+            RowOutcome::Failed => self.failed += 1,
+        }
+        self.rows.push(RowReport {
+            row,
+            title: title.map(|t| t.to_string()),
+            outcome,
+            reason,
+        });
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportBatchSummary {
+    pub id: String,
+    pub source: String,
+    pub created_at: String,
+    /// Items created by the batch that still exist
+    pub item_count: i64,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportQuery {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+pub use api::routes;
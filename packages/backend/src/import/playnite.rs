@@ -0,0 +1,148 @@
+use serde::Deserialize;
+
+use crate::{
+    import::ImportRow,
+    library::{ItemDetails, ItemStatus, NewLibraryItem},
+};
+
+pub const SOURCE: &str = "playnite";
+
+/// A game in a Playnite library export. Lists are exported either as names
+/// or as objects with a `Name`.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct PlayniteGame {
+    id: String,
+    name: String,
+    developers: Option<Vec<Named>>,
+    platforms: Option<Vec<Named>>,
+    completion_status: Option<Named>,
+    last_activity: Option<String>,
+    /// Seconds
+    playtime: Option<i64>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Named {
+    Name(String),
+    Object {
+        #[serde(rename = "Name")]
+        name: String,
+    },
+}
+
+impl Named {
+    fn name(&self) -> &str {
+        match self {
+            Named::Name(name) => name,
+            Named::Object { name } => name,
+        }
+    }
+}
+
+/// Maps a Playnite JSON export to games keyed by their Playnite id.
+/// Completed and beaten games are completed on the day they were last
+/// played.
+pub fn parse(json: &str) -> Result<Vec<ImportRow>, String> {
+    let entries: Vec<serde_json::Value> = serde_json::from_str(json).map_err(|e| e.to_string())?;
+
+    let rows = entries
+        .into_iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            let game = serde_json::from_value::<PlayniteGame>(entry).map_err(|e| e.to_string());
+            ImportRow {
+                row: index + 1,
+                external_id: game.as_ref().ok().map(|game| game.id.clone()),
+                item: game.map(to_new_item),
+            }
+        })
+        .collect();
+
+    Ok(rows)
+}
+
+fn to_new_item(game: PlayniteGame) -> NewLibraryItem {
+    let last_played = game
+        .last_activity
+        .as_deref()
+        .and_then(|date| date.get(..10))
+        .map(|date| date.to_string());
+
+    let completion_status = game.completion_status.as_ref().map(Named::name);
+    let status = match completion_status {
+        Some("Completed") | Some("Beaten") => ItemStatus::Completed,
+        Some("Playing") | Some("Played") | Some("On Hold") => ItemStatus::InProgress,
+        Some("Abandoned") => ItemStatus::Abandoned,
+        _ => ItemStatus::Planned,
+    };
+    let completed_at = match status {
+        ItemStatus::Completed => last_played,
+        _ => None,
+    };
+
+    NewLibraryItem {
+        title: game.name.trim().to_string(),
+        status: Some(status),
+        started_at: None,
+        completed_at,
+        isbn: None,
+        page_count: None,
+        expected_hours: None,
+        language: None,
+        original_language: None,
+        favorite: false,
+        rating: None,
+        review: None,
+        notes: None,
+        activated_challenge_ids: Vec::new(),
+        details: ItemDetails::Game {
+            creator: join_names(game.developers),
+            platform: Some(join_names(game.platforms)).filter(|p| !p.is_empty()),
+            playtime_minutes: game
+                .playtime
+                .filter(|seconds| *seconds > 0)
+                .map(|seconds| seconds / 60),
+        },
+    }
+}
+
+fn join_names(names: Option<Vec<Named>>) -> String {
+    names
+        .unwrap_or_default()
+        .iter()
+        .map(Named::name)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_playnite_games() {
+        let rows = parse(
+            r#"[{"Id":"g-1","Name":"Alan Wake","Developers":[{"Name":"Remedy"}],
+                "Platforms":["PC"],"CompletionStatus":{"Name":"Beaten"},
+                "LastActivity":"2025-11-02T20:15:00+02:00","Playtime":54000},
+                {"Name":"Missing id"}]"#,
+        )
+        .unwrap();
+
+        assert_eq!(rows[0].external_id.as_deref(), Some("g-1"));
+        let game = rows[0].item.as_ref().unwrap();
+        assert_eq!(game.status, Some(ItemStatus::Completed));
+        assert_eq!(game.completed_at.as_deref(), Some("2025-11-02"));
+        assert_eq!(
+            game.details,
+            ItemDetails::Game {
+                creator: "Remedy".to_string(),
+                platform: Some("PC".to_string()),
+                playtime_minutes: Some(900),
+            }
+        );
+        assert!(rows[1].item.is_err());
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+
+use rusqlite::{Connection, OptionalExtension, Result};
+
+use crate::{
+    contributors::delete_unused_contributors,
+    database::{Database, query_in_transation},
+    import::ImportBatchSummary,
+    library::LibraryItem,
+};
+
+/// Uploaded CSV files are kept for a day for the mapping step
+const UPLOAD_MAX_AGE_HOURS: i64 = 24;
+
+pub struct ImportRepository {
+    db: Database,
+}
+
+impl ImportRepository {
+    pub fn new(db: Database) -> Self {
+        ImportRepository { db }
+    }
+
+    /// Saves an uploaded file, removing uploads nobody finished mapping
+    pub fn save_upload(&mut self, id: &str, user_id: &str, content: &str) -> Result<()> {
+        let now = chrono::Utc::now();
+        let expired = (now - chrono::Duration::hours(UPLOAD_MAX_AGE_HOURS)).to_rfc3339();
+
+        let tx = self.db.conn.transaction()?;
+        tx.execute("DELETE FROM import_upload WHERE created_at < ?", [&expired])?;
+        tx.execute(
+            "INSERT INTO import_upload (id, user_id, content, created_at) VALUES (?, ?, ?, ?)",
+            [id, user_id, content, &now.to_rfc3339()],
+        )?;
+        tx.commit()
+    }
+
+    pub fn read_upload(&mut self, id: &str, user_id: &str) -> Result<Option<String>> {
+        self.db
+            .conn
+            .query_row(
+                "SELECT content FROM import_upload WHERE id = ? AND user_id = ?",
+                [id, user_id],
+                |row| row.get(0),
+            )
+            .optional()
+    }
+
+    pub fn delete_upload(&mut self, id: &str) -> Result<()> {
+        self.db
+            .conn
+            .execute("DELETE FROM import_upload WHERE id = ?", [id])?;
+        Ok(())
+    }
+
+    pub fn batches(&mut self, user_id: &str) -> Result<Vec<ImportBatchSummary>> {
+        let tx = self.db.conn.transaction()?;
+        let batches = query_in_transation(
+            &tx,
+            "SELECT b.id, b.source, b.created_at, 
+                (SELECT COUNT(*) FROM import_batch_item bi WHERE bi.batch_id = b.id) 
+            FROM import_batch b 
+            WHERE b.user_id = ? 
+            ORDER BY b.created_at DESC",
+            &[&user_id],
+            |row| {
+                Ok(ImportBatchSummary {
+                    id: row.get(0)?,
+                    source: row.get(1)?,
+                    created_at: row.get(2)?,
+                    item_count: row.get(3)?,
+                })
+            },
+        )?;
+        tx.commit()?;
+        Ok(batches)
+    }
+
+    /// Deletes the batch and the items it created. Returns the number of
+    /// deleted items, or `None` when the user has no such batch.
+    pub fn undo_batch(&mut self, batch_id: &str, user_id: &str) -> Result<Option<usize>> {
+        let tx = self.db.conn.transaction()?;
+        let owned: Option<String> = tx
+            .query_row(
+                "SELECT id FROM import_batch WHERE id = ? AND user_id = ?",
+                [batch_id, user_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if owned.is_none() {
+            return Ok(None);
+        }
+
+        let deleted = tx.execute(
+            "DELETE FROM library WHERE user_id = ? AND id IN 
+                (SELECT item_id FROM import_batch_item WHERE batch_id = ?)",
+            [user_id, batch_id],
+        )?;
+        tx.execute("DELETE FROM import_batch WHERE id = ?", [batch_id])?;
+        delete_unused_contributors(&tx, user_id)?;
+        tx.commit()?;
+        Ok(Some(deleted))
+    }
+}
+
+pub fn create_batch(conn: &Connection, user_id: &str, source: &str, now: &str) -> Result<String> {
+    let id = uuid::Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT INTO import_batch (id, user_id, source, created_at) VALUES (?, ?, ?, ?)",
+        [&id, user_id, source, now],
+    )?;
+    Ok(id)
+}
+
+pub fn add_batch_item(conn: &Connection, batch_id: &str, item_id: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO import_batch_item (batch_id, item_id) VALUES (?, ?)",
+        [batch_id, item_id],
+    )?;
+    Ok(())
+}
+
+/// Keys of the items a user already has, used to skip duplicates. An item
+/// is a duplicate when its ISBN or its title and author match.
+pub struct ExistingItems {
+    isbns: HashMap<String, String>,
+    titles: HashMap<(String, String), String>,
+}
+
+impl ExistingItems {
+    pub fn load(conn: &Connection, user_id: &str) -> Result<Self> {
+        let mut stmt =
+            conn.prepare("SELECT id, title, author, isbn FROM library WHERE user_id = ?")?;
+        let mut existing = ExistingItems {
+            isbns: HashMap::new(),
+            titles: HashMap::new(),
+        };
+
+        let rows = stmt.query_map([user_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        })?;
+        for row in rows {
+            let (id, title, author, isbn) = row?;
+            existing.add(&id, &title, &author, isbn.as_deref());
+        }
+
+        Ok(existing)
+    }
+
+    /// Id of an existing item matching `item`
+    pub fn find(&self, item: &LibraryItem) -> Option<&str> {
+        let by_isbn = item.isbn.as_ref().and_then(|isbn| self.isbns.get(isbn));
+        by_isbn
+            .or_else(|| {
+                self.titles
+                    .get(&(match_key(&item.title), match_key(item.details.creator())))
+            })
+            .map(|id| id.as_str())
+    }
+
+    pub fn insert(&mut self, item: &LibraryItem) {
+        self.add(
+            &item.id,
+            &item.title,
+            item.details.creator(),
+            item.isbn.as_deref(),
+        );
+    }
+
+    fn add(&mut self, id: &str, title: &str, author: &str, isbn: Option<&str>) {
+        if let Some(isbn) = isbn {
+            self.isbns.insert(isbn.to_string(), id.to_string());
+        }
+        self.titles
+            .insert((match_key(title), match_key(author)), id.to_string());
+    }
+}
+
+/// Item previously imported from `source` with the given id
+pub fn find_external_item(
+    conn: &Connection,
+    user_id: &str,
+    source: &str,
+    external_id: &str,
+) -> Result<Option<String>> {
+    conn.query_row(
+        "SELECT item_id FROM external_id WHERE user_id = ? AND source = ? AND external_id = ?",
+        [user_id, source, external_id],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+pub fn link_external_item(
+    conn: &Connection,
+    user_id: &str,
+    source: &str,
+    external_id: &str,
+    item_id: &str,
+) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO external_id (user_id, source, external_id, item_id) 
+            VALUES (?, ?, ?, ?)",
+        [user_id, source, external_id, item_id],
+    )?;
+    Ok(())
+}
+
+/// Lowercased words without punctuation
+fn match_key(value: &str) -> String {
+    value
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,28 @@
+use axum::{Json, Router, extract::Query, routing::get};
+use serde::Deserialize;
+
+use crate::{
+    AppState,
+    auth::User,
+    languages::{LanguageName, NameLocale, search_languages},
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/languages", get(get_languages_route))
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct LanguageQuery {
+    /// Part of the name or the code
+    q: Option<String>,
+    #[serde(default)]
+    locale: NameLocale,
+}
+
+async fn get_languages_route(
+    _user: User,
+    Query(query): Query<LanguageQuery>,
+) -> Json<Vec<LanguageName>> {
+    Json(search_languages(query.q.as_deref(), query.locale))
+}
@@ -0,0 +1,1010 @@
+use serde::{Deserialize, Serialize};
+
+use crate::collation::finnish_cmp;
+
+mod api;
+
+/// Language with a two letter ISO 639-1 code
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Language {
+    pub code: &'static str,
+    pub english: &'static str,
+    pub finnish: &'static str,
+}
+
+/// Locale of the language names returned by the lookup
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum NameLocale {
+    Fi,
+    #[default]
+    En,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageName {
+    pub code: &'static str,
+    pub name: &'static str,
+}
+
+impl Language {
+    pub fn name(&self, locale: NameLocale) -> &'static str {
+        match locale {
+            NameLocale::Fi => self.finnish,
+            NameLocale::En => self.english,
+        }
+    }
+}
+
+/// Finds a language by its ISO 639-1 code, ignoring case and surrounding
+/// whitespace
+pub fn find_language(code: &str) -> Option<&'static Language> {
+    let code = code.trim().to_ascii_lowercase();
+    LANGUAGES
+        .binary_search_by(|language| language.code.cmp(code.as_str()))
+        .ok()
+        .map(|index| &LANGUAGES[index])
+}
+
+/// Languages whose code or name in the locale contains the query, sorted by
+/// name
+pub fn search_languages(query: Option<&str>, locale: NameLocale) -> Vec<LanguageName> {
+    let query = query.map(|q| q.trim().to_lowercase()).unwrap_or_default();
+    let mut names: Vec<LanguageName> = LANGUAGES
+        .iter()
+        .filter(|language| {
+            query.is_empty()
+                || language.code == query
+                || language.name(locale).to_lowercase().contains(&query)
+        })
+        .map(|language| LanguageName {
+            code: language.code,
+            name: language.name(locale),
+        })
+        .collect();
+    names.sort_by(|a, b| finnish_cmp(a.name, b.name));
+    names
+}
+
+pub use api::routes;
+
+/// Sorted by code for lookups
+pub const LANGUAGES: &[Language] = &[
+    Language {
+        code: "aa",
+        english: "Afar",
+        finnish: "Afar",
+    },
+    Language {
+        code: "ab",
+        english: "Abkhazian",
+        finnish: "Abhaasi",
+    },
+    Language {
+        code: "ae",
+        english: "Avestan",
+        finnish: "Avesta",
+    },
+    Language {
+        code: "af",
+        english: "Afrikaans",
+        finnish: "Afrikaans",
+    },
+    Language {
+        code: "ak",
+        english: "Akan",
+        finnish: "Akan",
+    },
+    Language {
+        code: "am",
+        english: "Amharic",
+        finnish: "Amhara",
+    },
+    Language {
+        code: "an",
+        english: "Aragonese",
+        finnish: "Aragonia",
+    },
+    Language {
+        code: "ar",
+        english: "Arabic",
+        finnish: "Arabia",
+    },
+    Language {
+        code: "as",
+        english: "Assamese",
+        finnish: "Assami",
+    },
+    Language {
+        code: "av",
+        english: "Avaric",
+        finnish: "Avaari",
+    },
+    Language {
+        code: "ay",
+        english: "Aymara",
+        finnish: "Aimara",
+    },
+    Language {
+        code: "az",
+        english: "Azerbaijani",
+        finnish: "Azeri",
+    },
+    Language {
+        code: "ba",
+        english: "Bashkir",
+        finnish: "Baškiiri",
+    },
+    Language {
+        code: "be",
+        english: "Belarusian",
+        finnish: "Valkovenäjä",
+    },
+    Language {
+        code: "bg",
+        english: "Bulgarian",
+        finnish: "Bulgaria",
+    },
+    Language {
+        code: "bi",
+        english: "Bislama",
+        finnish: "Bislama",
+    },
+    Language {
+        code: "bm",
+        english: "Bambara",
+        finnish: "Bambara",
+    },
+    Language {
+        code: "bn",
+        english: "Bengali",
+        finnish: "Bengali",
+    },
+    Language {
+        code: "bo",
+        english: "Tibetan",
+        finnish: "Tiibet",
+    },
+    Language {
+        code: "br",
+        english: "Breton",
+        finnish: "Bretoni",
+    },
+    Language {
+        code: "bs",
+        english: "Bosnian",
+        finnish: "Bosnia",
+    },
+    Language {
+        code: "ca",
+        english: "Catalan",
+        finnish: "Katalaani",
+    },
+    Language {
+        code: "ce",
+        english: "Chechen",
+        finnish: "Tšetšeeni",
+    },
+    Language {
+        code: "ch",
+        english: "Chamorro",
+        finnish: "Tšamorro",
+    },
+    Language {
+        code: "co",
+        english: "Corsican",
+        finnish: "Korsika",
+    },
+    Language {
+        code: "cr",
+        english: "Cree",
+        finnish: "Cree",
+    },
+    Language {
+        code: "cs",
+        english: "Czech",
+        finnish: "Tšekki",
+    },
+    Language {
+        code: "cu",
+        english: "Church Slavic",
+        finnish: "Kirkkoslaavi",
+    },
+    Language {
+        code: "cv",
+        english: "Chuvash",
+        finnish: "Tšuvassi",
+    },
+    Language {
+        code: "cy",
+        english: "Welsh",
+        finnish: "Kymri",
+    },
+    Language {
+        code: "da",
+        english: "Danish",
+        finnish: "Tanska",
+    },
+    Language {
+        code: "de",
+        english: "German",
+        finnish: "Saksa",
+    },
+    Language {
+        code: "dv",
+        english: "Divehi",
+        finnish: "Divehi",
+    },
+    Language {
+        code: "dz",
+        english: "Dzongkha",
+        finnish: "Dzongkha",
+    },
+    Language {
+        code: "ee",
+        english: "Ewe",
+        finnish: "Ewe",
+    },
+    Language {
+        code: "el",
+        english: "Greek",
+        finnish: "Kreikka",
+    },
+    Language {
+        code: "en",
+        english: "English",
+        finnish: "Englanti",
+    },
+    Language {
+        code: "eo",
+        english: "Esperanto",
+        finnish: "Esperanto",
+    },
+    Language {
+        code: "es",
+        english: "Spanish",
+        finnish: "Espanja",
+    },
+    Language {
+        code: "et",
+        english: "Estonian",
+        finnish: "Viro",
+    },
+    Language {
+        code: "eu",
+        english: "Basque",
+        finnish: "Baski",
+    },
+    Language {
+        code: "fa",
+        english: "Persian",
+        finnish: "Persia",
+    },
+    Language {
+        code: "ff",
+        english: "Fulah",
+        finnish: "Fulani",
+    },
+    Language {
+        code: "fi",
+        english: "Finnish",
+        finnish: "Suomi",
+    },
+    Language {
+        code: "fj",
+        english: "Fijian",
+        finnish: "Fidži",
+    },
+    Language {
+        code: "fo",
+        english: "Faroese",
+        finnish: "Fääri",
+    },
+    Language {
+        code: "fr",
+        english: "French",
+        finnish: "Ranska",
+    },
+    Language {
+        code: "fy",
+        english: "Western Frisian",
+        finnish: "Länsifriisi",
+    },
+    Language {
+        code: "ga",
+        english: "Irish",
+        finnish: "Iiri",
+    },
+    Language {
+        code: "gd",
+        english: "Scottish Gaelic",
+        finnish: "Gaeli",
+    },
+    Language {
+        code: "gl",
+        english: "Galician",
+        finnish: "Galicia",
+    },
+    Language {
+        code: "gn",
+        english: "Guarani",
+        finnish: "Guarani",
+    },
+    Language {
+        code: "gu",
+        english: "Gujarati",
+        finnish: "Gudžarati",
+    },
+    Language {
+        code: "gv",
+        english: "Manx",
+        finnish: "Manksi",
+    },
+    Language {
+        code: "ha",
+        english: "Hausa",
+        finnish: "Hausa",
+    },
+    Language {
+        code: "he",
+        english: "Hebrew",
+        finnish: "Heprea",
+    },
+    Language {
+        code: "hi",
+        english: "Hindi",
+        finnish: "Hindi",
+    },
+    Language {
+        code: "ho",
+        english: "Hiri Motu",
+        finnish: "Hiri-motu",
+    },
+    Language {
+        code: "hr",
+        english: "Croatian",
+        finnish: "Kroatia",
+    },
+    Language {
+        code: "ht",
+        english: "Haitian Creole",
+        finnish: "Haiti",
+    },
+    Language {
+        code: "hu",
+        english: "Hungarian",
+        finnish: "Unkari",
+    },
+    Language {
+        code: "hy",
+        english: "Armenian",
+        finnish: "Armenia",
+    },
+    Language {
+        code: "hz",
+        english: "Herero",
+        finnish: "Herero",
+    },
+    Language {
+        code: "ia",
+        english: "Interlingua",
+        finnish: "Interlingua",
+    },
+    Language {
+        code: "id",
+        english: "Indonesian",
+        finnish: "Indonesia",
+    },
+    Language {
+        code: "ie",
+        english: "Interlingue",
+        finnish: "Interlingue",
+    },
+    Language {
+        code: "ig",
+        english: "Igbo",
+        finnish: "Igbo",
+    },
+    Language {
+        code: "ii",
+        english: "Sichuan Yi",
+        finnish: "Sichuanin-yi",
+    },
+    Language {
+        code: "ik",
+        english: "Inupiaq",
+        finnish: "Inupiaq",
+    },
+    Language {
+        code: "io",
+        english: "Ido",
+        finnish: "Ido",
+    },
+    Language {
+        code: "is",
+        english: "Icelandic",
+        finnish: "Islanti",
+    },
+    Language {
+        code: "it",
+        english: "Italian",
+        finnish: "Italia",
+    },
+    Language {
+        code: "iu",
+        english: "Inuktitut",
+        finnish: "Inuktitut",
+    },
+    Language {
+        code: "ja",
+        english: "Japanese",
+        finnish: "Japani",
+    },
+    Language {
+        code: "jv",
+        english: "Javanese",
+        finnish: "Jaava",
+    },
+    Language {
+        code: "ka",
+        english: "Georgian",
+        finnish: "Georgia",
+    },
+    Language {
+        code: "kg",
+        english: "Kongo",
+        finnish: "Kongo",
+    },
+    Language {
+        code: "ki",
+        english: "Kikuyu",
+        finnish: "Kikuju",
+    },
+    Language {
+        code: "kj",
+        english: "Kuanyama",
+        finnish: "Kuanjama",
+    },
+    Language {
+        code: "kk",
+        english: "Kazakh",
+        finnish: "Kazakki",
+    },
+    Language {
+        code: "kl",
+        english: "Kalaallisut",
+        finnish: "Kalaallisut",
+    },
+    Language {
+        code: "km",
+        english: "Khmer",
+        finnish: "Khmer",
+    },
+    Language {
+        code: "kn",
+        english: "Kannada",
+        finnish: "Kannada",
+    },
+    Language {
+        code: "ko",
+        english: "Korean",
+        finnish: "Korea",
+    },
+    Language {
+        code: "kr",
+        english: "Kanuri",
+        finnish: "Kanuri",
+    },
+    Language {
+        code: "ks",
+        english: "Kashmiri",
+        finnish: "Kašmiri",
+    },
+    Language {
+        code: "ku",
+        english: "Kurdish",
+        finnish: "Kurdi",
+    },
+    Language {
+        code: "kv",
+        english: "Komi",
+        finnish: "Komi",
+    },
+    Language {
+        code: "kw",
+        english: "Cornish",
+        finnish: "Korni",
+    },
+    Language {
+        code: "ky",
+        english: "Kyrgyz",
+        finnish: "Kirgiisi",
+    },
+    Language {
+        code: "la",
+        english: "Latin",
+        finnish: "Latina",
+    },
+    Language {
+        code: "lb",
+        english: "Luxembourgish",
+        finnish: "Luxemburg",
+    },
+    Language {
+        code: "lg",
+        english: "Ganda",
+        finnish: "Ganda",
+    },
+    Language {
+        code: "li",
+        english: "Limburgish",
+        finnish: "Limburg",
+    },
+    Language {
+        code: "ln",
+        english: "Lingala",
+        finnish: "Lingala",
+    },
+    Language {
+        code: "lo",
+        english: "Lao",
+        finnish: "Lao",
+    },
+    Language {
+        code: "lt",
+        english: "Lithuanian",
+        finnish: "Liettua",
+    },
+    Language {
+        code: "lu",
+        english: "Luba-Katanga",
+        finnish: "Luba-katanga",
+    },
+    Language {
+        code: "lv",
+        english: "Latvian",
+        finnish: "Latvia",
+    },
+    Language {
+        code: "mg",
+        english: "Malagasy",
+        finnish: "Malagassi",
+    },
+    Language {
+        code: "mh",
+        english: "Marshallese",
+        finnish: "Marshall",
+    },
+    Language {
+        code: "mi",
+        english: "Maori",
+        finnish: "Maori",
+    },
+    Language {
+        code: "mk",
+        english: "Macedonian",
+        finnish: "Makedonia",
+    },
+    Language {
+        code: "ml",
+        english: "Malayalam",
+        finnish: "Malajalam",
+    },
+    Language {
+        code: "mn",
+        english: "Mongolian",
+        finnish: "Mongoli",
+    },
+    Language {
+        code: "mr",
+        english: "Marathi",
+        finnish: "Marathi",
+    },
+    Language {
+        code: "ms",
+        english: "Malay",
+        finnish: "Malaiji",
+    },
+    Language {
+        code: "mt",
+        english: "Maltese",
+        finnish: "Malta",
+    },
+    Language {
+        code: "my",
+        english: "Burmese",
+        finnish: "Burma",
+    },
+    Language {
+        code: "na",
+        english: "Nauru",
+        finnish: "Nauru",
+    },
+    Language {
+        code: "nb",
+        english: "Norwegian Bokmål",
+        finnish: "Norjan bokmål",
+    },
+    Language {
+        code: "nd",
+        english: "North Ndebele",
+        finnish: "Pohjois-ndebele",
+    },
+    Language {
+        code: "ne",
+        english: "Nepali",
+        finnish: "Nepali",
+    },
+    Language {
+        code: "ng",
+        english: "Ndonga",
+        finnish: "Ndonga",
+    },
+    Language {
+        code: "nl",
+        english: "Dutch",
+        finnish: "Hollanti",
+    },
+    Language {
+        code: "nn",
+        english: "Norwegian Nynorsk",
+        finnish: "Norjan nynorsk",
+    },
+    Language {
+        code: "no",
+        english: "Norwegian",
+        finnish: "Norja",
+    },
+    Language {
+        code: "nr",
+        english: "South Ndebele",
+        finnish: "Etelä-ndebele",
+    },
+    Language {
+        code: "nv",
+        english: "Navajo",
+        finnish: "Navajo",
+    },
+    Language {
+        code: "ny",
+        english: "Chichewa",
+        finnish: "Njandža",
+    },
+    Language {
+        code: "oc",
+        english: "Occitan",
+        finnish: "Oksitaani",
+    },
+    Language {
+        code: "oj",
+        english: "Ojibwa",
+        finnish: "Odžibwa",
+    },
+    Language {
+        code: "om",
+        english: "Oromo",
+        finnish: "Oromo",
+    },
+    Language {
+        code: "or",
+        english: "Odia",
+        finnish: "Orija",
+    },
+    Language {
+        code: "os",
+        english: "Ossetian",
+        finnish: "Osseetti",
+    },
+    Language {
+        code: "pa",
+        english: "Punjabi",
+        finnish: "Pandžabi",
+    },
+    Language {
+        code: "pi",
+        english: "Pali",
+        finnish: "Paali",
+    },
+    Language {
+        code: "pl",
+        english: "Polish",
+        finnish: "Puola",
+    },
+    Language {
+        code: "ps",
+        english: "Pashto",
+        finnish: "Paštu",
+    },
+    Language {
+        code: "pt",
+        english: "Portuguese",
+        finnish: "Portugali",
+    },
+    Language {
+        code: "qu",
+        english: "Quechua",
+        finnish: "Ketšua",
+    },
+    Language {
+        code: "rm",
+        english: "Romansh",
+        finnish: "Retoromaani",
+    },
+    Language {
+        code: "rn",
+        english: "Rundi",
+        finnish: "Rundi",
+    },
+    Language {
+        code: "ro",
+        english: "Romanian",
+        finnish: "Romania",
+    },
+    Language {
+        code: "ru",
+        english: "Russian",
+        finnish: "Venäjä",
+    },
+    Language {
+        code: "rw",
+        english: "Kinyarwanda",
+        finnish: "Ruanda",
+    },
+    Language {
+        code: "sa",
+        english: "Sanskrit",
+        finnish: "Sanskrit",
+    },
+    Language {
+        code: "sc",
+        english: "Sardinian",
+        finnish: "Sardi",
+    },
+    Language {
+        code: "sd",
+        english: "Sindhi",
+        finnish: "Sindhi",
+    },
+    Language {
+        code: "se",
+        english: "Northern Sami",
+        finnish: "Pohjoissaame",
+    },
+    Language {
+        code: "sg",
+        english: "Sango",
+        finnish: "Sango",
+    },
+    Language {
+        code: "si",
+        english: "Sinhala",
+        finnish: "Sinhala",
+    },
+    Language {
+        code: "sk",
+        english: "Slovak",
+        finnish: "Slovakki",
+    },
+    Language {
+        code: "sl",
+        english: "Slovenian",
+        finnish: "Sloveeni",
+    },
+    Language {
+        code: "sm",
+        english: "Samoan",
+        finnish: "Samoa",
+    },
+    Language {
+        code: "sn",
+        english: "Shona",
+        finnish: "Šona",
+    },
+    Language {
+        code: "so",
+        english: "Somali",
+        finnish: "Somali",
+    },
+    Language {
+        code: "sq",
+        english: "Albanian",
+        finnish: "Albania",
+    },
+    Language {
+        code: "sr",
+        english: "Serbian",
+        finnish: "Serbia",
+    },
+    Language {
+        code: "ss",
+        english: "Swati",
+        finnish: "Swazi",
+    },
+    Language {
+        code: "st",
+        english: "Southern Sotho",
+        finnish: "Eteläsotho",
+    },
+    Language {
+        code: "su",
+        english: "Sundanese",
+        finnish: "Sunda",
+    },
+    Language {
+        code: "sv",
+        english: "Swedish",
+        finnish: "Ruotsi",
+    },
+    Language {
+        code: "sw",
+        english: "Swahili",
+        finnish: "Swahili",
+    },
+    Language {
+        code: "ta",
+        english: "Tamil",
+        finnish: "Tamili",
+    },
+    Language {
+        code: "te",
+        english: "Telugu",
+        finnish: "Telugu",
+    },
+    Language {
+        code: "tg",
+        english: "Tajik",
+        finnish: "Tadžikki",
+    },
+    Language {
+        code: "th",
+        english: "Thai",
+        finnish: "Thai",
+    },
+    Language {
+        code: "ti",
+        english: "Tigrinya",
+        finnish: "Tigrinja",
+    },
+    Language {
+        code: "tk",
+        english: "Turkmen",
+        finnish: "Turkmeeni",
+    },
+    Language {
+        code: "tl",
+        english: "Tagalog",
+        finnish: "Tagalog",
+    },
+    Language {
+        code: "tn",
+        english: "Tswana",
+        finnish: "Tswana",
+    },
+    Language {
+        code: "to",
+        english: "Tongan",
+        finnish: "Tonga",
+    },
+    Language {
+        code: "tr",
+        english: "Turkish",
+        finnish: "Turkki",
+    },
+    Language {
+        code: "ts",
+        english: "Tsonga",
+        finnish: "Tsonga",
+    },
+    Language {
+        code: "tt",
+        english: "Tatar",
+        finnish: "Tataari",
+    },
+    Language {
+        code: "tw",
+        english: "Twi",
+        finnish: "Twi",
+    },
+    Language {
+        code: "ty",
+        english: "Tahitian",
+        finnish: "Tahiti",
+    },
+    Language {
+        code: "ug",
+        english: "Uyghur",
+        finnish: "Uiguuri",
+    },
+    Language {
+        code: "uk",
+        english: "Ukrainian",
+        finnish: "Ukraina",
+    },
+    Language {
+        code: "ur",
+        english: "Urdu",
+        finnish: "Urdu",
+    },
+    Language {
+        code: "uz",
+        english: "Uzbek",
+        finnish: "Uzbekki",
+    },
+    Language {
+        code: "ve",
+        english: "Venda",
+        finnish: "Venda",
+    },
+    Language {
+        code: "vi",
+        english: "Vietnamese",
+        finnish: "Vietnam",
+    },
+    Language {
+        code: "vo",
+        english: "Volapük",
+        finnish: "Volapük",
+    },
+    Language {
+        code: "wa",
+        english: "Walloon",
+        finnish: "Valloni",
+    },
+    Language {
+        code: "wo",
+        english: "Wolof",
+        finnish: "Wolof",
+    },
+    Language {
+        code: "xh",
+        english: "Xhosa",
+        finnish: "Xhosa",
+    },
+    Language {
+        code: "yi",
+        english: "Yiddish",
+        finnish: "Jiddiš",
+    },
+    Language {
+        code: "yo",
+        english: "Yoruba",
+        finnish: "Joruba",
+    },
+    Language {
+        code: "za",
+        english: "Zhuang",
+        finnish: "Zhuang",
+    },
+    Language {
+        code: "zh",
+        english: "Chinese",
+        finnish: "Kiina",
+    },
+    Language {
+        code: "zu",
+        english: "Zulu",
+        finnish: "Zulu",
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn languages_are_sorted_by_code() {
+        assert!(LANGUAGES.windows(2).all(|pair| pair[0].code < pair[1].code));
+    }
+
+    #[test]
+    fn finds_languages_by_code_and_name() {
+        assert_eq!(find_language(" FI ").unwrap().finnish, "Suomi");
+        assert!(find_language("fin").is_none());
+
+        let names = search_languages(Some("ruot"), NameLocale::Fi);
+        assert_eq!(names[0].code, "sv");
+        let names = search_languages(Some("sv"), NameLocale::En);
+        assert_eq!(names[0].name, "Swedish");
+    }
+}
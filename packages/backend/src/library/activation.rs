@@ -0,0 +1,179 @@
+use rusqlite::{Result, types::Value};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    database::Repository,
+    library::{ItemStatus, LibraryRepository, parse_date},
+    validation::ValidationErrors,
+};
+
+/// Completion dates of the items to activate a challenge for. Both ends are
+/// optional and inclusive, YYYY-MM-DD.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivationRange {
+    pub completed_from: Option<String>,
+    pub completed_to: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivationReport {
+    /// Items that were not activated for the challenge before
+    pub linked: usize,
+}
+
+impl ActivationRange {
+    /// Range starting from the beginning of the given year, used when a
+    /// challenge becomes active so that items finished earlier in the year
+    /// are not left out
+    pub fn from_start_of_year(year: i32) -> Self {
+        ActivationRange {
+            completed_from: Some(format!("{:04}-01-01", year)),
+            completed_to: None,
+        }
+    }
+
+    /// Bounds to compare completion dates with: inclusive start and
+    /// exclusive end
+    pub fn to_bounds(
+        &self,
+    ) -> std::result::Result<(Option<String>, Option<String>), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        let from = self.completed_from.as_deref().and_then(|from| {
+            parse_date(from)
+                .map_err(|_| errors.add("completedFrom", "must be a date as YYYY-MM-DD"))
+                .ok()
+        });
+        let to = self.completed_to.as_deref().and_then(|to| {
+            parse_date(to)
+                .map_err(|_| errors.add("completedTo", "must be a date as YYYY-MM-DD"))
+                .ok()
+        });
+        if let (Some(from), Some(to)) = (from, to)
+            && from > to
+        {
+            errors.add("completedTo", "must not be before completedFrom");
+        }
+        errors.into_result()?;
+
+        Ok((
+            from.map(|from| from.to_string()),
+            to.and_then(|to| to.succ_opt()).map(|end| end.to_string()),
+        ))
+    }
+}
+
+impl LibraryRepository {
+    /// Activates the challenge for completed items of the kind it targets
+    /// that have a completion within the bounds. Only the items of `user_id`
+    /// are activated when given. Returns the number of newly activated items.
+    pub fn activate_challenge(
+        &mut self,
+        challenge_id: &str,
+        kind: &str,
+        user_id: Option<&str>,
+        completed_after: Option<&str>,
+        completed_before: Option<&str>,
+    ) -> Result<usize> {
+        let mut conditions = vec!["l.kind = ?".to_string(), "l.status = ?".to_string()];
+        let mut params = vec![
+            Value::Text(challenge_id.to_string()),
+            Value::Text(kind.to_string()),
+            Value::Text(ItemStatus::Completed.as_str().to_string()),
+        ];
+        if let Some(user_id) = user_id {
+            conditions.push("l.user_id = ?".to_string());
+            params.push(Value::Text(user_id.to_string()));
+        }
+        if completed_after.is_some() || completed_before.is_some() {
+            let mut completion_conditions = vec!["c.item_id = l.id"];
+            if let Some(after) = completed_after {
+                params.push(Value::Text(after.to_string()));
+                completion_conditions.push("c.completed_at >= ?");
+            }
+            if let Some(before) = completed_before {
+                params.push(Value::Text(before.to_string()));
+                completion_conditions.push("c.completed_at < ?");
+            }
+            conditions.push(format!(
+                "EXISTS (SELECT 1 FROM completion c WHERE {})",
+                completion_conditions.join(" AND ")
+            ));
+        }
+
+        let sql = format!(
+            "INSERT OR IGNORE INTO activated_item_challenge (item_id, challenge_id)
+                SELECT l.id, ? FROM library l WHERE {}",
+            conditions.join(" AND ")
+        );
+        let tx = self.transaction()?;
+        let linked = tx.execute(&sql, rusqlite::params_from_iter(params))?;
+        tx.commit()?;
+        Ok(linked)
+    }
+
+    /// Activates the challenges for a single item, keeping existing links.
+    /// Returns the number of newly activated challenges.
+    pub fn activate_item_challenges(
+        &mut self,
+        item_id: &str,
+        challenge_ids: &[String],
+    ) -> Result<usize> {
+        let tx = self.transaction()?;
+        let mut linked = 0;
+        for challenge_id in challenge_ids {
+            linked += tx.execute(
+                "INSERT OR IGNORE INTO activated_item_challenge (item_id, challenge_id) VALUES (?, ?)",
+                [item_id, challenge_id],
+            )?;
+        }
+        tx.commit()?;
+        Ok(linked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_inclusive_dates_to_bounds() {
+        let range = ActivationRange {
+            completed_from: Some("2025-01-01".to_string()),
+            completed_to: Some("2025-12-31".to_string()),
+        };
+        assert_eq!(
+            range.to_bounds().unwrap(),
+            (
+                Some("2025-01-01".to_string()),
+                Some("2026-01-01".to_string())
+            )
+        );
+        assert_eq!(
+            ActivationRange::default().to_bounds().unwrap(),
+            (None, None)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_ranges() {
+        let range = ActivationRange {
+            completed_from: Some("2025-02-01".to_string()),
+            completed_to: Some("2025-01-01".to_string()),
+        };
+        assert_eq!(
+            range.to_bounds().unwrap_err().errors[0].field,
+            "completedTo"
+        );
+
+        let range = ActivationRange {
+            completed_from: Some("1.2.2025".to_string()),
+            completed_to: None,
+        };
+        assert_eq!(
+            range.to_bounds().unwrap_err().errors[0].field,
+            "completedFrom"
+        );
+    }
+}
@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+
+use rusqlite::{Connection, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    contributors::delete_unused_contributors,
+    database::Repository,
+    library::{ItemStatus, LibraryItem, LibraryRepository, completions::sync_latest_completion},
+    validation::ValidationErrors,
+};
+
+/// Upper limit of items changed by one bulk request
+pub const MAX_BULK_ITEMS: usize = 500;
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkRequest {
+    pub ids: Vec<String>,
+    pub operation: BulkOperation,
+}
+
+impl BulkRequest {
+    /// Checks the fields that don't need the database. The challenge of
+    /// challenge operations is checked when applying.
+    pub fn validate(&self) -> ValidationErrors {
+        let mut errors = ValidationErrors::new();
+        if self.ids.is_empty() || self.ids.len() > MAX_BULK_ITEMS {
+            errors.add("ids", format!("must have 1 to {} items", MAX_BULK_ITEMS));
+        }
+        if let BulkOperation::SetCompletedAt { completed_at } = &self.operation {
+            errors.date("operation.completedAt", Some(completed_at));
+        }
+        errors
+    }
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum BulkOperation {
+    SetFavorite {
+        favorite: bool,
+    },
+    /// Moves the latest completion, or completes items that are not
+    /// completed yet
+    #[serde(rename_all = "camelCase")]
+    SetCompletedAt {
+        completed_at: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    AddChallenge {
+        challenge_id: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    RemoveChallenge {
+        challenge_id: String,
+    },
+    Delete,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum BulkOutcome {
+    Updated,
+    Unchanged,
+    Deleted,
+    /// The operation does not apply to the item, see the reason
+    Skipped,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkItemResult {
+    pub id: String,
+    pub outcome: BulkOutcome,
+    pub reason: Option<String>,
+}
+
+impl BulkItemResult {
+    fn new(id: &str, outcome: BulkOutcome) -> Self {
+        BulkItemResult {
+            id: id.to_string(),
+            outcome,
+            reason: None,
+        }
+    }
+
+    fn skipped(id: &str, reason: String) -> Self {
+        BulkItemResult {
+            id: id.to_string(),
+            outcome: BulkOutcome::Skipped,
+            reason: Some(reason),
+        }
+    }
+}
+
+/// What the operation needs to know beyond the items themselves
+pub struct BulkContext {
+    /// Active challenge ids by kind, activated when an item gets completed
+    pub active_challenge_ids: HashMap<String, Vec<String>>,
+    /// Target media of the challenge added or removed
+    pub challenge_media: Option<String>,
+}
+
+impl LibraryRepository {
+    /// Applies the operation to every item in one transaction. The items
+    /// must already be checked to belong to the user.
+    pub fn apply_bulk(
+        &mut self,
+        items: &[LibraryItem],
+        operation: &BulkOperation,
+        context: &BulkContext,
+    ) -> Result<Vec<BulkItemResult>> {
+        let tx = self.transaction()?;
+        let results = items
+            .iter()
+            .map(|item| apply_operation(&tx, item, operation, context))
+            .collect::<Result<Vec<_>>>()?;
+        tx.commit()?;
+        Ok(results)
+    }
+}
+
+fn apply_operation(
+    conn: &Connection,
+    item: &LibraryItem,
+    operation: &BulkOperation,
+    context: &BulkContext,
+) -> Result<BulkItemResult> {
+    let id = item.id.as_str();
+    let changed = match operation {
+        BulkOperation::SetFavorite { favorite } => conn.execute(
+            "UPDATE library SET favorite = ? WHERE id = ? AND favorite != ?",
+            rusqlite::params![favorite, id, favorite],
+        )?,
+        BulkOperation::SetCompletedAt { completed_at } => {
+            if item.status == ItemStatus::Completed {
+                if item.completed_at.as_deref() == Some(completed_at.as_str()) {
+                    0
+                } else {
+                    sync_latest_completion(conn, id, item.status, item.status, Some(completed_at))?;
+                    1
+                }
+            } else {
+                conn.execute(
+                    "UPDATE library SET status = ? WHERE id = ?",
+                    [ItemStatus::Completed.as_str(), id],
+                )?;
+                sync_latest_completion(
+                    conn,
+                    id,
+                    item.status,
+                    ItemStatus::Completed,
+                    Some(completed_at),
+                )?;
+                // Finishing an item activates it like logging a completed item does
+                let challenge_ids = context.active_challenge_ids.get(item.details.kind());
+                for challenge_id in challenge_ids.into_iter().flatten() {
+                    conn.execute(
+                        "INSERT OR IGNORE INTO activated_item_challenge (item_id, challenge_id) VALUES (?, ?)",
+                        [id, challenge_id],
+                    )?;
+                }
+                1
+            }
+        }
+        BulkOperation::AddChallenge { challenge_id } => {
+            if !item.status.counts_toward_challenges() {
+                return Ok(BulkItemResult::skipped(
+                    id,
+                    format!("Item is {}", item.status.as_str()),
+                ));
+            }
+            if let Some(media) = context.challenge_media.as_deref()
+                && media != item.details.kind()
+            {
+                return Ok(BulkItemResult::skipped(
+                    id,
+                    format!("Challenge is for {}", media),
+                ));
+            }
+            conn.execute(
+                "INSERT OR IGNORE INTO activated_item_challenge (item_id, challenge_id) VALUES (?, ?)",
+                [id, challenge_id],
+            )?
+        }
+        BulkOperation::RemoveChallenge { challenge_id } => conn.execute(
+            "DELETE FROM activated_item_challenge WHERE item_id = ? AND challenge_id = ?",
+            [id, challenge_id],
+        )?,
+        BulkOperation::Delete => {
+            conn.execute("DELETE FROM library WHERE id = ?", [id])?;
+            delete_unused_contributors(conn, &item.user_id)?;
+            return Ok(BulkItemResult::new(id, BulkOutcome::Deleted));
+        }
+    };
+
+    let outcome = if changed > 0 {
+        BulkOutcome::Updated
+    } else {
+        BulkOutcome::Unchanged
+    };
+    Ok(BulkItemResult::new(id, outcome))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_tagged_operations() {
+        let request: BulkRequest = serde_json::from_str(
+            r#"{"ids":["a"],"operation":{"type":"setCompletedAt","completedAt":"2024-05-01"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            request.operation,
+            BulkOperation::SetCompletedAt {
+                completed_at: "2024-05-01".to_string()
+            }
+        );
+
+        let request: BulkRequest =
+            serde_json::from_str(r#"{"ids":["a"],"operation":{"type":"delete"}}"#).unwrap();
+        assert_eq!(request.operation, BulkOperation::Delete);
+    }
+
+    #[test]
+    fn validates_item_count_and_date() {
+        let request = |ids: Vec<String>, completed_at: &str| BulkRequest {
+            ids,
+            operation: BulkOperation::SetCompletedAt {
+                completed_at: completed_at.to_string(),
+            },
+        };
+        let fields = |request: BulkRequest| -> Vec<String> {
+            request
+                .validate()
+                .errors
+                .into_iter()
+                .map(|error| error.field)
+                .collect()
+        };
+
+        assert!(fields(request(vec!["a".to_string()], "2024-05-01")).is_empty());
+        assert_eq!(
+            fields(request(Vec::new(), "2024-5-1")),
+            vec!["ids", "operation.completedAt"]
+        );
+        assert_eq!(
+            fields(request(
+                vec!["a".to_string(); MAX_BULK_ITEMS + 1],
+                "2024-05-01"
+            )),
+            vec!["ids"]
+        );
+    }
+}
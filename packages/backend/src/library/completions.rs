@@ -0,0 +1,303 @@
+use rusqlite::{Connection, OptionalExtension, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    database::{Repository, query_in_transation},
+    library::{ItemStatus, LibraryRepository},
+    validation::ValidationErrors,
+};
+
+/// One finished read or playthrough of a library item
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Completion {
+    pub id: String,
+    pub item_id: String,
+    pub completed_at: String,
+    pub note: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct NewCompletion {
+    pub completed_at: String,
+    pub note: Option<String>,
+}
+
+impl NewCompletion {
+    pub fn validate(&self) -> ValidationErrors {
+        let mut errors = ValidationErrors::new();
+        errors.date("completedAt", Some(&self.completed_at));
+        errors
+    }
+}
+
+/// Count of completions during a year. Re-reads count once per completion.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct YearlyCompletions {
+    pub year: i32,
+    pub kind: String,
+    pub completions: i64,
+    pub items: i64,
+}
+
+impl LibraryRepository {
+    pub fn completions(&mut self, item_id: &str) -> Result<Vec<Completion>> {
+        let tx = self.transaction()?;
+        let completions = query_in_transation(
+            &tx,
+            "SELECT id, item_id, completed_at, note FROM completion 
+                WHERE item_id = ? ORDER BY completed_at DESC",
+            &[&item_id],
+            row_to_completion,
+        )?;
+        tx.commit()?;
+        Ok(completions)
+    }
+
+    pub fn create_completion(&mut self, completion: &Completion) -> Result<String> {
+        let tx = self.transaction()?;
+        insert_completion(&tx, completion)?;
+        tx.commit()?;
+        Ok(completion.id.clone())
+    }
+
+    pub fn update_completion(&mut self, completion: &Completion) -> Result<bool> {
+        let tx = self.transaction()?;
+        let result = tx.execute(
+            "UPDATE completion SET completed_at = ?, note = ? WHERE id = ? AND item_id = ?",
+            rusqlite::params![
+                completion.completed_at,
+                completion.note,
+                completion.id,
+                completion.item_id
+            ],
+        )?;
+        tx.commit()?;
+        Ok(result == 1)
+    }
+
+    pub fn delete_completion(&mut self, item_id: &str, completion_id: &str) -> Result<bool> {
+        let tx = self.transaction()?;
+        let result = tx.execute(
+            "DELETE FROM completion WHERE id = ? AND item_id = ?",
+            [completion_id, item_id],
+        )?;
+        tx.commit()?;
+        Ok(result == 1)
+    }
+
+    pub fn yearly_completions(&mut self, user_id: &str) -> Result<Vec<YearlyCompletions>> {
+        let sql = "SELECT CAST(substr(c.completed_at, 1, 4) AS INTEGER) as year, l.kind, 
+                COUNT(*), COUNT(DISTINCT l.id)
+            FROM completion c
+            JOIN library l ON l.id = c.item_id
+            WHERE l.user_id = ?
+            GROUP BY year, l.kind
+            ORDER BY year, l.kind";
+
+        let tx = self.transaction()?;
+        let stats = query_in_transation(&tx, sql, &[&user_id], |row| {
+            Ok(YearlyCompletions {
+                year: row.get(0)?,
+                kind: row.get(1)?,
+                completions: row.get(2)?,
+                items: row.get(3)?,
+            })
+        })?;
+        tx.commit()?;
+        Ok(stats)
+    }
+}
+
+pub(super) fn insert_completion(tx: &Connection, completion: &Completion) -> Result<()> {
+    tx.execute(
+        "INSERT INTO completion (id, item_id, completed_at, note) VALUES (?, ?, ?, ?)",
+        rusqlite::params![
+            completion.id,
+            completion.item_id,
+            completion.completed_at,
+            completion.note
+        ],
+    )?;
+    Ok(())
+}
+
+/// Reconciles the completions of an item with the completion date saved on
+/// the item itself. Finishing an item again adds a new completion, while
+/// editing the date of a completed item moves its latest completion. Items
+/// that are no longer completed keep their completions but lose the date.
+pub(super) fn sync_latest_completion(
+    tx: &Connection,
+    item_id: &str,
+    previous_status: ItemStatus,
+    status: ItemStatus,
+    completed_at: Option<&str>,
+) -> Result<()> {
+    if status != ItemStatus::Completed {
+        tx.execute(
+            "UPDATE library SET completed_at = NULL WHERE id = ?",
+            [item_id],
+        )?;
+        return Ok(());
+    }
+    let Some(completed_at) = completed_at else {
+        return Ok(());
+    };
+
+    let latest: Option<(String, String)> = tx
+        .query_row(
+            "SELECT id, completed_at FROM completion WHERE item_id = ? 
+                ORDER BY completed_at DESC LIMIT 1",
+            [item_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+
+    match latest {
+        Some((_, latest_at)) if latest_at == completed_at => {}
+        Some((latest_id, _)) if previous_status == status => {
+            tx.execute(
+                "UPDATE completion SET completed_at = ? WHERE id = ?",
+                [completed_at, &latest_id],
+            )?;
+        }
+        _ => insert_completion(
+            tx,
+            &Completion {
+                id: uuid::Uuid::new_v4().to_string(),
+                item_id: item_id.to_string(),
+                completed_at: completed_at.to_string(),
+                note: None,
+            },
+        )?,
+    }
+    Ok(())
+}
+
+fn row_to_completion(row: &rusqlite::Row) -> Result<Completion> {
+    Ok(Completion {
+        id: row.get(0)?,
+        item_id: row.get(1)?,
+        completed_at: row.get(2)?,
+        note: row.get(3)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::library::test_support::{book, completed_book, repository};
+
+    fn completion(id: &str, item_id: &str, completed_at: &str) -> Completion {
+        Completion {
+            id: id.to_string(),
+            item_id: item_id.to_string(),
+            completed_at: completed_at.to_string(),
+            note: None,
+        }
+    }
+
+    fn stored(repo: &mut LibraryRepository, id: &str) -> (ItemStatus, Option<String>, Vec<String>) {
+        let item = repo.read_by_id(id).unwrap().unwrap();
+        let dates = repo
+            .completions(id)
+            .unwrap()
+            .into_iter()
+            .map(|completion| completion.completed_at)
+            .collect();
+        (item.status, item.completed_at, dates)
+    }
+
+    #[test]
+    fn rereads_add_completions_and_move_the_latest() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut repo = repository(&dir);
+        repo.create(&completed_book("a", "user", "2023-04-01"))
+            .unwrap();
+
+        repo.create_completion(&completion("reread", "a", "2025-02-01"))
+            .unwrap();
+        assert_eq!(
+            stored(&mut repo, "a"),
+            (
+                ItemStatus::Completed,
+                Some("2025-02-01".to_string()),
+                vec!["2025-02-01".to_string(), "2023-04-01".to_string()]
+            )
+        );
+
+        // Editing the date of a completed item moves the latest completion
+        repo.update("a", &completed_book("a", "user", "2025-03-01"))
+            .unwrap();
+        assert_eq!(
+            stored(&mut repo, "a").2,
+            vec!["2025-03-01".to_string(), "2023-04-01".to_string()]
+        );
+    }
+
+    #[test]
+    fn deleting_completions_falls_back_to_the_previous_one() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut repo = repository(&dir);
+        repo.create(&completed_book("a", "user", "2023-04-01"))
+            .unwrap();
+        repo.create_completion(&completion("reread", "a", "2025-02-01"))
+            .unwrap();
+
+        assert!(repo.delete_completion("a", "reread").unwrap());
+        assert_eq!(
+            stored(&mut repo, "a"),
+            (
+                ItemStatus::Completed,
+                Some("2023-04-01".to_string()),
+                vec!["2023-04-01".to_string()]
+            )
+        );
+
+        // Without completions the item is no longer completed
+        let first = repo.completions("a").unwrap().remove(0);
+        assert!(repo.delete_completion("a", &first.id).unwrap());
+        assert_eq!(stored(&mut repo, "a"), (ItemStatus::Planned, None, vec![]));
+    }
+
+    #[test]
+    fn unfinished_items_keep_completions_without_a_date() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut repo = repository(&dir);
+        repo.create(&completed_book("a", "user", "2023-04-01"))
+            .unwrap();
+        repo.create_completion(&completion("reread", "a", "2024-02-01"))
+            .unwrap();
+
+        repo.update("a", &book("a", "user", "a", "Author")).unwrap();
+        let history = vec!["2024-02-01".to_string(), "2023-04-01".to_string()];
+        assert_eq!(
+            stored(&mut repo, "a"),
+            (ItemStatus::Planned, None, history.clone())
+        );
+
+        // Editing the history does not complete the item again
+        repo.update_completion(&completion("reread", "a", "2022-02-01"))
+            .unwrap();
+        let first = repo.completions("a").unwrap().remove(0);
+        repo.delete_completion("a", &first.id).unwrap();
+        assert_eq!(
+            stored(&mut repo, "a"),
+            (ItemStatus::Planned, None, vec!["2022-02-01".to_string()])
+        );
+
+        // Finishing it again adds a completion
+        repo.update("a", &completed_book("a", "user", "2025-01-01"))
+            .unwrap();
+        assert_eq!(
+            stored(&mut repo, "a"),
+            (
+                ItemStatus::Completed,
+                Some("2025-01-01".to_string()),
+                vec!["2025-01-01".to_string(), "2022-02-01".to_string()]
+            )
+        );
+    }
+}
@@ -1,24 +1,91 @@
+use std::collections::HashMap;
+
+use chrono::Datelike;
+
 use crate::{
     AppState,
     auth::User,
     challenge::{ChallengeFilter, ChallengeRepository},
+    covers::purge_deleted_covers,
     database::{Database, Repository},
-    library::{LibraryFilter, LibraryItem, LibraryRepository, NewLibraryItem},
+    library::{
+        ActivationRange, BulkItemResult, BulkOperation, BulkRequest, Completion, DuplicateGroup,
+        ItemStatus, LanguageStatistics, LibraryCursor, LibraryFilter, LibraryItem, LibraryPage,
+        LibraryRepository, LibrarySearchHit, NewCompletion, NewLibraryItem, NewProgressSession,
+        Progress, ProgressSession, YearlyCompletions, YearlyRatings,
+        bulk::BulkContext,
+        duplicates::find_duplicates,
+        language_code,
+        progress::{calculate_progress, sessions_after},
+    },
+    metadata::isbn,
+    validation::ValidationErrors,
 };
 
 pub fn get_library_items(
     user: &User,
     state: &AppState,
-) -> Result<Vec<LibraryItem>, Box<dyn std::error::Error>> {
+    filter: LibraryFilter,
+) -> Result<LibraryPage, Box<dyn std::error::Error>> {
     let db = Database::new(&state.database_path)?;
     let mut repo = LibraryRepository::new(db);
+    let limit = filter.limit;
+    let sort = filter.sort;
     let filter = LibraryFilter {
         user_id: user.id.clone(),
-        item_id: None,
+        // Fetch one extra row to know if there is a next page
+        limit: limit.map(|l| l + 1),
+        ..filter
     };
 
-    let items = repo.search(filter)?;
-    Ok(items)
+    let mut items = repo.search(filter)?;
+    let next_cursor = match limit {
+        Some(limit) if items.len() > limit as usize => {
+            items.truncate(limit as usize);
+            items.last().map(|last| LibraryCursor {
+                value: sort.field.value_of(last),
+                id: last.id.clone(),
+            })
+        }
+        _ => None,
+    };
+
+    Ok(LibraryPage { items, next_cursor })
+}
+
+pub fn search_library_items(
+    user: &User,
+    state: &AppState,
+    query: &str,
+    limit: u32,
+) -> Result<Vec<LibrarySearchHit>, Box<dyn std::error::Error>> {
+    let db = Database::new(&state.database_path)?;
+    let mut repo = LibraryRepository::new(db);
+    Ok(repo.full_text_search(&user.id, query, limit)?)
+}
+
+pub fn get_rating_statistics(
+    user: &User,
+    state: &AppState,
+    kind: Option<&str>,
+) -> Result<Vec<YearlyRatings>, Box<dyn std::error::Error>> {
+    let db = Database::new(&state.database_path)?;
+    let mut repo = LibraryRepository::new(db);
+    Ok(repo.rating_distribution(&user.id, kind)?)
+}
+
+pub fn get_language_statistics(
+    user: &User,
+    state: &AppState,
+    year: Option<i32>,
+) -> Result<LanguageStatistics, Box<dyn std::error::Error>> {
+    let mut errors = ValidationErrors::new();
+    errors.year("year", year);
+    errors.into_result()?;
+
+    let db = Database::new(&state.database_path)?;
+    let mut repo = LibraryRepository::new(db);
+    Ok(repo.language_statistics(&user.id, year)?)
 }
 
 pub fn get_library_item_by_id(
@@ -45,33 +112,57 @@ pub fn create_library_item(
     item: &NewLibraryItem,
 ) -> Result<String, Box<dyn std::error::Error>> {
     let db = Database::new(&state.database_path)?;
-    // Should maybe reuse the same connection
-    let mut challenge_repo = ChallengeRepository::new(Database::new(&state.database_path)?);
-    let challenges = challenge_repo.search(ChallengeFilter {
-        status: Some("active".to_string()),
-        media_type: Some(item.kind.to_string()),
-    })?;
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut item = item.to_library_item(&user.id, &now);
 
-    let activated_challenges = challenges.iter().map(|f| f.id.clone()).collect();
+    if item.status.counts_toward_challenges() {
+        item.activated_challenge_ids = active_challenge_ids(user, state, item.details.kind())?;
+    }
 
     let mut repo = LibraryRepository::new(db);
-
-    let item = LibraryItem {
-        id: uuid::Uuid::new_v4().to_string(),
-        user_id: user.id.clone(),
-        kind: item.kind.clone(),
-        title: item.title.clone(),
-        author: item.author.clone(),
-        added_at: chrono::Utc::now().to_rfc3339(),
-        completed_at: item.completed_at.clone(),
-        favorite: item.favorite,
-        activated_challenge_ids: activated_challenges,
-        translator: item.translator.clone(),
-    };
     let id = repo.create(&item)?;
     Ok(id)
 }
 
+/// Challenges activated on an item must exist, be open to the user and
+/// target its kind
+fn validate_activated_challenges(
+    user: &User,
+    state: &AppState,
+    item: &NewLibraryItem,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut challenge_repo = ChallengeRepository::new(Database::new(&state.database_path)?);
+    let kind = item.details.kind();
+    let mut errors = ValidationErrors::new();
+    for (index, challenge_id) in item.activated_challenge_ids.iter().enumerate() {
+        let field = format!("activatedChallengeIds[{}]", index);
+        match challenge_repo
+            .read_by_id(challenge_id)?
+            .filter(|challenge| challenge.is_open_to(&user.id))
+        {
+            Some(challenge) if challenge.target_media == kind => {}
+            Some(_) => errors.add(field, format!("challenge does not target {}", kind)),
+            None => errors.add(field, "challenge does not exist"),
+        }
+    }
+    Ok(errors.into_result()?)
+}
+
+fn active_challenge_ids(
+    user: &User,
+    state: &AppState,
+    kind: &str,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut challenge_repo = ChallengeRepository::new(Database::new(&state.database_path)?);
+    let challenges = challenge_repo.search(ChallengeFilter {
+        status: Some("active".to_string()),
+        media_type: Some(kind.to_string()),
+        ..ChallengeFilter::new(&user.id)
+    })?;
+
+    Ok(challenges.into_iter().map(|c| c.id).collect())
+}
+
 pub fn update_library_item(
     user: &User,
     state: &AppState,
@@ -80,32 +171,391 @@ pub fn update_library_item(
 ) -> Result<bool, Box<dyn std::error::Error>> {
     let db = Database::new(&state.database_path)?;
     let mut repo = LibraryRepository::new(db);
-    if let Some(existing_item) = repo.read_by_id(id)? {
-        if existing_item.user_id != user.id {
+    let existing_item = match repo.read_by_id(id)? {
+        Some(existing_item) if existing_item.user_id == user.id => existing_item,
+        Some(existing_item) => {
             println!("User ID mismatch: {} vs {}", existing_item.user_id, user.id);
             return Ok(false);
         }
+        None => return Ok(false),
+    };
+    validate_activated_challenges(user, state, item)?;
+
+    let status = item
+        .status
+        .unwrap_or_else(|| ItemStatus::infer(item.completed_at.as_deref()));
+    let (started_at, completed_at) = status.resolve_dates(
+        item.started_at.clone(),
+        item.completed_at.clone(),
+        &chrono::Utc::now().to_rfc3339(),
+    );
+
+    let activated_challenge_ids = if !status.counts_toward_challenges() {
+        Vec::new()
+    } else if !existing_item.status.counts_toward_challenges() {
+        // Finishing an item activates it like logging a completed item does
+        let mut ids = item.activated_challenge_ids.clone();
+        for challenge_id in active_challenge_ids(user, state, item.details.kind())? {
+            if !ids.contains(&challenge_id) {
+                ids.push(challenge_id);
+            }
+        }
+        ids
     } else {
-        return Ok(false);
-    }
+        item.activated_challenge_ids.clone()
+    };
 
     let item = LibraryItem {
         id: id.to_string(),
         user_id: user.id.clone(),
-        kind: item.kind.clone(),
         title: item.title.clone(),
-        author: item.author.clone(),
         added_at: "".to_string(), // Not updated
-        completed_at: item.completed_at.clone(),
+        status,
+        started_at,
+        completed_at,
+        completion_count: 0, // Not stored
+        isbn: item.isbn.as_deref().and_then(isbn::normalize),
+        page_count: item.page_count,
+        expected_hours: item.expected_hours,
+        language: item.language.as_deref().and_then(language_code),
+        original_language: item.original_language.as_deref().and_then(language_code),
         favorite: item.favorite,
-        activated_challenge_ids: item.activated_challenge_ids.clone(),
-        translator: item.translator.clone(),
+        rating: item.rating,
+        review: item.review.clone(),
+        review_html: None,
+        notes: item.notes.clone(),
+        activated_challenge_ids,
+        tag_ids: Vec::new(), // Not updated
+        cover_id: None,      // Not updated
+        details: item.details.clone(),
     };
 
     let updated = repo.update(id, &item)?;
     Ok(updated)
 }
 
+pub fn get_completions(
+    user: &User,
+    state: &AppState,
+    item_id: &str,
+) -> Result<Option<Vec<Completion>>, Box<dyn std::error::Error>> {
+    let db = Database::new(&state.database_path)?;
+    let mut repo = LibraryRepository::new(db);
+    if !is_owned_item(&mut repo, user, item_id)? {
+        return Ok(None);
+    }
+    Ok(Some(repo.completions(item_id)?))
+}
+
+pub fn add_completion(
+    user: &User,
+    state: &AppState,
+    item_id: &str,
+    completion: &NewCompletion,
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    completion.validate().into_result()?;
+    let db = Database::new(&state.database_path)?;
+    let mut repo = LibraryRepository::new(db);
+    let item = match repo.read_by_id(item_id)? {
+        Some(item) if item.user_id == user.id => item,
+        _ => return Ok(None),
+    };
+
+    let completion = Completion {
+        id: uuid::Uuid::new_v4().to_string(),
+        item_id: item_id.to_string(),
+        completed_at: completion.completed_at.clone(),
+        note: completion.note.clone(),
+    };
+    let id = repo.create_completion(&completion)?;
+    activate_for_completion(user, state, &mut repo, &item, &completion.completed_at)?;
+    Ok(Some(id))
+}
+
+pub fn update_completion(
+    user: &User,
+    state: &AppState,
+    item_id: &str,
+    completion_id: &str,
+    completion: &NewCompletion,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    completion.validate().into_result()?;
+    let db = Database::new(&state.database_path)?;
+    let mut repo = LibraryRepository::new(db);
+    let item = match repo.read_by_id(item_id)? {
+        Some(item) if item.user_id == user.id => item,
+        _ => return Ok(false),
+    };
+
+    let completion = Completion {
+        id: completion_id.to_string(),
+        item_id: item_id.to_string(),
+        completed_at: completion.completed_at.clone(),
+        note: completion.note.clone(),
+    };
+    if !repo.update_completion(&completion)? {
+        return Ok(false);
+    }
+    activate_for_completion(user, state, &mut repo, &item, &completion.completed_at)?;
+    Ok(true)
+}
+
+pub fn delete_completion(
+    user: &User,
+    state: &AppState,
+    item_id: &str,
+    completion_id: &str,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let db = Database::new(&state.database_path)?;
+    let mut repo = LibraryRepository::new(db);
+    if !is_owned_item(&mut repo, user, item_id)? {
+        return Ok(false);
+    }
+    Ok(repo.delete_completion(item_id, completion_id)?)
+}
+
+pub fn get_yearly_completions(
+    user: &User,
+    state: &AppState,
+) -> Result<Vec<YearlyCompletions>, Box<dyn std::error::Error>> {
+    let db = Database::new(&state.database_path)?;
+    let mut repo = LibraryRepository::new(db);
+    Ok(repo.yearly_completions(&user.id)?)
+}
+
+pub fn get_progress_sessions(
+    user: &User,
+    state: &AppState,
+    item_id: &str,
+) -> Result<Option<Vec<ProgressSession>>, Box<dyn std::error::Error>> {
+    let db = Database::new(&state.database_path)?;
+    let mut repo = LibraryRepository::new(db);
+    if !is_owned_item(&mut repo, user, item_id)? {
+        return Ok(None);
+    }
+    Ok(Some(repo.progress_sessions(item_id)?))
+}
+
+pub fn add_progress_session(
+    user: &User,
+    state: &AppState,
+    item_id: &str,
+    session: &NewProgressSession,
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    session.validate().into_result()?;
+    let db = Database::new(&state.database_path)?;
+    let mut repo = LibraryRepository::new(db);
+    if !is_owned_item(&mut repo, user, item_id)? {
+        return Ok(None);
+    }
+
+    let session = ProgressSession {
+        id: uuid::Uuid::new_v4().to_string(),
+        item_id: item_id.to_string(),
+        date: session.date.clone(),
+        pages: session.pages,
+        minutes: session.minutes,
+        note: session.note.clone(),
+    };
+    Ok(Some(repo.create_progress_session(&session)?))
+}
+
+pub fn delete_progress_session(
+    user: &User,
+    state: &AppState,
+    item_id: &str,
+    session_id: &str,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let db = Database::new(&state.database_path)?;
+    let mut repo = LibraryRepository::new(db);
+    if !is_owned_item(&mut repo, user, item_id)? {
+        return Ok(false);
+    }
+    Ok(repo.delete_progress_session(item_id, session_id)?)
+}
+
+pub fn get_progress(
+    user: &User,
+    state: &AppState,
+    item_id: &str,
+) -> Result<Option<Progress>, Box<dyn std::error::Error>> {
+    let db = Database::new(&state.database_path)?;
+    let mut repo = LibraryRepository::new(db);
+    let item = match repo.read_by_id(item_id)? {
+        Some(item) if item.user_id == user.id => item,
+        _ => return Ok(None),
+    };
+
+    // A completed item shows the read it finished, anything else the read
+    // started after its latest completion
+    let completions = repo.completions(item_id)?;
+    let previous = if item.status == ItemStatus::Completed {
+        completions.get(1)
+    } else {
+        completions.first()
+    };
+    let sessions = sessions_after(
+        &repo.progress_sessions(item_id)?,
+        previous.map(|c| c.completed_at.as_str()),
+    );
+    let today = chrono::Utc::now().date_naive();
+    Ok(Some(calculate_progress(
+        item.page_count,
+        item.expected_hours,
+        &sessions,
+        today,
+    )))
+}
+
+pub fn get_duplicates(
+    user: &User,
+    state: &AppState,
+) -> Result<Vec<DuplicateGroup>, Box<dyn std::error::Error>> {
+    let db = Database::new(&state.database_path)?;
+    let mut repo = LibraryRepository::new(db);
+    let items = repo.search(LibraryFilter::new(&user.id))?;
+    Ok(find_duplicates(items))
+}
+
+/// Merges the source items into the target. Returns `None` when any of the
+/// items is missing or belongs to someone else.
+pub fn merge_library_items(
+    user: &User,
+    state: &AppState,
+    target_id: &str,
+    source_ids: &[String],
+) -> Result<Option<LibraryItem>, Box<dyn std::error::Error>> {
+    let mut errors = ValidationErrors::new();
+    if source_ids.is_empty() {
+        errors.add("sourceIds", "must not be empty");
+    }
+    if source_ids.iter().any(|id| id == target_id) {
+        errors.add("sourceIds", "must not contain the item merged into");
+    }
+    errors.into_result()?;
+
+    let db = Database::new(&state.database_path)?;
+    let mut repo = LibraryRepository::new(db);
+    let target = match repo.read_by_id(target_id)? {
+        Some(item) if item.user_id == user.id => item,
+        _ => return Ok(None),
+    };
+    for source_id in source_ids {
+        match repo.read_by_id(source_id)? {
+            Some(source) if source.user_id == user.id => {
+                if source.details.kind() != target.details.kind() {
+                    let mut errors = ValidationErrors::new();
+                    errors.add(
+                        "sourceIds",
+                        "must be of the same kind as the item merged into",
+                    );
+                    return Err(errors.into());
+                }
+            }
+            _ => return Ok(None),
+        }
+    }
+
+    let mut source_ids = source_ids.to_vec();
+    source_ids.sort();
+    source_ids.dedup();
+    repo.merge_items(target_id, &source_ids)?;
+    purge_deleted_covers(state);
+    Ok(repo.read_by_id(target_id)?)
+}
+
+/// Applies one operation to many items in a single transaction. Returns
+/// `None` when any of the items is missing or belongs to someone else, in
+/// which case nothing is changed.
+pub fn apply_bulk_operation(
+    user: &User,
+    state: &AppState,
+    request: &BulkRequest,
+) -> Result<Option<Vec<BulkItemResult>>, Box<dyn std::error::Error>> {
+    request.validate().into_result()?;
+
+    let challenge_media = match &request.operation {
+        BulkOperation::AddChallenge { challenge_id } => {
+            let mut challenge_repo = ChallengeRepository::new(Database::new(&state.database_path)?);
+            match challenge_repo
+                .read_by_id(challenge_id)?
+                .filter(|challenge| challenge.is_open_to(&user.id))
+            {
+                Some(challenge) => Some(challenge.target_media),
+                None => {
+                    let mut errors = ValidationErrors::new();
+                    errors.add("operation.challengeId", "not found");
+                    return Err(errors.into());
+                }
+            }
+        }
+        _ => None,
+    };
+
+    let db = Database::new(&state.database_path)?;
+    let mut repo = LibraryRepository::new(db);
+    let mut items: Vec<LibraryItem> = Vec::with_capacity(request.ids.len());
+    for id in &request.ids {
+        if items.iter().any(|item| &item.id == id) {
+            continue;
+        }
+        match repo.read_by_id(id)? {
+            Some(item) if item.user_id == user.id => items.push(item),
+            _ => return Ok(None),
+        }
+    }
+
+    let mut active_by_kind = HashMap::new();
+    if matches!(request.operation, BulkOperation::SetCompletedAt { .. }) {
+        for item in &items {
+            let kind = item.details.kind();
+            if !active_by_kind.contains_key(kind) {
+                active_by_kind.insert(kind.to_string(), active_challenge_ids(user, state, kind)?);
+            }
+        }
+    }
+
+    let context = BulkContext {
+        active_challenge_ids: active_by_kind,
+        challenge_media,
+    };
+    let results = repo.apply_bulk(&items, &request.operation, &context)?;
+    if matches!(request.operation, BulkOperation::Delete) {
+        purge_deleted_covers(state);
+    }
+    Ok(Some(results))
+}
+
+/// A completion during the current year activates the challenges that are
+/// active now, the same way a challenge that becomes active picks up the
+/// items finished earlier in the year
+fn activate_for_completion(
+    user: &User,
+    state: &AppState,
+    repo: &mut LibraryRepository,
+    item: &LibraryItem,
+    completed_at: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (start_of_year, _) =
+        ActivationRange::from_start_of_year(chrono::Utc::now().year()).to_bounds()?;
+    if start_of_year.is_some_and(|start| completed_at < start.as_str()) {
+        return Ok(());
+    }
+    let challenge_ids = active_challenge_ids(user, state, item.details.kind())?;
+    repo.activate_item_challenges(&item.id, &challenge_ids)?;
+    Ok(())
+}
+
+fn is_owned_item(
+    repo: &mut LibraryRepository,
+    user: &User,
+    item_id: &str,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    Ok(repo
+        .read_by_id(item_id)?
+        .is_some_and(|item| item.user_id == user.id))
+}
+
 pub fn delete_library_item(
     user: &User,
     state: &AppState,
@@ -123,5 +573,67 @@ pub fn delete_library_item(
     }
 
     let deleted = repo.delete(id)?;
+    purge_deleted_covers(state);
     Ok(deleted)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{database::migrated_test_database, library::test_support::completed_book};
+
+    #[test]
+    fn pages_through_items_with_a_cursor() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::for_tests(&migrated_test_database(&dir));
+        let mut repo = LibraryRepository::new(Database::new(&state.database_path).unwrap());
+        for (id, completed_at) in [
+            ("a", "2024-01-05"),
+            ("b", "2024-01-03"),
+            ("c", "2024-01-02"),
+            ("d", "2024-01-01"),
+        ] {
+            repo.create(&completed_book(id, "me", completed_at))
+                .unwrap();
+        }
+        // A kind added by a newer version, between the first two items
+        repo.conn()
+            .execute(
+                "INSERT INTO library (id, user_id, kind, title, author, added_at, status, completed_at)
+                    VALUES ('x', 'me', 'Comic', 'x', 'x', '2024-01-01', 'completed', '2024-01-04')",
+                [],
+            )
+            .unwrap();
+
+        let user = User::new("me".to_string());
+        let page = |cursor: Option<LibraryCursor>| {
+            let filter = LibraryFilter {
+                cursor,
+                limit: Some(2),
+                ..LibraryFilter::new("me")
+            };
+            let page = get_library_items(&user, &state, filter).unwrap();
+            let ids: Vec<String> = page.items.into_iter().map(|item| item.id).collect();
+            (ids, page.next_cursor)
+        };
+
+        let (ids, cursor) = page(None);
+        assert_eq!(ids, ["a", "b"]);
+        assert!(cursor.is_some());
+        let (ids, cursor) = page(cursor);
+        assert_eq!(ids, ["c", "d"]);
+        assert_eq!(cursor, None);
+    }
+
+    #[test]
+    fn language_statistics_validate_the_year() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::for_tests(&migrated_test_database(&dir));
+        let user = User::new("me".to_string());
+
+        let err = get_language_statistics(&user, &state, Some(i32::MAX)).unwrap_err();
+        let errors = err.downcast_ref::<ValidationErrors>().unwrap();
+        assert_eq!(errors.errors[0].field, "year");
+        assert!(get_language_statistics(&user, &state, Some(2025)).is_ok());
+    }
+}
@@ -0,0 +1,428 @@
+use std::collections::{HashMap, HashSet};
+
+use rusqlite::{Connection, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    contributors::{delete_unused_contributors, refresh_item_names},
+    database::Repository,
+    library::{LibraryItem, LibraryRepository},
+};
+
+/// Titles at least this similar are considered the same work
+const TITLE_SIMILARITY: f64 = 0.85;
+/// Creators at least this similar are considered the same person
+const AUTHOR_SIMILARITY: f64 = 0.8;
+
+/// Library items that likely describe the same work
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateGroup {
+    /// Lowest title similarity between the matched items, from 0 to 1
+    pub similarity: f64,
+    pub items: Vec<LibraryItem>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeRequest {
+    /// Items merged into the target and then deleted
+    pub source_ids: Vec<String>,
+}
+
+/// Lowercases, folds diacritics and drops punctuation so that "Tuntematon
+/// sotilas." and "tuntematon  Sotilas" compare equal.
+pub fn normalize(value: &str) -> String {
+    let folded: String = value
+        .chars()
+        .flat_map(char::to_lowercase)
+        .map(|c| match c {
+            'á' | 'à' | 'â' | 'ä' | 'å' | 'ã' => 'a',
+            'é' | 'è' | 'ê' | 'ë' => 'e',
+            'í' | 'ì' | 'î' | 'ï' => 'i',
+            'ó' | 'ò' | 'ô' | 'ö' | 'õ' | 'ø' => 'o',
+            'ú' | 'ù' | 'û' | 'ü' => 'u',
+            'ç' => 'c',
+            'ñ' => 'n',
+            'š' => 's',
+            'ž' => 'z',
+            c if c.is_alphanumeric() => c,
+            _ => ' ',
+        })
+        .collect();
+    folded.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Levenshtein distance scaled to 0..=1 where 1 means equal strings
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    1.0 - previous[b.len()] as f64 / longest as f64
+}
+
+/// Groups items of the same kind whose normalized titles and creators are
+/// close enough. An empty creator matches any creator.
+pub fn find_duplicates(items: Vec<LibraryItem>) -> Vec<DuplicateGroup> {
+    let keys: Vec<(String, String)> = items
+        .iter()
+        .map(|item| (normalize(&item.title), normalize(item.details.creator())))
+        .collect();
+
+    // Only compare items sharing a kind and at least one title word, which
+    // keeps the comparison count small for large libraries.
+    let mut blocks: HashMap<(&str, &str), Vec<usize>> = HashMap::new();
+    for (index, item) in items.iter().enumerate() {
+        let title = keys[index].0.as_str();
+        let mut words: Vec<&str> = title.split(' ').filter(|word| word.len() >= 3).collect();
+        if words.is_empty() {
+            words.push(title);
+        }
+        words.sort_unstable();
+        words.dedup();
+        for word in words {
+            blocks
+                .entry((item.details.kind(), word))
+                .or_default()
+                .push(index);
+        }
+    }
+
+    let mut parents: Vec<usize> = (0..items.len()).collect();
+    let mut scores: HashMap<usize, f64> = HashMap::new();
+    let mut compared = HashSet::new();
+    for indexes in blocks.values() {
+        for (position, &a) in indexes.iter().enumerate() {
+            for &b in &indexes[position + 1..] {
+                if !compared.insert((a, b)) {
+                    continue;
+                }
+                let (title_a, author_a) = &keys[a];
+                let (title_b, author_b) = &keys[b];
+                let title_similarity = similarity(title_a, title_b);
+                let authors_match = author_a.is_empty()
+                    || author_b.is_empty()
+                    || similarity(author_a, author_b) >= AUTHOR_SIMILARITY;
+                if title_similarity < TITLE_SIMILARITY || !authors_match {
+                    continue;
+                }
+
+                let root_a = find_root(&mut parents, a);
+                let root_b = find_root(&mut parents, b);
+                let score = [root_a, root_b]
+                    .iter()
+                    .filter_map(|root| scores.get(root))
+                    .fold(title_similarity, |lowest, score| lowest.min(*score));
+                parents[root_b] = root_a;
+                scores.remove(&root_b);
+                scores.insert(root_a, score);
+            }
+        }
+    }
+
+    let mut members: HashMap<usize, Vec<usize>> = HashMap::new();
+    for index in 0..items.len() {
+        let root = find_root(&mut parents, index);
+        if scores.contains_key(&root) {
+            members.entry(root).or_default().push(index);
+        }
+    }
+
+    let mut items: Vec<Option<LibraryItem>> = items.into_iter().map(Some).collect();
+    let mut groups: Vec<DuplicateGroup> = members
+        .into_iter()
+        .map(|(root, indexes)| DuplicateGroup {
+            similarity: scores[&root],
+            items: indexes
+                .into_iter()
+                .filter_map(|index| items[index].take())
+                .collect(),
+        })
+        .collect();
+    groups.sort_by(|a, b| {
+        b.similarity
+            .total_cmp(&a.similarity)
+            .then_with(|| a.items[0].title.cmp(&b.items[0].title))
+    });
+    groups
+}
+
+fn find_root(parents: &mut [usize], index: usize) -> usize {
+    let mut root = index;
+    while parents[root] != root {
+        root = parents[root];
+    }
+    let mut current = index;
+    while parents[current] != root {
+        let next = parents[current];
+        parents[current] = root;
+        current = next;
+    }
+    root
+}
+
+impl LibraryRepository {
+    /// Moves everything attached to the source items onto the target and
+    /// deletes the sources. Fields empty on the target are filled from the
+    /// sources. Runs in a single transaction.
+    pub fn merge_items(&mut self, target_id: &str, source_ids: &[String]) -> Result<()> {
+        let tx = self.transaction()?;
+        for source_id in source_ids {
+            merge_item(&tx, target_id, source_id)?;
+        }
+        // Contributors of roles the target already had are left unlinked
+        let user_id: String = tx.query_row(
+            "SELECT user_id FROM library WHERE id = ?",
+            [target_id],
+            |row| row.get(0),
+        )?;
+        delete_unused_contributors(&tx, &user_id)?;
+        tx.commit()
+    }
+}
+
+fn merge_item(conn: &Connection, target_id: &str, source_id: &str) -> Result<()> {
+    let ids = rusqlite::params![target_id, source_id];
+
+    conn.execute(
+        "INSERT OR IGNORE INTO activated_item_challenge (item_id, challenge_id)
+            SELECT ?1, challenge_id FROM activated_item_challenge WHERE item_id = ?2",
+        ids,
+    )?;
+    conn.execute("UPDATE answer SET item_id = ?1 WHERE item_id = ?2", ids)?;
+    conn.execute(
+        "UPDATE question_solution SET single_answer_item_id = ?1 WHERE single_answer_item_id = ?2",
+        ids,
+    )?;
+    conn.execute(
+        "INSERT OR IGNORE INTO multipart_solution (solution_id, item_id)
+            SELECT solution_id, ?1 FROM multipart_solution WHERE item_id = ?2",
+        ids,
+    )?;
+    conn.execute(
+        "INSERT OR IGNORE INTO item_tag (item_id, tag_id)
+            SELECT ?1, tag_id FROM item_tag WHERE item_id = ?2",
+        ids,
+    )?;
+    conn.execute(
+        "INSERT OR IGNORE INTO series_entry (series_id, item_id, position)
+            SELECT series_id, ?1, position FROM series_entry WHERE item_id = ?2",
+        ids,
+    )?;
+    // Like the other fields, contributors of a role fill the role only when
+    // the target has none
+    conn.execute(
+        "INSERT OR IGNORE INTO item_contributor (item_id, contributor_id, role, position)
+            SELECT ?1, contributor_id, role, position FROM item_contributor source
+            WHERE source.item_id = ?2 AND NOT EXISTS (
+                SELECT 1 FROM item_contributor target
+                WHERE target.item_id = ?1 AND target.role = source.role
+            )",
+        ids,
+    )?;
+    conn.execute(
+        "UPDATE progress_session SET item_id = ?1 WHERE item_id = ?2",
+        ids,
+    )?;
+    conn.execute(
+        "UPDATE external_id SET item_id = ?1 WHERE item_id = ?2",
+        ids,
+    )?;
+
+    conn.execute(
+        "UPDATE library SET
+            status = CASE WHEN source.status = 'completed' THEN 'completed' ELSE library.status END,
+            started_at = COALESCE(library.started_at, source.started_at),
+            favorite = MAX(library.favorite, source.favorite),
+            isbn = COALESCE(library.isbn, source.isbn),
+            page_count = COALESCE(library.page_count, source.page_count),
+            expected_hours = COALESCE(library.expected_hours, source.expected_hours),
+            language = COALESCE(library.language, source.language),
+            original_language = COALESCE(library.original_language, source.original_language),
+            rating = COALESCE(library.rating, source.rating),
+            review = COALESCE(library.review, source.review),
+            notes = COALESCE(library.notes, source.notes)
+        FROM (SELECT * FROM library WHERE id = ?2) AS source
+        WHERE library.id = ?1",
+        ids,
+    )?;
+    // Moved after the status so that the source still counts as completed
+    // above. The completion triggers recompute completed_at for both items.
+    conn.execute("UPDATE completion SET item_id = ?1 WHERE item_id = ?2", ids)?;
+    refresh_item_names(conn, target_id)?;
+
+    // Import batches keep pointing at the source so undoing an import never
+    // deletes the item it was merged into
+    conn.execute("DELETE FROM library WHERE id = ?", [source_id])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::library::{ItemDetails, ItemStatus, test_support};
+
+    fn item(id: &str, title: &str, author: &str) -> LibraryItem {
+        LibraryItem {
+            id: id.to_string(),
+            user_id: "user".to_string(),
+            title: title.to_string(),
+            added_at: "2024-01-01T00:00:00Z".to_string(),
+            status: ItemStatus::Completed,
+            started_at: None,
+            completed_at: None,
+            completion_count: 0,
+            isbn: None,
+            page_count: None,
+            expected_hours: None,
+            language: None,
+            original_language: None,
+            favorite: false,
+            rating: None,
+            review: None,
+            review_html: None,
+            notes: None,
+            activated_challenge_ids: Vec::new(),
+            tag_ids: Vec::new(),
+            cover_id: None,
+            details: ItemDetails::from_parts("Book", author.to_string(), None).unwrap(),
+        }
+    }
+
+    #[test]
+    fn normalizes_case_punctuation_and_diacritics() {
+        assert_eq!(normalize("  Tuntematon  Sotilas. "), "tuntematon sotilas");
+        assert_eq!(normalize("Väinö Linna"), "vaino linna");
+        assert_eq!(
+            normalize("Harry Potter: Viisasten kivi"),
+            "harry potter viisasten kivi"
+        );
+    }
+
+    #[test]
+    fn groups_near_identical_titles_by_same_author() {
+        let groups = find_duplicates(vec![
+            item("1", "Tuntematon sotilas", "Väinö Linna"),
+            item("2", "Tuntematon sotilas.", "Vaino Linna"),
+            item("3", "Tuntemton sotilas", "Väinö Linna"),
+            item("4", "Täällä Pohjantähden alla", "Väinö Linna"),
+            item("5", "Tuntematon sotilas", "Someone Else"),
+        ]);
+
+        assert_eq!(groups.len(), 1);
+        let ids: Vec<&str> = groups[0].items.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(ids, vec!["1", "2", "3"]);
+        assert!(groups[0].similarity < 1.0 && groups[0].similarity >= TITLE_SIMILARITY);
+    }
+
+    #[test]
+    fn merge_moves_everything_to_the_target() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut repo = test_support::repository(&dir);
+        repo.create(&test_support::book(
+            "target",
+            "user",
+            "Tuntematon sotilas",
+            "Väinö Linna",
+        ))
+        .unwrap();
+        repo.create(&LibraryItem {
+            status: ItemStatus::Completed,
+            completed_at: Some("2023-05-05".to_string()),
+            rating: Some(4.0),
+            details: ItemDetails::Book {
+                author: "Vaino Linna".to_string(),
+                translator: Some("Translator".to_string()),
+            },
+            ..test_support::book("source", "user", "Tuntematon sotilas.", "")
+        })
+        .unwrap();
+        repo.conn()
+            .execute_batch(
+                "INSERT INTO challenge (id, name, status, target_media, kind)
+                    VALUES ('ch', 'Challenge', 'active', 'Book', 'shared');
+                INSERT INTO question (id, challenge_id, kind, question, question_cluster_size, number)
+                    VALUES ('q', 'ch', 'TextInput', 'Question', 1, 1);
+                INSERT INTO activated_item_challenge (item_id, challenge_id) VALUES ('source', 'ch');
+                INSERT INTO answer (id, question_id, challenge_id, user_id, kind, answer, answered, item_id)
+                    VALUES ('answer', 'q', 'ch', 'user', 'TextInput', 'x', 1, 'source');
+                INSERT INTO question_solution (id, user_id, challenge_id, question_id, kind, single_answer_item_id)
+                    VALUES ('solution', 'user', 'ch', 'q', 'single', 'source');
+                INSERT INTO multipart_solution (solution_id, item_id) VALUES ('solution', 'source');
+                INSERT INTO tag (id, user_id, name, normalized_name) VALUES ('tag', 'user', 'Sota', 'sota');
+                INSERT INTO item_tag (item_id, tag_id) VALUES ('source', 'tag');
+                INSERT INTO series (id, user_id, name, normalized_name) VALUES ('series', 'user', 'S', 's');
+                INSERT INTO series_entry (series_id, item_id, position) VALUES ('series', 'source', 1);
+                INSERT INTO progress_session (id, item_id, date, pages) VALUES ('session', 'source', '2023-05-01', 10);
+                INSERT INTO external_id (user_id, source, external_id, item_id)
+                    VALUES ('user', 'calibre', 'uuid', 'source');",
+            )
+            .unwrap();
+
+        repo.merge_items("target", &["source".to_string()]).unwrap();
+
+        assert!(repo.read_by_id("source").unwrap().is_none());
+        let merged = repo.read_by_id("target").unwrap().unwrap();
+        assert_eq!(merged.status, ItemStatus::Completed);
+        assert_eq!(merged.completed_at.as_deref(), Some("2023-05-05"));
+        assert_eq!(merged.completion_count, 1);
+        assert_eq!(merged.rating, Some(4.0));
+        assert_eq!(merged.activated_challenge_ids, vec!["ch".to_string()]);
+        assert_eq!(merged.tag_ids, vec!["tag".to_string()]);
+        // The target's author is kept and the missing translator filled in
+        assert_eq!(
+            merged.details,
+            ItemDetails::Book {
+                author: "Väinö Linna".to_string(),
+                translator: Some("Translator".to_string()),
+            }
+        );
+
+        for (table, column) in [
+            ("answer", "item_id"),
+            ("question_solution", "single_answer_item_id"),
+            ("multipart_solution", "item_id"),
+            ("series_entry", "item_id"),
+            ("progress_session", "item_id"),
+            ("external_id", "item_id"),
+            ("completion", "item_id"),
+        ] {
+            let (target, source): (i64, i64) = repo
+                .conn()
+                .query_row(
+                    &format!(
+                        "SELECT SUM({0} = 'target'), SUM({0} = 'source') FROM {1}",
+                        column, table
+                    ),
+                    [],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .unwrap();
+            assert_eq!((target, source), (1, 0), "{}", table);
+        }
+        let authors: Vec<String> = repo
+            .conn()
+            .prepare("SELECT name FROM contributor ORDER BY name")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(authors, vec!["Translator", "Väinö Linna"]);
+    }
+}
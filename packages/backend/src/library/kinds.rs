@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+
+/// Kind specific fields of a library item. Serialized with the `kind` tag
+/// next to the common item fields, so an unknown kind fails deserialization.
+/// The tag is also the `target_media` of challenges for that kind.
+///
+/// The person responsible for the work (book author, game creator, movie
+/// director...) is stored in the shared `library.author` column so that
+/// sorting and search work across kinds. Other fields live in per-kind
+/// extension tables.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind")]
+pub enum ItemDetails {
+    #[serde(rename_all = "camelCase")]
+    Book {
+        author: String,
+        translator: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Game {
+        #[serde(alias = "author")]
+        creator: String,
+        platform: Option<String>,
+        playtime_minutes: Option<i64>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Movie {
+        director: String,
+        release_year: Option<i32>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Series {
+        creator: String,
+        season: Option<i32>,
+        episodes: Option<i32>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Audiobook {
+        author: String,
+        translator: Option<String>,
+        narrator: Option<String>,
+        duration_minutes: Option<i64>,
+    },
+    #[serde(rename_all = "camelCase")]
+    BoardGame {
+        designer: String,
+        min_players: Option<i32>,
+        max_players: Option<i32>,
+    },
+}
+
+impl ItemDetails {
+    pub const KINDS: &'static [&'static str] =
+        &["Book", "Game", "Movie", "Series", "Audiobook", "BoardGame"];
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ItemDetails::Book { .. } => "Book",
+            ItemDetails::Game { .. } => "Game",
+            ItemDetails::Movie { .. } => "Movie",
+            ItemDetails::Series { .. } => "Series",
+            ItemDetails::Audiobook { .. } => "Audiobook",
+            ItemDetails::BoardGame { .. } => "BoardGame",
+        }
+    }
+
+    /// Books read or listened to record their language
+    pub fn has_language(&self) -> bool {
+        matches!(
+            self,
+            ItemDetails::Book { .. } | ItemDetails::Audiobook { .. }
+        )
+    }
+
+    /// Value of the shared `author` column
+    pub fn creator(&self) -> &str {
+        match self {
+            ItemDetails::Book { author, .. } => author,
+            ItemDetails::Game { creator, .. } => creator,
+            ItemDetails::Movie { director, .. } => director,
+            ItemDetails::Series { creator, .. } => creator,
+            ItemDetails::Audiobook { author, .. } => author,
+            ItemDetails::BoardGame { designer, .. } => designer,
+        }
+    }
+
+    pub fn translator(&self) -> Option<&str> {
+        match self {
+            ItemDetails::Book { translator, .. } => translator.as_deref(),
+            ItemDetails::Audiobook { translator, .. } => translator.as_deref(),
+            _ => None,
+        }
+    }
+
+    pub fn is_known_kind(kind: &str) -> bool {
+        Self::KINDS.contains(&kind)
+    }
+
+    /// Details of the kind with only the shared columns filled in
+    pub fn from_parts(kind: &str, creator: String, translator: Option<String>) -> Option<Self> {
+        let details = match kind {
+            "Book" => ItemDetails::Book {
+                author: creator,
+                translator,
+            },
+            "Game" => ItemDetails::Game {
+                creator,
+                platform: None,
+                playtime_minutes: None,
+            },
+            "Movie" => ItemDetails::Movie {
+                director: creator,
+                release_year: None,
+            },
+            "Series" => ItemDetails::Series {
+                creator,
+                season: None,
+                episodes: None,
+            },
+            "Audiobook" => ItemDetails::Audiobook {
+                author: creator,
+                translator,
+                narrator: None,
+                duration_minutes: None,
+            },
+            "BoardGame" => ItemDetails::BoardGame {
+                designer: creator,
+                min_players: None,
+                max_players: None,
+            },
+            _ => return None,
+        };
+        Some(details)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::library::NewLibraryItem;
+
+    #[test]
+    fn deserializes_kind_specific_fields() {
+        let item: NewLibraryItem = serde_json::from_str(
+            r#"{"kind":"Game","title":"Alan Wake","author":"Remedy","platform":"PC",
+                "playtimeMinutes":900,"completedAt":"2025-01-01","favorite":false,
+                "activatedChallengeIds":[]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(item.details.kind(), "Game");
+        assert_eq!(item.details.creator(), "Remedy");
+    }
+
+    #[test]
+    fn rejects_unknown_kind() {
+        let result = serde_json::from_str::<NewLibraryItem>(
+            r#"{"kind":"Podcast","title":"x","author":"y","completedAt":"2025-01-01",
+                "favorite":false,"activatedChallengeIds":[]}"#,
+        );
+
+        assert!(result.is_err());
+    }
+}
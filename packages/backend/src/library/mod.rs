@@ -2,27 +2,179 @@ use crate::{
     AppState,
     auth::User,
     database::Database,
+    languages::find_language,
     library::domain::{
-        create_library_item, delete_library_item, get_library_item_by_id, get_library_items,
-        update_library_item,
+        add_completion, add_progress_session, apply_bulk_operation, create_library_item,
+        delete_completion, delete_library_item, delete_progress_session, get_completions,
+        get_duplicates, get_language_statistics, get_library_item_by_id, get_library_items,
+        get_progress, get_progress_sessions, get_rating_statistics, get_yearly_completions,
+        merge_library_items, search_library_items, update_completion, update_library_item,
     },
+    metadata::isbn,
     utils::map_to_internal_error,
+    validation::{self, ApiError, ValidJson, ValidationErrors, map_validation_error},
 };
 use axum::{
     Json, Router,
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
     routing::{delete, get, post, put},
 };
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
+mod activation;
+mod bulk;
+mod completions;
 mod domain;
+mod duplicates;
+mod kinds;
+mod progress;
 mod repository;
+mod stats;
+mod status;
+
+pub use activation::{ActivationRange, ActivationReport};
+pub use bulk::{BulkItemResult, BulkOperation, BulkRequest};
+pub use completions::{Completion, NewCompletion, YearlyCompletions};
+pub use duplicates::{DuplicateGroup, MergeRequest};
+pub use kinds::ItemDetails;
+pub use progress::{NewProgressSession, Progress, ProgressSession};
+pub use repository::{insert_library_item, read_library_item, update_imported_item};
+pub use stats::{LanguageStatistics, YearlyRatings};
+pub use status::ItemStatus;
+
+#[cfg(test)]
+pub(crate) use repository::tests as test_support;
+
+const MAX_PAGE_SIZE: u32 = 200;
+const DEFAULT_SEARCH_LIMIT: u32 = 50;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LibraryFilter {
     pub user_id: String,
     pub item_id: Option<String>,
+    pub kind: Option<String>,
+    pub status: Option<ItemStatus>,
+    /// Inclusive lower bound for any completion, compared as an RFC 3339 prefix
+    pub completed_after: Option<String>,
+    /// Exclusive upper bound for any completion
+    pub completed_before: Option<String>,
+    pub favorite: Option<bool>,
+    pub challenge_id: Option<String>,
+    pub tag_id: Option<String>,
+    pub contributor_id: Option<String>,
+    pub language: Option<String>,
+    pub original_language: Option<String>,
+    pub sort: LibrarySort,
+    pub cursor: Option<LibraryCursor>,
+    pub limit: Option<u32>,
+}
+
+impl LibraryFilter {
+    pub fn new(user_id: &str) -> Self {
+        LibraryFilter {
+            user_id: user_id.to_string(),
+            item_id: None,
+            kind: None,
+            status: None,
+            completed_after: None,
+            completed_before: None,
+            favorite: None,
+            challenge_id: None,
+            tag_id: None,
+            contributor_id: None,
+            language: None,
+            original_language: None,
+            sort: LibrarySort::default(),
+            cursor: None,
+            limit: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LibrarySortField {
+    Title,
+    Author,
+    #[default]
+    CompletedAt,
+    AddedAt,
+}
+
+impl LibrarySortField {
+    /// The value of the sort column for an item, used to build the next cursor
+    fn value_of(&self, item: &LibraryItem) -> String {
+        match self {
+            LibrarySortField::Title => item.title.clone(),
+            LibrarySortField::Author => item.details.creator().to_string(),
+            LibrarySortField::CompletedAt => item.completed_at.clone().unwrap_or_default(),
+            LibrarySortField::AddedAt => item.added_at.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+    Asc,
+    #[default]
+    Desc,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct LibrarySort {
+    pub field: LibrarySortField,
+    pub direction: SortDirection,
+}
+
+/// Position after the last returned item: the value of the sort column and
+/// the item id as a tiebreaker.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LibraryCursor {
+    pub value: String,
+    pub id: String,
+}
+
+impl LibraryCursor {
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(format!("{}\n{}", self.id, self.value))
+    }
+
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let bytes = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+        let decoded = String::from_utf8(bytes).ok()?;
+        let (id, value) = decoded.split_once('\n')?;
+        Some(LibraryCursor {
+            value: value.to_string(),
+            id: id.to_string(),
+        })
+    }
+}
+
+/// Library item matched by a full-text search, with the matching terms of
+/// each field wrapped in `<mark>` tags. Snippets are HTML escaped.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LibrarySearchHit {
+    pub item: LibraryItem,
+    pub score: f64,
+    pub snippets: SearchSnippets,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchSnippets {
+    pub title: String,
+    pub author: String,
+    pub translator: Option<String>,
+}
+
+pub struct LibraryPage {
+    pub items: Vec<LibraryItem>,
+    pub next_cursor: Option<LibraryCursor>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -30,26 +182,170 @@ pub struct LibraryFilter {
 pub struct LibraryItem {
     pub id: String,
     pub user_id: String,
-    pub kind: String,
     pub title: String,
-    pub author: String,
     pub added_at: String,
-    pub completed_at: String,
+    pub status: ItemStatus,
+    pub started_at: Option<String>,
+    /// Date of the latest completion
+    pub completed_at: Option<String>,
+    pub completion_count: i64,
+    /// Normalized to ISBN-13
+    pub isbn: Option<String>,
+    pub page_count: Option<i64>,
+    pub expected_hours: Option<f64>,
+    /// ISO 639-1 code of the language the book was read in
+    pub language: Option<String>,
+    /// ISO 639-1 code of the language the book was written in
+    pub original_language: Option<String>,
     pub favorite: bool,
+    pub rating: Option<f64>,
+    /// Markdown as written by the user
+    pub review: Option<String>,
+    /// `review` rendered to sanitized HTML
+    pub review_html: Option<String>,
+    /// Private notes, never shown to other users
+    pub notes: Option<String>,
     pub activated_challenge_ids: Vec<String>,
-    pub translator: Option<String>,
+    /// Managed through the tag endpoints
+    pub tag_ids: Vec<String>,
+    /// Managed through the cover endpoints, changes with every upload
+    pub cover_id: Option<String>,
+    #[serde(flatten)]
+    pub details: ItemDetails,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct NewLibraryItem {
-    pub kind: String,
     pub title: String,
-    pub author: String,
-    pub completed_at: String,
+    /// Inferred from `completed_at` when missing
+    pub status: Option<ItemStatus>,
+    pub started_at: Option<String>,
+    pub completed_at: Option<String>,
+    /// ISBN-10 or ISBN-13, hyphens and spaces allowed
+    pub isbn: Option<String>,
+    pub page_count: Option<i64>,
+    pub expected_hours: Option<f64>,
+    /// ISO 639-1 code of the language the book was read in
+    pub language: Option<String>,
+    /// ISO 639-1 code of the language the book was written in
+    pub original_language: Option<String>,
     pub favorite: bool,
+    pub rating: Option<f64>,
+    pub review: Option<String>,
+    pub notes: Option<String>,
     pub activated_challenge_ids: Vec<String>,
-    pub translator: Option<String>,
+    #[serde(flatten)]
+    pub details: ItemDetails,
+}
+
+impl NewLibraryItem {
+    /// Ratings are given in half stars from 0.5 to 5
+    pub fn has_valid_rating(&self) -> bool {
+        match self.rating {
+            Some(rating) => (0.5..=5.0).contains(&rating) && (rating * 2.0).fract() == 0.0,
+            None => true,
+        }
+    }
+
+    /// Builds the item to store with the status and dates resolved. Challenges
+    /// are not activated.
+    pub fn to_library_item(&self, user_id: &str, now: &str) -> LibraryItem {
+        let status = self
+            .status
+            .unwrap_or_else(|| ItemStatus::infer(self.completed_at.as_deref()));
+        let (started_at, completed_at) =
+            status.resolve_dates(self.started_at.clone(), self.completed_at.clone(), now);
+
+        LibraryItem {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: user_id.to_string(),
+            title: self.title.clone(),
+            added_at: now.to_string(),
+            status,
+            started_at,
+            completed_at,
+            completion_count: 0, // Not stored
+            isbn: self.isbn.as_deref().and_then(isbn::normalize),
+            page_count: self.page_count,
+            expected_hours: self.expected_hours,
+            language: self.language.as_deref().and_then(language_code),
+            original_language: self.original_language.as_deref().and_then(language_code),
+            favorite: self.favorite,
+            rating: self.rating,
+            review: self.review.clone(),
+            review_html: None,
+            notes: self.notes.clone(),
+            activated_challenge_ids: Vec::new(),
+            tag_ids: Vec::new(),
+            cover_id: None,
+            details: self.details.clone(),
+        }
+    }
+
+    pub fn has_valid_isbn(&self) -> bool {
+        match &self.isbn {
+            Some(value) => isbn::normalize(value).is_some(),
+            None => true,
+        }
+    }
+
+    /// Checks the fields that don't need the database. References to
+    /// challenges are checked when updating.
+    pub fn validate(&self) -> ValidationErrors {
+        let mut errors = ValidationErrors::new();
+        errors.require("title", &self.title);
+        errors.date("startedAt", self.started_at.as_deref());
+        errors.date("completedAt", self.completed_at.as_deref());
+        let started_at = self.started_at.as_deref().and_then(validation::parse_date);
+        let completed_at = self
+            .completed_at
+            .as_deref()
+            .and_then(validation::parse_date);
+        if let (Some(started_at), Some(completed_at)) = (started_at, completed_at)
+            && started_at > completed_at
+        {
+            errors.add("completedAt", "must not be before startedAt");
+        }
+        if !self.has_valid_rating() {
+            errors.add("rating", "must be between 0.5 and 5 in steps of 0.5");
+        }
+        if !self.has_valid_isbn() {
+            errors.add("isbn", "must be a valid ISBN-10 or ISBN-13");
+        }
+        if self.page_count.is_some_and(|count| count < 1) {
+            errors.add("pageCount", "must be positive");
+        }
+        if self.expected_hours.is_some_and(|hours| hours <= 0.0) {
+            errors.add("expectedHours", "must be positive");
+        }
+        for (field, value) in [
+            ("language", &self.language),
+            ("originalLanguage", &self.original_language),
+        ] {
+            let Some(value) = value else { continue };
+            if language_code(value).is_none() {
+                errors.add(field, "must be an ISO 639-1 language code");
+            } else if !self.details.has_language() {
+                errors.add(field, "is only recorded for books");
+            }
+        }
+        if let ItemDetails::BoardGame {
+            min_players: Some(min),
+            max_players: Some(max),
+            ..
+        } = self.details
+            && min > max
+        {
+            errors.add("maxPlayers", "must not be less than minPlayers");
+        }
+        errors
+    }
+}
+
+/// Normalized ISO 639-1 code, `None` for unknown codes
+fn language_code(code: &str) -> Option<String> {
+    find_language(code).map(|language| language.code.to_string())
 }
 
 pub struct LibraryRepository {
@@ -62,6 +358,100 @@ impl LibraryRepository {
     }
 }
 
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct LibraryQuery {
+    kind: Option<String>,
+    status: Option<ItemStatus>,
+    year: Option<i32>,
+    /// Inclusive, YYYY-MM-DD
+    completed_from: Option<String>,
+    /// Inclusive, YYYY-MM-DD
+    completed_to: Option<String>,
+    favorite: Option<bool>,
+    challenge_id: Option<String>,
+    tag_id: Option<String>,
+    contributor_id: Option<String>,
+    /// ISO 639-1 code
+    language: Option<String>,
+    /// ISO 639-1 code
+    original_language: Option<String>,
+    sort: Option<LibrarySortField>,
+    direction: Option<SortDirection>,
+    cursor: Option<String>,
+    limit: Option<u32>,
+}
+
+impl LibraryQuery {
+    fn to_filter(&self, user: &User) -> Result<LibraryFilter, String> {
+        let mut filter = LibraryFilter::new(&user.id);
+        if let Some(kind) = &self.kind {
+            if !ItemDetails::is_known_kind(kind) {
+                return Err(format!("Unknown kind {}", kind));
+            }
+            filter.kind = Some(kind.clone());
+        }
+        filter.status = self.status;
+        filter.favorite = self.favorite;
+        filter.challenge_id = self.challenge_id.clone();
+        filter.tag_id = self.tag_id.clone();
+        filter.contributor_id = self.contributor_id.clone();
+        if let Some(language) = &self.language {
+            filter.language = Some(
+                language_code(language).ok_or_else(|| format!("Unknown language {}", language))?,
+            );
+        }
+        if let Some(language) = &self.original_language {
+            filter.original_language = Some(
+                language_code(language).ok_or_else(|| format!("Unknown language {}", language))?,
+            );
+        }
+
+        if let Some(year) = self.year {
+            if !validation::YEARS.contains(&year) {
+                return Err(format!("Invalid year {}", year));
+            }
+            let start = NaiveDate::from_ymd_opt(year, 1, 1).ok_or("Invalid year")?;
+            let end = NaiveDate::from_ymd_opt(year + 1, 1, 1).ok_or("Invalid year")?;
+            filter.completed_after = Some(start.to_string());
+            filter.completed_before = Some(end.to_string());
+        }
+        if let Some(from) = &self.completed_from {
+            let from = parse_date(from)?;
+            filter.completed_after = max_bound(filter.completed_after, from.to_string());
+        }
+        if let Some(to) = &self.completed_to {
+            let end = parse_date(to)?.succ_opt().ok_or("Invalid completedTo")?;
+            filter.completed_before = min_bound(filter.completed_before, end.to_string());
+        }
+
+        filter.sort = LibrarySort {
+            field: self.sort.unwrap_or_default(),
+            direction: self.direction.unwrap_or_default(),
+        };
+        if let Some(cursor) = &self.cursor {
+            filter.cursor = Some(LibraryCursor::decode(cursor).ok_or("Invalid cursor")?);
+        }
+        filter.limit = self.limit.map(|limit| limit.clamp(1, MAX_PAGE_SIZE));
+
+        Ok(filter)
+    }
+}
+
+/// Filters compare completion dates as strings, so only zero padded four
+/// digit years are accepted
+fn parse_date(value: &str) -> Result<NaiveDate, String> {
+    validation::parse_day(value).ok_or_else(|| format!("Invalid date {}", value))
+}
+
+fn max_bound(current: Option<String>, candidate: String) -> Option<String> {
+    Some(current.map_or(candidate.clone(), |c| c.max(candidate)))
+}
+
+fn min_bound(current: Option<String>, candidate: String) -> Option<String> {
+    Some(current.map_or(candidate.clone(), |c| c.min(candidate)))
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 struct IdResponse {
@@ -71,18 +461,113 @@ struct IdResponse {
 pub fn library_routes() -> Router<AppState> {
     Router::new()
         .route("/library", get(get_library_items_route))
+        .route("/library/search", get(search_library_items_route))
+        .route("/library/kinds", get(get_library_kinds_route))
+        .route("/library/stats/ratings", get(get_rating_statistics_route))
+        .route(
+            "/library/stats/languages",
+            get(get_language_statistics_route),
+        )
+        .route("/library/stats/yearly", get(get_yearly_completions_route))
+        .route("/library/duplicates", get(get_duplicates_route))
+        .route("/library/bulk", post(bulk_library_items_route))
         .route("/library/{id}", get(get_library_item_by_id_route))
         .route("/library", post(create_library_item_route))
         .route("/library/{id}", put(update_library_item_route))
         .route("/library/{id}", delete(delete_library))
+        .route("/library/{id}/merge", post(merge_library_items_route))
+        .route("/library/{id}/completions", get(get_completions_route))
+        .route("/library/{id}/completions", post(add_completion_route))
+        .route(
+            "/library/{id}/completions/{completionId}",
+            put(update_completion_route),
+        )
+        .route(
+            "/library/{id}/completions/{completionId}",
+            delete(delete_completion_route),
+        )
+        .route("/library/{id}/sessions", get(get_progress_sessions_route))
+        .route("/library/{id}/sessions", post(add_progress_session_route))
+        .route(
+            "/library/{id}/sessions/{sessionId}",
+            delete(delete_progress_session_route),
+        )
+        .route("/library/{id}/progress", get(get_progress_route))
 }
 
 async fn get_library_items_route(
     user: User,
     state: State<AppState>,
-) -> Result<Json<Vec<LibraryItem>>, StatusCode> {
-    let items = get_library_items(&user, &state).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    Ok(Json(items))
+    Query(query): Query<LibraryQuery>,
+) -> Result<(HeaderMap, Json<Vec<LibraryItem>>), StatusCode> {
+    let filter = query.to_filter(&user).map_err(|err| {
+        println!("Invalid library query: {}", err);
+        StatusCode::BAD_REQUEST
+    })?;
+    let page = get_library_items(&user, &state, filter).map_err(map_to_internal_error)?;
+
+    let mut headers = HeaderMap::new();
+    if let Some(cursor) = page.next_cursor {
+        let value = HeaderValue::from_str(&cursor.encode())
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        headers.insert("x-next-cursor", value);
+    }
+
+    Ok((headers, Json(page.items)))
+}
+
+#[derive(Deserialize, Debug)]
+struct SearchQuery {
+    q: String,
+    limit: Option<u32>,
+}
+
+async fn search_library_items_route(
+    user: User,
+    state: State<AppState>,
+    Query(query): Query<SearchQuery>,
+) -> Result<Json<Vec<LibrarySearchHit>>, StatusCode> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_SEARCH_LIMIT)
+        .clamp(1, MAX_PAGE_SIZE);
+    let hits =
+        search_library_items(&user, &state, &query.q, limit).map_err(map_to_internal_error)?;
+    Ok(Json(hits))
+}
+
+async fn get_library_kinds_route(_user: User) -> Json<&'static [&'static str]> {
+    Json(ItemDetails::KINDS)
+}
+
+#[derive(Deserialize, Debug)]
+struct StatsQuery {
+    kind: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct LanguageStatsQuery {
+    year: Option<i32>,
+}
+
+async fn get_language_statistics_route(
+    user: User,
+    state: State<AppState>,
+    Query(query): Query<LanguageStatsQuery>,
+) -> Result<Json<LanguageStatistics>, ApiError> {
+    let stats = get_language_statistics(&user, &state, query.year)
+        .map_err(|err| map_validation_error(err, map_to_internal_error))?;
+    Ok(Json(stats))
+}
+
+async fn get_rating_statistics_route(
+    user: User,
+    state: State<AppState>,
+    Query(query): Query<StatsQuery>,
+) -> Result<Json<Vec<YearlyRatings>>, StatusCode> {
+    let stats = get_rating_statistics(&user, &state, query.kind.as_deref())
+        .map_err(map_to_internal_error)?;
+    Ok(Json(stats))
 }
 
 async fn get_library_item_by_id_route(
@@ -101,31 +586,33 @@ async fn get_library_item_by_id_route(
 async fn create_library_item_route(
     user: User,
     state: State<AppState>,
-    Json(item): Json<NewLibraryItem>,
-) -> Result<Json<IdResponse>, StatusCode> {
+    ValidJson(item): ValidJson<NewLibraryItem>,
+) -> Result<Json<IdResponse>, ApiError> {
+    item.validate().into_result()?;
     let id = create_library_item(&user, &state, &item).map_err(map_to_internal_error)?;
 
-    Ok(Json(IdResponse { id: id }))
+    Ok(Json(IdResponse { id }))
 }
 
 async fn update_library_item_route(
     user: User,
     state: State<AppState>,
     Path(id): Path<String>,
-    Json(library): Json<NewLibraryItem>,
-) -> Result<Json<LibraryItem>, StatusCode> {
-    let success =
-        update_library_item(&user, &state, &id, &library).map_err(map_to_internal_error)?;
+    ValidJson(library): ValidJson<NewLibraryItem>,
+) -> Result<Json<LibraryItem>, ApiError> {
+    library.validate().into_result()?;
+    let success = update_library_item(&user, &state, &id, &library)
+        .map_err(|err| map_validation_error(err, map_to_internal_error))?;
 
     if !success {
-        return Err(StatusCode::NOT_FOUND);
+        return Err(StatusCode::NOT_FOUND.into());
     }
 
     let item = get_library_item_by_id(&user, &state, &id).map_err(map_to_internal_error)?;
 
     match item {
         Some(lib) => Ok(Json(lib)),
-        None => Err(StatusCode::NOT_FOUND),
+        None => Err(StatusCode::NOT_FOUND.into()),
     }
 }
 
@@ -142,3 +629,206 @@ async fn delete_library(
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+async fn bulk_library_items_route(
+    user: User,
+    state: State<AppState>,
+    ValidJson(request): ValidJson<BulkRequest>,
+) -> Result<Json<Vec<BulkItemResult>>, ApiError> {
+    let results = apply_bulk_operation(&user, &state, &request)
+        .map_err(|err| map_validation_error(err, map_to_internal_error))?;
+
+    match results {
+        Some(results) => Ok(Json(results)),
+        None => Err(StatusCode::NOT_FOUND.into()),
+    }
+}
+
+async fn get_duplicates_route(
+    user: User,
+    state: State<AppState>,
+) -> Result<Json<Vec<DuplicateGroup>>, StatusCode> {
+    let groups = get_duplicates(&user, &state).map_err(map_to_internal_error)?;
+    Ok(Json(groups))
+}
+
+async fn merge_library_items_route(
+    user: User,
+    state: State<AppState>,
+    Path(id): Path<String>,
+    ValidJson(request): ValidJson<MergeRequest>,
+) -> Result<Json<LibraryItem>, ApiError> {
+    let item = merge_library_items(&user, &state, &id, &request.source_ids)
+        .map_err(|err| map_validation_error(err, map_to_internal_error))?;
+
+    match item {
+        Some(item) => Ok(Json(item)),
+        None => Err(StatusCode::NOT_FOUND.into()),
+    }
+}
+
+async fn get_yearly_completions_route(
+    user: User,
+    state: State<AppState>,
+) -> Result<Json<Vec<YearlyCompletions>>, StatusCode> {
+    let stats = get_yearly_completions(&user, &state).map_err(map_to_internal_error)?;
+    Ok(Json(stats))
+}
+
+async fn get_completions_route(
+    user: User,
+    state: State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<Completion>>, StatusCode> {
+    match get_completions(&user, &state, &id).map_err(map_to_internal_error)? {
+        Some(completions) => Ok(Json(completions)),
+        None => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+async fn add_completion_route(
+    user: User,
+    state: State<AppState>,
+    Path(id): Path<String>,
+    ValidJson(completion): ValidJson<NewCompletion>,
+) -> Result<Json<IdResponse>, ApiError> {
+    match add_completion(&user, &state, &id, &completion)
+        .map_err(|err| map_validation_error(err, map_to_internal_error))?
+    {
+        Some(id) => Ok(Json(IdResponse { id })),
+        None => Err(StatusCode::NOT_FOUND.into()),
+    }
+}
+
+async fn update_completion_route(
+    user: User,
+    state: State<AppState>,
+    Path((id, completion_id)): Path<(String, String)>,
+    ValidJson(completion): ValidJson<NewCompletion>,
+) -> Result<StatusCode, ApiError> {
+    let success = update_completion(&user, &state, &id, &completion_id, &completion)
+        .map_err(|err| map_validation_error(err, map_to_internal_error))?;
+
+    if !success {
+        return Err(StatusCode::NOT_FOUND.into());
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn delete_completion_route(
+    user: User,
+    state: State<AppState>,
+    Path((id, completion_id)): Path<(String, String)>,
+) -> Result<StatusCode, StatusCode> {
+    let success =
+        delete_completion(&user, &state, &id, &completion_id).map_err(map_to_internal_error)?;
+
+    if !success {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_progress_sessions_route(
+    user: User,
+    state: State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<ProgressSession>>, StatusCode> {
+    match get_progress_sessions(&user, &state, &id).map_err(map_to_internal_error)? {
+        Some(sessions) => Ok(Json(sessions)),
+        None => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+async fn add_progress_session_route(
+    user: User,
+    state: State<AppState>,
+    Path(id): Path<String>,
+    ValidJson(session): ValidJson<NewProgressSession>,
+) -> Result<Json<IdResponse>, ApiError> {
+    match add_progress_session(&user, &state, &id, &session)
+        .map_err(|err| map_validation_error(err, map_to_internal_error))?
+    {
+        Some(id) => Ok(Json(IdResponse { id })),
+        None => Err(StatusCode::NOT_FOUND.into()),
+    }
+}
+
+async fn delete_progress_session_route(
+    user: User,
+    state: State<AppState>,
+    Path((id, session_id)): Path<(String, String)>,
+) -> Result<StatusCode, StatusCode> {
+    let success =
+        delete_progress_session(&user, &state, &id, &session_id).map_err(map_to_internal_error)?;
+
+    if !success {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_progress_route(
+    user: User,
+    state: State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Progress>, StatusCode> {
+    match get_progress(&user, &state, &id).map_err(map_to_internal_error)? {
+        Some(progress) => Ok(Json(progress)),
+        None => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(started_at: &str, completed_at: &str) -> NewLibraryItem {
+        serde_json::from_value(serde_json::json!({
+            "kind": "Book",
+            "title": "Title",
+            "author": "Author",
+            "startedAt": started_at,
+            "completedAt": completed_at,
+            "favorite": false,
+            "activatedChallengeIds": [],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn rejects_unpadded_dates_instead_of_comparing_them() {
+        let errors = item("2025-1-1", "2025-1-2").validate();
+        let fields: Vec<&str> = errors.errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, vec!["startedAt", "completedAt"]);
+
+        assert!(!item(" 2025-01-01", "2025-01-02").validate().is_empty());
+        assert!(
+            item("2025-01-01", "2025-01-02T10:00:00Z")
+                .validate()
+                .is_empty()
+        );
+        assert!(
+            !item("2025-01-03", "2025-01-02T10:00:00Z")
+                .validate()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn rejects_years_without_four_digits() {
+        let user = User::new("me".to_string());
+        let query = |year: i32| LibraryQuery {
+            year: Some(year),
+            ..Default::default()
+        };
+        assert!(query(i32::MAX).to_filter(&user).is_err());
+        assert!(query(10000).to_filter(&user).is_err());
+
+        let filter = query(9999).to_filter(&user).unwrap();
+        assert_eq!(filter.completed_after.as_deref(), Some("9999-01-01"));
+    }
+}
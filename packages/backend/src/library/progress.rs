@@ -0,0 +1,248 @@
+use chrono::{Days, NaiveDate};
+use rusqlite::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    database::{Repository, query_in_transation},
+    library::LibraryRepository,
+    validation::ValidationErrors,
+};
+
+/// Pages read or minutes played on a single day
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgressSession {
+    pub id: String,
+    pub item_id: String,
+    pub date: String,
+    pub pages: Option<i64>,
+    pub minutes: Option<i64>,
+    pub note: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct NewProgressSession {
+    pub date: String,
+    pub pages: Option<i64>,
+    pub minutes: Option<i64>,
+    pub note: Option<String>,
+}
+
+impl NewProgressSession {
+    pub fn validate(&self) -> ValidationErrors {
+        let mut errors = ValidationErrors::new();
+        errors.date("date", Some(&self.date));
+        if self.pages.is_none() && self.minutes.is_none() {
+            errors.add("pages", "pages or minutes is required");
+        }
+        if self.pages.is_some_and(|pages| pages < 0) {
+            errors.add("pages", "must not be negative");
+        }
+        if self.minutes.is_some_and(|minutes| minutes < 0) {
+            errors.add("minutes", "must not be negative");
+        }
+        errors
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ProgressUnit {
+    Pages,
+    Minutes,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Progress {
+    pub unit: ProgressUnit,
+    pub done: i64,
+    /// Page count or expected hours as minutes, when known
+    pub total: Option<i64>,
+    pub percent: Option<f64>,
+    /// Average pages or minutes per day since the first session
+    pub pace_per_day: Option<f64>,
+    pub projected_finish: Option<String>,
+}
+
+/// Computes progress from the sessions logged so far. Pages are used when
+/// the item has a page count, minutes when it has an expected duration.
+pub fn calculate_progress(
+    page_count: Option<i64>,
+    expected_hours: Option<f64>,
+    sessions: &[ProgressSession],
+    today: NaiveDate,
+) -> Progress {
+    let has_pages = sessions.iter().any(|s| s.pages.is_some());
+    let (unit, total) = match (page_count, expected_hours) {
+        (Some(pages), _) => (ProgressUnit::Pages, Some(pages)),
+        (None, Some(hours)) => (ProgressUnit::Minutes, Some((hours * 60.0).round() as i64)),
+        (None, None) if has_pages => (ProgressUnit::Pages, None),
+        (None, None) => (ProgressUnit::Minutes, None),
+    };
+
+    let done: i64 = sessions
+        .iter()
+        .filter_map(|s| match unit {
+            ProgressUnit::Pages => s.pages,
+            ProgressUnit::Minutes => s.minutes,
+        })
+        .sum();
+
+    let percent = total
+        .filter(|total| *total > 0)
+        .map(|total| (done as f64 / total as f64 * 100.0).min(100.0));
+
+    let first_day = sessions.iter().filter_map(|s| session_day(&s.date)).min();
+    let pace_per_day = first_day.map(|first| {
+        let days = (today - first).num_days().max(0) + 1;
+        done as f64 / days as f64
+    });
+
+    let projected_finish = match (total, pace_per_day) {
+        (Some(total), Some(pace)) if pace > 0.0 && done < total => {
+            let days_left = ((total - done) as f64 / pace).ceil() as u64;
+            today
+                .checked_add_days(Days::new(days_left))
+                .map(|date| date.to_string())
+        }
+        _ => None,
+    };
+
+    Progress {
+        unit,
+        done,
+        total,
+        percent,
+        pace_per_day,
+        projected_finish,
+    }
+}
+
+/// Sessions of the current read or playthrough: those after the given
+/// completion. Sessions on the day of the completion belong to the read it
+/// finished.
+pub fn sessions_after(
+    sessions: &[ProgressSession],
+    completed_at: Option<&str>,
+) -> Vec<ProgressSession> {
+    let Some(completed_on) = completed_at.and_then(session_day) else {
+        return sessions.to_vec();
+    };
+    sessions
+        .iter()
+        .filter(|s| session_day(&s.date).is_some_and(|day| day > completed_on))
+        .cloned()
+        .collect()
+}
+
+/// Sessions are stored with a date or an RFC 3339 timestamp
+fn session_day(date: &str) -> Option<NaiveDate> {
+    date.get(..10)
+        .and_then(|day| NaiveDate::parse_from_str(day, "%Y-%m-%d").ok())
+}
+
+impl LibraryRepository {
+    pub fn progress_sessions(&mut self, item_id: &str) -> Result<Vec<ProgressSession>> {
+        let tx = self.transaction()?;
+        let sessions = query_in_transation(
+            &tx,
+            "SELECT id, item_id, date, pages, minutes, note FROM progress_session 
+                WHERE item_id = ? ORDER BY date, id",
+            &[&item_id],
+            |row| {
+                Ok(ProgressSession {
+                    id: row.get(0)?,
+                    item_id: row.get(1)?,
+                    date: row.get(2)?,
+                    pages: row.get(3)?,
+                    minutes: row.get(4)?,
+                    note: row.get(5)?,
+                })
+            },
+        )?;
+        tx.commit()?;
+        Ok(sessions)
+    }
+
+    pub fn create_progress_session(&mut self, session: &ProgressSession) -> Result<String> {
+        let tx = self.transaction()?;
+        tx.execute(
+            "INSERT INTO progress_session (id, item_id, date, pages, minutes, note) 
+                VALUES (?, ?, ?, ?, ?, ?)",
+            rusqlite::params![
+                session.id,
+                session.item_id,
+                session.date,
+                session.pages,
+                session.minutes,
+                session.note
+            ],
+        )?;
+        tx.commit()?;
+        Ok(session.id.clone())
+    }
+
+    pub fn delete_progress_session(&mut self, item_id: &str, session_id: &str) -> Result<bool> {
+        let tx = self.transaction()?;
+        let result = tx.execute(
+            "DELETE FROM progress_session WHERE id = ? AND item_id = ?",
+            [session_id, item_id],
+        )?;
+        tx.commit()?;
+        Ok(result == 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(date: &str, pages: i64) -> ProgressSession {
+        ProgressSession {
+            id: date.to_string(),
+            item_id: "item".to_string(),
+            date: date.to_string(),
+            pages: Some(pages),
+            minutes: None,
+            note: None,
+        }
+    }
+
+    #[test]
+    fn projects_finish_from_pace() {
+        let sessions = vec![session("2026-03-01", 40), session("2026-03-04", 60)];
+        let today = NaiveDate::from_ymd_opt(2026, 3, 4).unwrap();
+
+        let progress = calculate_progress(Some(300), None, &sessions, today);
+
+        assert_eq!(progress.done, 100);
+        assert_eq!(progress.pace_per_day, Some(25.0));
+        assert_eq!(progress.projected_finish, Some("2026-03-12".to_string()));
+    }
+
+    #[test]
+    fn finished_item_has_no_projection() {
+        let sessions = vec![session("2026-03-01", 350)];
+        let today = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+
+        let progress = calculate_progress(Some(300), None, &sessions, today);
+
+        assert_eq!(progress.percent, Some(100.0));
+        assert_eq!(progress.projected_finish, None);
+    }
+
+    #[test]
+    fn rereads_only_count_sessions_after_the_completion() {
+        let sessions = vec![
+            session("2025-01-10", 300),
+            session("2025-01-20", 20),
+            session("2026-02-01T08:00:00Z", 40),
+        ];
+
+        let current = sessions_after(&sessions, Some("2025-01-20T21:00:00+00:00"));
+        assert_eq!(current, vec![sessions[2].clone()]);
+        assert_eq!(sessions_after(&sessions, None), sessions);
+    }
+}
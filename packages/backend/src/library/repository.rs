@@ -1,104 +1,139 @@
+use crate::contributors::{delete_unused_contributors, sync_legacy_contributors};
 use crate::database::{Repository, query_in_transation};
-use crate::library::{LibraryFilter, LibraryItem, LibraryRepository};
-use rusqlite::{OptionalExtension, Result};
+use crate::library::completions::{Completion, insert_completion, sync_latest_completion};
+use crate::library::{
+    ItemDetails, ItemStatus, LibraryFilter, LibraryItem, LibraryRepository, LibrarySearchHit,
+    LibrarySortField, SearchSnippets, SortDirection,
+};
+use crate::markdown;
+use rusqlite::{Connection, OptionalExtension, Result, types::Value};
+
+// Tags, completion count and kind specific extension columns selected next to `l.*`
+const EXTENSION_COLUMNS: &str =
+    "(SELECT GROUP_CONCAT(it.tag_id) FROM item_tag it WHERE it.item_id = l.id) as tag_ids,
+    (SELECT COUNT(*) FROM completion c WHERE c.item_id = l.id) as completion_count,
+    (SELECT cv.id FROM cover cv WHERE cv.item_id = l.id) as cover_id,
+    g.platform, g.playtime_minutes, m.release_year, 
+    s.season, s.episodes, a.narrator, a.duration_minutes, b.min_players, b.max_players";
+const EXTENSION_JOINS: &str = "LEFT JOIN library_game g ON g.item_id = l.id
+    LEFT JOIN library_movie m ON m.item_id = l.id
+    LEFT JOIN library_series s ON s.item_id = l.id
+    LEFT JOIN library_audiobook a ON a.item_id = l.id
+    LEFT JOIN library_board_game b ON b.item_id = l.id";
+const EXTENSION_TABLES: &[&str] = &[
+    "library_game",
+    "library_movie",
+    "library_series",
+    "library_audiobook",
+    "library_board_game",
+];
 
 impl Repository<LibraryItem, LibraryFilter> for LibraryRepository {
     fn create(&mut self, item: &LibraryItem) -> Result<String> {
-        let sql =
-            "INSERT INTO library (id, user_id, kind, title, author, added_at, completed_at, favorite, translator) 
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)";
-
         let tx = self.transaction()?;
-
-        tx.execute::<&[&dyn rusqlite::ToSql]>(
-            &sql,
-            &[
-                &item.id,
-                &item.user_id,
-                &item.kind,
-                &item.title,
-                &item.author,
-                &item.added_at,
-                &item.completed_at,
-                &(if item.favorite { 1i64 } else { 0i64 }),
-                &item.translator,
-            ],
-        )?;
-
-        // Insert new challenge associations
-        for challenge_id in &item.activated_challenge_ids {
-            tx.execute(
-                "INSERT INTO activated_item_challenge (item_id, challenge_id) VALUES (?, ?)",
-                &[&item.id, &challenge_id],
-            )?;
-        }
-
+        insert_library_item(&tx, item)?;
         tx.commit()?;
 
         Ok(item.id.clone())
     }
 
     fn read_by_id(&mut self, id: &str) -> Result<Option<LibraryItem>> {
-        let sql = "SELECT l.*, GROUP_CONCAT(aic.challenge_id) as challenge_ids 
-            FROM library l 
-            LEFT JOIN activated_item_challenge aic ON l.id = aic.item_id 
-            WHERE l.id = ?
-            GROUP BY l.id";
-        let connection = self.conn();
-        let item = connection
-            .query_row(sql, &[&id], row_to_library_item)
-            .optional()?;
-
-        Ok(item)
+        read_library_item(self.conn(), id)
     }
 
     fn search(&mut self, filter: LibraryFilter) -> Result<Vec<LibraryItem>> {
         let (conditions, params) = to_sql_params(&filter);
 
-        let sql = format!(
-            "SELECT l.*, GROUP_CONCAT(aic.challenge_id) as challenge_ids 
+        let (order_column, direction) = sort_column(&filter);
+
+        let mut sql = format!(
+            "SELECT l.*, {}, GROUP_CONCAT(aic.challenge_id) as challenge_ids 
             FROM library l
+            {}
             LEFT JOIN activated_item_challenge aic ON l.id = aic.item_id 
             WHERE {} 
-            GROUP BY l.id",
-            conditions
+            GROUP BY l.id
+            ORDER BY {} {}, l.id {}",
+            EXTENSION_COLUMNS, EXTENSION_JOINS, conditions, order_column, direction, direction
         );
+        if let Some(limit) = filter.limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+        let params: Vec<&dyn rusqlite::ToSql> =
+            params.iter().map(|p| p as &dyn rusqlite::ToSql).collect();
 
         let tx = self.transaction()?;
         let items = query_in_transation(&tx, &sql, &params, row_to_library_item)?;
         tx.commit()?;
 
-        Ok(items)
+        Ok(items.into_iter().flatten().collect())
     }
 
     fn update(&mut self, id: &str, item: &LibraryItem) -> Result<bool> {
-        // Update the main library item
+        // Update the main library item. completed_at follows the completions.
+        // Optional fields left out of the update keep their stored value, as
+        // clients that don't know them would otherwise clear them.
         let sql =
-            "UPDATE library SET user_id = ?, kind = ?, title = ?, author = ?, completed_at = ?, favorite = ?, translator = ? 
+            "UPDATE library SET user_id = ?, kind = ?, title = ?, author = ?, status = ?, started_at = ?, favorite = ?, translator = ?, 
+                rating = COALESCE(?, rating), review = COALESCE(?, review), notes = COALESCE(?, notes),
+                isbn = COALESCE(?, isbn), page_count = COALESCE(?, page_count),
+                expected_hours = COALESCE(?, expected_hours),
+                language = CASE WHEN ? THEN COALESCE(?, language) END,
+                original_language = CASE WHEN ? THEN COALESCE(?, original_language) END
              WHERE id = ?";
 
         let tx = self.transaction()?;
 
+        let previous_status: Option<String> = tx
+            .query_row("SELECT status FROM library WHERE id = ?", [id], |row| {
+                row.get(0)
+            })
+            .optional()?;
+
         let result = tx.execute::<&[&dyn rusqlite::ToSql]>(
             sql,
             &[
                 &item.user_id,
-                &item.kind,
+                &item.details.kind(),
                 &item.title,
-                &item.author,
-                &item.completed_at,
+                &item.details.creator(),
+                &item.status.as_str(),
+                &item.started_at,
                 &(if item.favorite { 1 } else { 0 }),
-                &item.translator,
+                &item.details.translator(),
+                &item.rating,
+                &item.review,
+                &item.notes,
+                &item.isbn,
+                &item.page_count,
+                &item.expected_hours,
+                &item.details.has_language(),
+                &item.language,
+                &item.details.has_language(),
+                &item.original_language,
                 &id,
             ],
         )?;
 
         // Only proceed with challenge updates if the item exists
         if result > 0 {
+            write_details(&tx, id, &item.details)?;
+            sync_legacy_contributors(&tx, id, &item.user_id, &item.details)?;
+            sync_latest_completion(
+                &tx,
+                id,
+                previous_status
+                    .as_deref()
+                    .and_then(ItemStatus::parse)
+                    .unwrap_or_default(),
+                item.status,
+                item.completed_at.as_deref(),
+            )?;
+
             // Delete existing challenge associations
             tx.execute(
                 "DELETE FROM activated_item_challenge WHERE item_id = ?",
-                &[&id],
+                [&id],
             )?;
 
             // Insert new challenge associations
@@ -115,10 +150,11 @@ impl Repository<LibraryItem, LibraryFilter> for LibraryRepository {
     }
 
     fn delete(&mut self, id: &str) -> Result<bool> {
-        let sql = "DELETE FROM library WHERE id = ?";
+        let sql = "DELETE FROM library WHERE id = ? RETURNING user_id";
         let tx = self.transaction()?;
-        let result = tx.execute(sql, &[&id])?;
-        if result == 1 {
+        let user_id: Option<String> = tx.query_row(sql, [&id], |row| row.get(0)).optional()?;
+        if let Some(user_id) = user_id {
+            delete_unused_contributors(&tx, &user_id)?;
             tx.commit()?;
             return Ok(true);
         }
@@ -130,39 +166,841 @@ impl Repository<LibraryItem, LibraryFilter> for LibraryRepository {
     }
 }
 
-fn row_to_library_item(row: &rusqlite::Row) -> Result<LibraryItem> {
-    let challenge_ids: Option<String> = row.get(9)?;
+// Markers wrapped around matched terms by FTS5, replaced after HTML escaping
+const MATCH_START: &str = "\u{E000}";
+const MATCH_END: &str = "\u{E001}";
+
+impl LibraryRepository {
+    /// Calls `f` with every item of the user in the order they were added
+    /// without collecting them first. Stops early when `f` returns false.
+    pub fn for_each_item<F>(&mut self, user_id: &str, mut f: F) -> Result<()>
+    where
+        F: FnMut(LibraryItem) -> bool,
+    {
+        let sql = format!(
+            "SELECT l.*, {}, 
+                (SELECT GROUP_CONCAT(aic.challenge_id) FROM activated_item_challenge aic 
+                    WHERE aic.item_id = l.id) as challenge_ids 
+            FROM library l 
+            {}
+            WHERE l.user_id = ?
+            ORDER BY l.added_at, l.id",
+            EXTENSION_COLUMNS, EXTENSION_JOINS
+        );
+        let mut stmt = self.conn().prepare(&sql)?;
+        let mut rows = stmt.query([user_id])?;
+        while let Some(row) = rows.next()? {
+            if let Some(item) = row_to_library_item(row)?
+                && !f(item)
+            {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Ranked full-text search over the user's library. `query` is free-form
+    /// user input; every word is matched as a prefix.
+    pub fn full_text_search(
+        &mut self,
+        user_id: &str,
+        query: &str,
+        limit: u32,
+    ) -> Result<Vec<LibrarySearchHit>> {
+        let Some(fts_query) = to_fts_query(query) else {
+            return Ok(Vec::new());
+        };
+
+        let sql = format!(
+            "SELECT l.*, {}, 
+                (SELECT GROUP_CONCAT(aic.challenge_id) FROM activated_item_challenge aic 
+                    WHERE aic.item_id = l.id) as challenge_ids,
+                snippet(library_fts, 1, ?1, ?2, '…', 12) as title_snippet,
+                snippet(library_fts, 2, ?1, ?2, '…', 12) as author_snippet,
+                snippet(library_fts, 3, ?1, ?2, '…', 12) as translator_snippet,
+                bm25(library_fts, 0.0, 10.0, 5.0, 2.0) as score
+            FROM library_fts
+            JOIN library l ON l.id = library_fts.item_id
+            {}
+            WHERE library_fts MATCH ?3 AND l.user_id = ?4
+            ORDER BY score
+            LIMIT ?5",
+            EXTENSION_COLUMNS, EXTENSION_JOINS
+        );
+
+        let tx = self.transaction()?;
+        let hits = query_in_transation(
+            &tx,
+            &sql,
+            rusqlite::params![MATCH_START, MATCH_END, fts_query, user_id, limit],
+            |row| {
+                let Some(item) = row_to_library_item(row)? else {
+                    return Ok(None);
+                };
+                let translator_snippet: Option<String> = row.get("translator_snippet")?;
+                Ok(Some(LibrarySearchHit {
+                    item,
+                    // bm25 is smaller for better matches, flip it for the API
+                    score: -row.get::<_, f64>("score")?,
+                    snippets: SearchSnippets {
+                        title: escape_snippet(&row.get::<_, String>("title_snippet")?),
+                        author: escape_snippet(&row.get::<_, String>("author_snippet")?),
+                        translator: translator_snippet
+                            .filter(|s| !s.is_empty())
+                            .map(|s| escape_snippet(&s)),
+                    },
+                }))
+            },
+        )?;
+        tx.commit()?;
+
+        Ok(hits.into_iter().flatten().collect())
+    }
+}
+
+/// Inserts an item with its details, first completion and challenge links
+/// inside an existing transaction
+pub fn insert_library_item(tx: &Connection, item: &LibraryItem) -> Result<()> {
+    let sql =
+        "INSERT INTO library (id, user_id, kind, title, author, added_at, status, started_at, completed_at, favorite, translator, rating, review, notes, 
+            isbn, page_count, expected_hours, language, original_language) 
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)";
+
+    tx.execute::<&[&dyn rusqlite::ToSql]>(
+        sql,
+        &[
+            &item.id,
+            &item.user_id,
+            &item.details.kind(),
+            &item.title,
+            &item.details.creator(),
+            &item.added_at,
+            &item.status.as_str(),
+            &item.started_at,
+            &item.completed_at,
+            &(if item.favorite { 1i64 } else { 0i64 }),
+            &item.details.translator(),
+            &item.rating,
+            &item.review,
+            &item.notes,
+            &item.isbn,
+            &item.page_count,
+            &item.expected_hours,
+            &item.language,
+            &item.original_language,
+        ],
+    )?;
+    write_details(tx, &item.id, &item.details)?;
+    sync_legacy_contributors(tx, &item.id, &item.user_id, &item.details)?;
+
+    if let Some(completed_at) = &item.completed_at {
+        insert_completion(
+            tx,
+            &Completion {
+                id: uuid::Uuid::new_v4().to_string(),
+                item_id: item.id.clone(),
+                completed_at: completed_at.clone(),
+                note: None,
+            },
+        )?;
+    }
+
+    // Insert new challenge associations
+    for challenge_id in &item.activated_challenge_ids {
+        tx.execute(
+            "INSERT INTO activated_item_challenge (item_id, challenge_id) VALUES (?, ?)",
+            [&item.id, challenge_id],
+        )?;
+    }
+
+    Ok(())
+}
+
+pub fn read_library_item(conn: &Connection, id: &str) -> Result<Option<LibraryItem>> {
+    let sql = format!(
+        "SELECT l.*, {}, GROUP_CONCAT(aic.challenge_id) as challenge_ids 
+        FROM library l 
+        {}
+        LEFT JOIN activated_item_challenge aic ON l.id = aic.item_id 
+        WHERE l.id = ?
+        GROUP BY l.id",
+        EXTENSION_COLUMNS, EXTENSION_JOINS
+    );
+    conn.query_row(&sql, [&id], row_to_library_item)
+        .optional()
+        .map(Option::flatten)
+}
+
+/// Updates the fields an importer owns: title, kind specific details,
+/// status and dates. Ratings, reviews, notes, tags and challenge links set
+/// by the user are kept, except that challenges are deactivated when the
+/// item no longer counts toward them.
+pub fn update_imported_item(conn: &Connection, item: &LibraryItem) -> Result<()> {
+    let previous_status = conn.query_row(
+        "SELECT status FROM library WHERE id = ?",
+        [&item.id],
+        row_to_status,
+    )?;
+
+    conn.execute(
+        "UPDATE library SET kind = ?, title = ?, author = ?, translator = ?, status = ?, started_at = ? 
+            WHERE id = ?",
+        rusqlite::params![
+            item.details.kind(),
+            item.title,
+            item.details.creator(),
+            item.details.translator(),
+            item.status.as_str(),
+            item.started_at,
+            item.id
+        ],
+    )?;
+    write_details(conn, &item.id, &item.details)?;
+    sync_legacy_contributors(conn, &item.id, &item.user_id, &item.details)?;
+    sync_latest_completion(
+        conn,
+        &item.id,
+        previous_status,
+        item.status,
+        item.completed_at.as_deref(),
+    )?;
+
+    if !item.status.counts_toward_challenges() {
+        conn.execute(
+            "DELETE FROM activated_item_challenge WHERE item_id = ?",
+            [&item.id],
+        )?;
+    }
+    Ok(())
+}
+
+/// Turns user input into an FTS5 query where every word is a quoted prefix
+/// term, so that FTS syntax characters in the input are never interpreted.
+fn to_fts_query(input: &str) -> Option<String> {
+    let terms: Vec<String> = input
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("\"{}\"*", word))
+        .collect();
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+fn escape_snippet(snippet: &str) -> String {
+    snippet
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace(MATCH_START, "<mark>")
+        .replace(MATCH_END, "</mark>")
+}
+
+/// Rows of a kind this version does not know are skipped with a log line
+/// instead of failing the whole query
+fn row_to_library_item(row: &rusqlite::Row) -> Result<Option<LibraryItem>> {
+    let Some(details) = row_to_details(row)? else {
+        let id: String = row.get("id")?;
+        let kind: String = row.get("kind")?;
+        println!("Skipping library item {} of unknown kind {}", id, kind);
+        return Ok(None);
+    };
+    let challenge_ids: Option<String> = row.get("challenge_ids")?;
     let activated_challenge_ids = challenge_ids
         .map(|ids| ids.split(',').map(String::from).collect())
         .unwrap_or_default();
+    let review: Option<String> = row.get("review")?;
+    let tag_ids: Option<String> = row.get("tag_ids")?;
 
-    Ok(LibraryItem {
-        id: row.get(0)?,
-        user_id: row.get(1)?,
-        kind: row.get(2)?,
-        title: row.get(3)?,
-        author: row.get(4)?,
-        added_at: row.get(5)?,
-        completed_at: row.get(6)?,
-        favorite: row.get::<_, i64>(7)? != 0,
-        translator: row.get(8)?,
+    Ok(Some(LibraryItem {
+        id: row.get("id")?,
+        user_id: row.get("user_id")?,
+        title: row.get("title")?,
+        added_at: row.get("added_at")?,
+        status: row_to_status(row)?,
+        started_at: row.get("started_at")?,
+        completed_at: row.get("completed_at")?,
+        completion_count: row.get("completion_count")?,
+        isbn: row.get("isbn")?,
+        page_count: row.get("page_count")?,
+        expected_hours: row.get("expected_hours")?,
+        language: row.get("language")?,
+        original_language: row.get("original_language")?,
+        favorite: row.get::<_, i64>("favorite")? != 0,
+        rating: row.get("rating")?,
+        review_html: review.as_deref().map(markdown::render_sanitized),
+        review,
+        notes: row.get("notes")?,
         activated_challenge_ids,
+        tag_ids: tag_ids
+            .map(|ids| ids.split(',').map(String::from).collect())
+            .unwrap_or_default(),
+        cover_id: row.get("cover_id")?,
+        details,
+    }))
+}
+
+fn row_to_status(row: &rusqlite::Row) -> Result<ItemStatus> {
+    let status: String = row.get("status")?;
+    ItemStatus::parse(&status).ok_or_else(|| {
+        rusqlite::Error::FromSqlConversionFailure(
+            0,
+            rusqlite::types::Type::Text,
+            format!("Unknown library item status {}", status).into(),
+        )
     })
 }
 
-fn to_sql_params(item: &LibraryFilter) -> (String, Vec<&dyn rusqlite::ToSql>) {
-    let mut params: Vec<&dyn rusqlite::ToSql> = Vec::new();
-    let mut conditions = Vec::new();
+fn row_to_details(row: &rusqlite::Row) -> Result<Option<ItemDetails>> {
+    let kind: String = row.get("kind")?;
+    let details = match kind.as_str() {
+        "Book" => ItemDetails::Book {
+            author: row.get("author")?,
+            translator: row.get("translator")?,
+        },
+        "Game" => ItemDetails::Game {
+            creator: row.get("author")?,
+            platform: row.get("platform")?,
+            playtime_minutes: row.get("playtime_minutes")?,
+        },
+        "Movie" => ItemDetails::Movie {
+            director: row.get("author")?,
+            release_year: row.get("release_year")?,
+        },
+        "Series" => ItemDetails::Series {
+            creator: row.get("author")?,
+            season: row.get("season")?,
+            episodes: row.get("episodes")?,
+        },
+        "Audiobook" => ItemDetails::Audiobook {
+            author: row.get("author")?,
+            translator: row.get("translator")?,
+            narrator: row.get("narrator")?,
+            duration_minutes: row.get("duration_minutes")?,
+        },
+        "BoardGame" => ItemDetails::BoardGame {
+            designer: row.get("author")?,
+            min_players: row.get("min_players")?,
+            max_players: row.get("max_players")?,
+        },
+        _ => return Ok(None),
+    };
+    Ok(Some(details))
+}
+
+/// Replaces the kind specific extension rows of an item
+/// Writes the extension row of the item's kind and removes rows of other
+/// kinds. Extension fields left out keep their stored value while the kind
+/// stays the same.
+fn write_details(tx: &Connection, item_id: &str, details: &ItemDetails) -> Result<()> {
+    let extension: Option<(&str, &[&str], Vec<&dyn rusqlite::ToSql>)> = match details {
+        ItemDetails::Book { .. } => None,
+        ItemDetails::Game {
+            platform,
+            playtime_minutes,
+            ..
+        } => Some((
+            "library_game",
+            &["platform", "playtime_minutes"],
+            vec![platform, playtime_minutes],
+        )),
+        ItemDetails::Movie { release_year, .. } => {
+            Some(("library_movie", &["release_year"], vec![release_year]))
+        }
+        ItemDetails::Series {
+            season, episodes, ..
+        } => Some((
+            "library_series",
+            &["season", "episodes"],
+            vec![season, episodes],
+        )),
+        ItemDetails::Audiobook {
+            narrator,
+            duration_minutes,
+            ..
+        } => Some((
+            "library_audiobook",
+            &["narrator", "duration_minutes"],
+            vec![narrator, duration_minutes],
+        )),
+        ItemDetails::BoardGame {
+            min_players,
+            max_players,
+            ..
+        } => Some((
+            "library_board_game",
+            &["min_players", "max_players"],
+            vec![min_players, max_players],
+        )),
+    };
+
+    for table in EXTENSION_TABLES {
+        if extension
+            .as_ref()
+            .is_none_or(|(current, _, _)| current != table)
+        {
+            tx.execute(
+                &format!("DELETE FROM {} WHERE item_id = ?", table),
+                [item_id],
+            )?;
+        }
+    }
 
-    params.push(&item.user_id);
-    conditions.push("user_id = ?");
+    let Some((table, columns, values)) = extension else {
+        return Ok(());
+    };
+    let updates: Vec<String> = columns
+        .iter()
+        .map(|column| format!("{0} = COALESCE(excluded.{0}, {0})", column))
+        .collect();
+    let sql = format!(
+        "INSERT INTO {} (item_id, {}) VALUES (?{})
+            ON CONFLICT (item_id) DO UPDATE SET {}",
+        table,
+        columns.join(", "),
+        ", ?".repeat(columns.len()),
+        updates.join(", ")
+    );
+    let mut params: Vec<&dyn rusqlite::ToSql> = vec![&item_id];
+    params.extend(values);
+    tx.execute(&sql, params.as_slice())?;
+    Ok(())
+}
+
+fn sort_column(filter: &LibraryFilter) -> (&'static str, &'static str) {
+    let column = match filter.sort.field {
+        LibrarySortField::Title => "l.title COLLATE FINNISH",
+        LibrarySortField::Author => "l.author COLLATE FINNISH",
+        // Items without a completion date sort as empty strings so that the
+        // cursor comparison also works for them
+        LibrarySortField::CompletedAt => "COALESCE(l.completed_at, '')",
+        LibrarySortField::AddedAt => "l.added_at",
+    };
+    let direction = match filter.sort.direction {
+        SortDirection::Asc => "ASC",
+        SortDirection::Desc => "DESC",
+    };
+    (column, direction)
+}
+
+fn to_sql_params(item: &LibraryFilter) -> (String, Vec<Value>) {
+    let mut params: Vec<Value> = Vec::new();
+    let mut conditions: Vec<String> = Vec::new();
+
+    params.push(Value::Text(item.user_id.clone()));
+    conditions.push("user_id = ?".to_string());
+
+    // Rows of unknown kinds are skipped when decoded. Leaving them out here
+    // keeps the limit, and so the next page cursor, counting returned items.
+    params.extend(
+        ItemDetails::KINDS
+            .iter()
+            .map(|kind| Value::Text(kind.to_string())),
+    );
+    conditions.push(format!(
+        "l.kind IN ({})",
+        vec!["?"; ItemDetails::KINDS.len()].join(", ")
+    ));
 
     if let Some(item_id) = &item.item_id {
-        params.push(item_id);
-        conditions.push("l.id = ?");
+        params.push(Value::Text(item_id.clone()));
+        conditions.push("l.id = ?".to_string());
+    }
+
+    if let Some(kind) = &item.kind {
+        params.push(Value::Text(kind.clone()));
+        conditions.push("l.kind = ?".to_string());
+    }
+
+    if let Some(status) = &item.status {
+        params.push(Value::Text(status.as_str().to_string()));
+        conditions.push("l.status = ?".to_string());
+    }
+
+    // An item matches a date range when any of its completions does
+    if item.completed_after.is_some() || item.completed_before.is_some() {
+        let mut completion_conditions = vec!["c.item_id = l.id"];
+        if let Some(after) = &item.completed_after {
+            params.push(Value::Text(after.clone()));
+            completion_conditions.push("c.completed_at >= ?");
+        }
+        if let Some(before) = &item.completed_before {
+            params.push(Value::Text(before.clone()));
+            completion_conditions.push("c.completed_at < ?");
+        }
+        conditions.push(format!(
+            "EXISTS (SELECT 1 FROM completion c WHERE {})",
+            completion_conditions.join(" AND ")
+        ));
+    }
+
+    if let Some(favorite) = item.favorite {
+        params.push(Value::Integer(if favorite { 1 } else { 0 }));
+        conditions.push("l.favorite = ?".to_string());
+    }
+
+    if let Some(challenge_id) = &item.challenge_id {
+        params.push(Value::Text(challenge_id.clone()));
+        conditions.push(
+            "EXISTS (SELECT 1 FROM activated_item_challenge f 
+                WHERE f.item_id = l.id AND f.challenge_id = ?)"
+                .to_string(),
+        );
+    }
+
+    if let Some(language) = &item.language {
+        params.push(Value::Text(language.clone()));
+        conditions.push("l.language = ?".to_string());
+    }
+
+    if let Some(language) = &item.original_language {
+        params.push(Value::Text(language.clone()));
+        conditions.push("l.original_language = ?".to_string());
+    }
+
+    if let Some(tag_id) = &item.tag_id {
+        params.push(Value::Text(tag_id.clone()));
+        conditions.push(
+            "EXISTS (SELECT 1 FROM item_tag t WHERE t.item_id = l.id AND t.tag_id = ?)".to_string(),
+        );
+    }
+
+    if let Some(contributor_id) = &item.contributor_id {
+        params.push(Value::Text(contributor_id.clone()));
+        conditions.push(
+            "EXISTS (SELECT 1 FROM item_contributor ic 
+                WHERE ic.item_id = l.id AND ic.contributor_id = ?)"
+                .to_string(),
+        );
+    }
+
+    if let Some(cursor) = &item.cursor {
+        let (column, _) = sort_column(item);
+        let comparison = match item.sort.direction {
+            SortDirection::Asc => ">",
+            SortDirection::Desc => "<",
+        };
+        params.push(Value::Text(cursor.value.clone()));
+        params.push(Value::Text(cursor.value.clone()));
+        params.push(Value::Text(cursor.id.clone()));
+        conditions.push(format!(
+            "({column} {comparison} ? OR ({column} = ? AND l.id {comparison} ?))"
+        ));
     }
 
     let conditions = conditions.join(" AND ");
 
     (conditions, params)
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::database::{Database, migrated_test_database};
+    use crate::library::LibrarySort;
+
+    pub fn book(id: &str, user_id: &str, title: &str, author: &str) -> LibraryItem {
+        LibraryItem {
+            id: id.to_string(),
+            user_id: user_id.to_string(),
+            title: title.to_string(),
+            added_at: "2024-01-01T00:00:00Z".to_string(),
+            status: ItemStatus::Planned,
+            started_at: None,
+            completed_at: None,
+            completion_count: 0,
+            isbn: None,
+            page_count: None,
+            expected_hours: None,
+            language: None,
+            original_language: None,
+            favorite: false,
+            rating: None,
+            review: None,
+            review_html: None,
+            notes: None,
+            activated_challenge_ids: Vec::new(),
+            tag_ids: Vec::new(),
+            cover_id: None,
+            details: ItemDetails::Book {
+                author: author.to_string(),
+                translator: None,
+            },
+        }
+    }
+
+    pub fn completed_book(id: &str, user_id: &str, completed_at: &str) -> LibraryItem {
+        LibraryItem {
+            status: ItemStatus::Completed,
+            completed_at: Some(completed_at.to_string()),
+            ..book(id, user_id, id, "Author")
+        }
+    }
+
+    pub fn repository(dir: &tempfile::TempDir) -> LibraryRepository {
+        LibraryRepository::new(Database::new(&migrated_test_database(dir)).unwrap())
+    }
+
+    #[test]
+    fn update_keeps_fields_left_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut repo = repository(&dir);
+        let item = LibraryItem {
+            rating: Some(4.5),
+            review: Some("Review".to_string()),
+            notes: Some("Notes".to_string()),
+            isbn: Some("9789510096048".to_string()),
+            page_count: Some(480),
+            expected_hours: Some(12.0),
+            language: Some("fi".to_string()),
+            original_language: Some("sv".to_string()),
+            ..book("a", "user", "Tuntematon sotilas", "Väinö Linna")
+        };
+        repo.create(&item).unwrap();
+
+        // What the favorite toggle of the library listing sends
+        let toggled = LibraryItem {
+            favorite: true,
+            ..book("a", "user", "Tuntematon sotilas", "Väinö Linna")
+        };
+        assert!(repo.update("a", &toggled).unwrap());
+
+        let stored = repo.read_by_id("a").unwrap().unwrap();
+        assert!(stored.favorite);
+        assert_eq!(stored.rating, item.rating);
+        assert_eq!(stored.review, item.review);
+        assert_eq!(stored.notes, item.notes);
+        assert_eq!(stored.isbn, item.isbn);
+        assert_eq!(stored.page_count, item.page_count);
+        assert_eq!(stored.expected_hours, item.expected_hours);
+        assert_eq!(stored.language, item.language);
+        assert_eq!(stored.original_language, item.original_language);
+    }
+
+    #[test]
+    fn update_keeps_extension_fields_left_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut repo = repository(&dir);
+        let game = |platform: Option<&str>, playtime_minutes: Option<i64>| LibraryItem {
+            details: ItemDetails::Game {
+                creator: "Remedy".to_string(),
+                platform: platform.map(str::to_string),
+                playtime_minutes,
+            },
+            ..book("g", "user", "Alan Wake", "Remedy")
+        };
+        repo.create(&game(Some("PC"), Some(900))).unwrap();
+
+        repo.update("g", &game(None, None)).unwrap();
+        assert_eq!(
+            repo.read_by_id("g").unwrap().unwrap().details,
+            game(Some("PC"), Some(900)).details
+        );
+
+        repo.update("g", &game(None, Some(1200))).unwrap();
+        assert_eq!(
+            repo.read_by_id("g").unwrap().unwrap().details,
+            game(Some("PC"), Some(1200)).details
+        );
+
+        // Changing the kind drops the extension of the old kind
+        repo.update("g", &book("g", "user", "Alan Wake", "Remedy"))
+            .unwrap();
+        repo.update("g", &game(None, None)).unwrap();
+        assert_eq!(
+            repo.read_by_id("g").unwrap().unwrap().details,
+            game(None, None).details
+        );
+    }
+
+    fn search_ids(repo: &mut LibraryRepository, filter: LibraryFilter) -> Vec<String> {
+        repo.search(filter)
+            .unwrap()
+            .into_iter()
+            .map(|item| item.id)
+            .collect()
+    }
+
+    #[test]
+    fn search_filters_and_sorts() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut repo = repository(&dir);
+        repo.create(&LibraryItem {
+            favorite: true,
+            language: Some("fi".to_string()),
+            ..completed_book("a", "me", "2023-06-01")
+        })
+        .unwrap();
+        repo.create_completion(&Completion {
+            id: "reread".to_string(),
+            item_id: "a".to_string(),
+            completed_at: "2025-02-01".to_string(),
+            note: None,
+        })
+        .unwrap();
+        repo.create(&completed_book("b", "me", "2024-06-01"))
+            .unwrap();
+        repo.create(&LibraryItem {
+            details: ItemDetails::Movie {
+                director: "Aki Kaurismäki".to_string(),
+                release_year: None,
+            },
+            ..book("c", "me", "Kuolleet lehdet", "Aki Kaurismäki")
+        })
+        .unwrap();
+        repo.create(&completed_book("d", "other", "2024-06-01"))
+            .unwrap();
+        let tag_id = crate::tags::ensure_tag(repo.conn(), "me", "kesä").unwrap();
+        repo.conn()
+            .execute(
+                "INSERT INTO item_tag (item_id, tag_id) VALUES ('b', ?)",
+                [&tag_id],
+            )
+            .unwrap();
+
+        let me = || LibraryFilter::new("me");
+        // Latest completion first, items without one last
+        assert_eq!(search_ids(&mut repo, me()), ["a", "b", "c"]);
+        assert_eq!(
+            search_ids(
+                &mut repo,
+                LibraryFilter {
+                    kind: Some("Movie".to_string()),
+                    ..me()
+                }
+            ),
+            ["c"]
+        );
+        assert_eq!(
+            search_ids(
+                &mut repo,
+                LibraryFilter {
+                    status: Some(ItemStatus::Planned),
+                    ..me()
+                }
+            ),
+            ["c"]
+        );
+        assert_eq!(
+            search_ids(
+                &mut repo,
+                LibraryFilter {
+                    favorite: Some(true),
+                    language: Some("fi".to_string()),
+                    ..me()
+                }
+            ),
+            ["a"]
+        );
+        // Any completion in the range matches, also earlier reads
+        assert_eq!(
+            search_ids(
+                &mut repo,
+                LibraryFilter {
+                    completed_after: Some("2023-01-01".to_string()),
+                    completed_before: Some("2024-01-01".to_string()),
+                    ..me()
+                }
+            ),
+            ["a"]
+        );
+        assert_eq!(
+            search_ids(
+                &mut repo,
+                LibraryFilter {
+                    tag_id: Some(tag_id),
+                    ..me()
+                }
+            ),
+            ["b"]
+        );
+        assert_eq!(
+            search_ids(
+                &mut repo,
+                LibraryFilter {
+                    sort: LibrarySort {
+                        field: LibrarySortField::Title,
+                        direction: SortDirection::Desc,
+                    },
+                    ..me()
+                }
+            ),
+            ["c", "b", "a"]
+        );
+    }
+
+    fn search_authors(repo: &mut LibraryRepository, query: &str) -> Vec<(String, String)> {
+        repo.full_text_search("user", query, 10)
+            .unwrap()
+            .into_iter()
+            .map(|hit| (hit.item.id, hit.snippets.author))
+            .collect()
+    }
+
+    #[test]
+    fn full_text_search_follows_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut repo = repository(&dir);
+
+        repo.create(&book("a", "user", "Tuntematon sotilas", "Väinö Linna"))
+            .unwrap();
+        repo.create(&book(
+            "b",
+            "other",
+            "Täällä Pohjantähden alla",
+            "Väinö Linna",
+        ))
+        .unwrap();
+        assert_eq!(
+            search_authors(&mut repo, "Vaino"),
+            vec![("a".to_string(), "<mark>Väinö</mark> Linna".to_string())]
+        );
+
+        repo.update(
+            "a",
+            &book("a", "user", "Tuntematon sotilas", "Linna, Väinö"),
+        )
+        .unwrap();
+        assert_eq!(
+            search_authors(&mut repo, "vain"),
+            vec![("a".to_string(), "Linna, <mark>Väinö</mark>".to_string())]
+        );
+
+        repo.update(
+            "a",
+            &book("a", "user", "Tuntematon sotilas", "Aleksis Kivi"),
+        )
+        .unwrap();
+        assert!(search_authors(&mut repo, "Vaino").is_empty());
+        assert_eq!(search_authors(&mut repo, "Kivi").len(), 1);
+
+        repo.delete("a").unwrap();
+        assert!(search_authors(&mut repo, "Kivi").is_empty());
+    }
+
+    #[test]
+    fn fts_query_quotes_words_as_prefixes() {
+        assert_eq!(
+            to_fts_query("Väinö  linna\"*"),
+            Some("\"Väinö\"* \"linna\"*".to_string())
+        );
+        assert_eq!(
+            to_fts_query("Jean-Paul K-19"),
+            Some("\"Jean\"* \"Paul\"* \"K\"* \"19\"*".to_string())
+        );
+        assert_eq!(to_fts_query(" \" - "), None);
+    }
+
+    #[test]
+    fn snippets_are_escaped_before_marking() {
+        let snippet = format!("<b>{}Tuntematon{} & sotilas", MATCH_START, MATCH_END);
+        assert_eq!(
+            escape_snippet(&snippet),
+            "&lt;b&gt;<mark>Tuntematon</mark> &amp; sotilas"
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use rusqlite::Result;
+use serde::Serialize;
+
+use crate::{
+    database::{Repository, query_in_transation},
+    library::LibraryRepository,
+};
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RatingCount {
+    pub rating: f64,
+    pub count: i64,
+}
+
+/// Ratings of the items completed during a year
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct YearlyRatings {
+    pub year: i32,
+    pub count: i64,
+    pub average: f64,
+    pub distribution: Vec<RatingCount>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageCount {
+    pub language: String,
+    pub count: i64,
+}
+
+/// Completed books by the language they were read in and the language they
+/// were written in. Books without a recorded language are left out.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageStatistics {
+    pub languages: Vec<LanguageCount>,
+    pub original_languages: Vec<LanguageCount>,
+    /// Books read in another language than they were written in
+    pub translated_count: i64,
+}
+
+impl LibraryRepository {
+    pub fn rating_distribution(
+        &mut self,
+        user_id: &str,
+        kind: Option<&str>,
+    ) -> Result<Vec<YearlyRatings>> {
+        let sql = "SELECT CAST(substr(completed_at, 1, 4) AS INTEGER) as year, rating, COUNT(*)
+            FROM library
+            WHERE user_id = ?1 AND rating IS NOT NULL AND completed_at IS NOT NULL
+                AND (?2 IS NULL OR kind = ?2)
+            GROUP BY year, rating
+            ORDER BY year, rating";
+
+        let tx = self.transaction()?;
+        let rows = query_in_transation(&tx, sql, rusqlite::params![user_id, kind], |row| {
+            Ok((
+                row.get::<_, i32>(0)?,
+                RatingCount {
+                    rating: row.get(1)?,
+                    count: row.get(2)?,
+                },
+            ))
+        })?;
+        tx.commit()?;
+
+        let mut years: Vec<YearlyRatings> = Vec::new();
+        for (year, rating) in rows {
+            let entry = match years.last_mut() {
+                Some(last) if last.year == year => last,
+                _ => {
+                    years.push(YearlyRatings {
+                        year,
+                        count: 0,
+                        average: 0.0,
+                        distribution: Vec::new(),
+                    });
+                    years.last_mut().expect("pushed above")
+                }
+            };
+            entry.count += rating.count;
+            entry.distribution.push(rating);
+        }
+
+        for year in &mut years {
+            let total: f64 = year
+                .distribution
+                .iter()
+                .map(|r| r.rating * r.count as f64)
+                .sum();
+            year.average = total / year.count as f64;
+        }
+
+        Ok(years)
+    }
+
+    /// Counts the completed books, optionally only those completed during
+    /// the year
+    pub fn language_statistics(
+        &mut self,
+        user_id: &str,
+        year: Option<i32>,
+    ) -> Result<LanguageStatistics> {
+        let (after, before) = match year {
+            Some(year) => (
+                Some(format!("{:04}-01-01", year)),
+                Some(format!("{:04}-01-01", year + 1)),
+            ),
+            None => (None, None),
+        };
+        let items = "SELECT l.language, l.original_language FROM library l
+            WHERE l.user_id = ?1 AND l.status = 'completed' AND l.kind IN ('Book', 'Audiobook')
+                AND (?2 IS NULL OR EXISTS (SELECT 1 FROM completion c
+                    WHERE c.item_id = l.id AND c.completed_at >= ?2 AND c.completed_at < ?3))";
+        let params = rusqlite::params![user_id, after, before];
+
+        let tx = self.transaction()?;
+        let count_by = |column: &str| {
+            let sql = format!(
+                "SELECT {column}, COUNT(*) as count FROM ({items})
+                    WHERE {column} IS NOT NULL
+                    GROUP BY {column}
+                    ORDER BY count DESC, {column}"
+            );
+            query_in_transation(&tx, &sql, params, |row| {
+                Ok(LanguageCount {
+                    language: row.get(0)?,
+                    count: row.get(1)?,
+                })
+            })
+        };
+        let languages = count_by("language")?;
+        let original_languages = count_by("original_language")?;
+        let translated_count = tx.query_row(
+            &format!(
+                "SELECT COUNT(*) FROM ({items})
+                    WHERE language IS NOT NULL AND original_language IS NOT NULL
+                        AND language != original_language"
+            ),
+            params,
+            |row| row.get(0),
+        )?;
+        tx.commit()?;
+
+        Ok(LanguageStatistics {
+            languages,
+            original_languages,
+            translated_count,
+        })
+    }
+}
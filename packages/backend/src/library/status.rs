@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+
+/// Where the user is with a library item
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum ItemStatus {
+    Planned,
+    InProgress,
+    #[default]
+    Completed,
+    Abandoned,
+}
+
+impl ItemStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ItemStatus::Planned => "planned",
+            ItemStatus::InProgress => "inProgress",
+            ItemStatus::Completed => "completed",
+            ItemStatus::Abandoned => "abandoned",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "planned" => Some(ItemStatus::Planned),
+            "inProgress" => Some(ItemStatus::InProgress),
+            "completed" => Some(ItemStatus::Completed),
+            "abandoned" => Some(ItemStatus::Abandoned),
+            _ => None,
+        }
+    }
+
+    /// Only finished items are activated for challenges and can answer
+    /// challenge questions
+    pub fn counts_toward_challenges(&self) -> bool {
+        matches!(self, ItemStatus::Completed)
+    }
+
+    /// Status of an item saved without one. Older clients only log finished
+    /// items and always send a completion date.
+    pub fn infer(completed_at: Option<&str>) -> Self {
+        if completed_at.is_some() {
+            ItemStatus::Completed
+        } else {
+            ItemStatus::Planned
+        }
+    }
+
+    /// Returns the `(started_at, completed_at)` pair to store for the status.
+    /// A completion date is only kept for completed items and missing dates
+    /// default to `now` when the status implies one.
+    pub fn resolve_dates(
+        &self,
+        started_at: Option<String>,
+        completed_at: Option<String>,
+        now: &str,
+    ) -> (Option<String>, Option<String>) {
+        match self {
+            ItemStatus::Planned => (started_at, None),
+            ItemStatus::InProgress => (started_at.or_else(|| Some(now.to_string())), None),
+            ItemStatus::Completed => (started_at, completed_at.or_else(|| Some(now.to_string()))),
+            ItemStatus::Abandoned => (started_at, None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_dates_for_status() {
+        let now = "2026-01-01T00:00:00+00:00";
+        let date = Some("2025-05-05".to_string());
+
+        assert_eq!(
+            ItemStatus::InProgress.resolve_dates(None, date.clone(), now),
+            (Some(now.to_string()), None)
+        );
+        assert_eq!(
+            ItemStatus::Completed.resolve_dates(None, date.clone(), now),
+            (None, date)
+        );
+        assert_eq!(
+            ItemStatus::Completed.resolve_dates(None, None, now),
+            (None, Some(now.to_string()))
+        );
+    }
+}
@@ -1,21 +1,52 @@
+use std::sync::Arc;
+
 use axum::{Router, http::HeaderName, routing::get};
 use tower_http::cors::{Any, CorsLayer};
 
 mod auth;
 mod challenge;
 mod challenge_answers;
+mod collation;
+mod contributors;
+mod covers;
 mod database;
+mod export;
+mod goals;
+mod import;
+mod languages;
 mod library;
+mod markdown;
+mod metadata;
 mod migrations;
 mod preferences;
+mod series;
 mod solution;
+mod tags;
 mod utils;
+mod validation;
 
 #[derive(Clone)]
 struct AppState {
     jwks: jsonwebtoken::jwk::JwkSet,
     required_audience: String,
     database_path: String,
+    /// Uploaded files such as cover images are stored here
+    data_dir: std::path::PathBuf,
+    metadata: Arc<dyn metadata::MetadataProvider>,
+}
+
+#[cfg(test)]
+impl AppState {
+    /// State over a test database, without signing keys or known books
+    fn for_tests(database_path: &str) -> Self {
+        AppState {
+            jwks: jsonwebtoken::jwk::JwkSet { keys: vec![] },
+            required_audience: String::new(),
+            database_path: database_path.to_string(),
+            data_dir: std::env::temp_dir(),
+            metadata: Arc::new(metadata::FixtureProvider::new(Default::default())),
+        }
+    }
 }
 
 #[tokio::main]
@@ -38,11 +69,21 @@ async fn main() {
         .expect("Failed to create migrator");
     migrator.run_migrations().expect("Failed to run migrations");
 
+    let data_dir = std::env::var("DATA_DIR")
+        .unwrap_or_else(|_| "data".to_string())
+        .into();
+
+    let metadata = metadata::provider_from_env().expect("Failed to create metadata provider");
+
     let app_state = AppState {
         jwks,
         required_audience,
         database_path,
+        data_dir,
+        metadata,
     };
+    // Files of covers deleted while the server was down
+    covers::purge_deleted_covers(&app_state);
 
     let cors = CorsLayer::new()
         .allow_origin(Any)
@@ -50,7 +91,8 @@ async fn main() {
         .allow_headers([
             HeaderName::from_static("authorization"),
             HeaderName::from_static("content-type"),
-        ]);
+        ])
+        .expose_headers([HeaderName::from_static("x-next-cursor")]);
 
     let app = Router::new()
         .route("/api/ping", get(ping))
@@ -59,6 +101,15 @@ async fn main() {
         .nest("/api", solution::routes())
         .nest("/api", challenge_answers::routes())
         .nest("/api", preferences::routes())
+        .nest("/api", tags::routes())
+        .nest("/api", contributors::routes())
+        .nest("/api", series::routes())
+        .nest("/api", goals::routes())
+        .nest("/api", covers::routes())
+        .nest("/api", languages::routes())
+        .nest("/api", metadata::routes())
+        .nest("/api", import::routes())
+        .nest("/api", export::routes())
         .with_state(app_state)
         .layer(cors);
 
@@ -69,5 +120,5 @@ async fn main() {
 }
 
 async fn ping() -> String {
-    format!("PONG")
+    "PONG".to_string()
 }
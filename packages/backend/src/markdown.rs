@@ -0,0 +1,26 @@
+use pulldown_cmark::{Options, Parser, html};
+
+/// Renders user written markdown to HTML that is safe to show as is. Raw HTML
+/// in the markdown is passed to the sanitizer, which drops scripts, event
+/// handlers and anything else outside its allowlist.
+pub fn render_sanitized(markdown: &str) -> String {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    let parser = Parser::new_ext(markdown, options);
+
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, parser);
+
+    ammonia::clean(&unsafe_html)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_scripts_but_keeps_formatting() {
+        let html = render_sanitized("**Hyvä** kirja<script>alert(1)</script>");
+        assert_eq!(html, "<p><strong>Hyvä</strong> kirja</p>\n");
+    }
+}
@@ -0,0 +1,41 @@
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    routing::get,
+};
+
+use crate::{
+    AppState,
+    auth::User,
+    metadata::{BookMetadata, MetadataError, domain::lookup_metadata, isbn},
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/metadata/isbn/{isbn}", get(lookup_isbn_route))
+}
+
+async fn lookup_isbn_route(
+    _user: User,
+    state: State<AppState>,
+    Path(value): Path<String>,
+) -> Result<Json<BookMetadata>, StatusCode> {
+    let isbn = isbn::normalize(&value).ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
+
+    match lookup_metadata(&state, &isbn)
+        .await
+        .map_err(map_metadata_error)?
+    {
+        Some(metadata) => Ok(Json(metadata)),
+        None => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+fn map_metadata_error(err: MetadataError) -> StatusCode {
+    println!("Metadata error: {}", err);
+    if err.to_string().contains("provider failed") {
+        StatusCode::BAD_GATEWAY
+    } else {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
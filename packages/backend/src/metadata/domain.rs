@@ -0,0 +1,82 @@
+use crate::{
+    AppState,
+    database::Database,
+    metadata::{BookMetadata, MetadataError, repository::MetadataCacheRepository},
+};
+
+/// Looks up a normalized ISBN-13, serving earlier results from the cache.
+/// Books the provider doesn't know are not cached.
+pub async fn lookup_metadata(
+    state: &AppState,
+    isbn: &str,
+) -> Result<Option<BookMetadata>, MetadataError> {
+    let provider = state.metadata.clone();
+
+    if let Some(cached) = cache(state)?.get(provider.name(), isbn)? {
+        return Ok(Some(cached));
+    }
+
+    let metadata = provider
+        .lookup(isbn)
+        .await
+        .map_err(|e| format!("Metadata provider failed: {}", e))?;
+
+    if let Some(metadata) = &metadata {
+        let now = chrono::Utc::now().to_rfc3339();
+        cache(state)?.put(provider.name(), metadata, &now)?;
+    }
+
+    Ok(metadata)
+}
+
+fn cache(state: &AppState) -> Result<MetadataCacheRepository, MetadataError> {
+    let db = Database::new(&state.database_path)?;
+    Ok(MetadataCacheRepository::new(db))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Arc};
+
+    use super::*;
+    use crate::{database::migrated_test_database, metadata::FixtureProvider};
+
+    fn state(database_path: &str, books: HashMap<String, BookMetadata>) -> AppState {
+        AppState {
+            metadata: Arc::new(FixtureProvider::new(books)),
+            ..AppState::for_tests(database_path)
+        }
+    }
+
+    #[tokio::test]
+    async fn caches_books_found_by_the_provider() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = migrated_test_database(&dir);
+
+        let book = BookMetadata {
+            isbn: String::new(),
+            title: "Tuntematon sotilas".to_string(),
+            author: Some("Väinö Linna".to_string()),
+            translator: None,
+            page_count: Some(480),
+            publication_year: Some(1954),
+        };
+        let isbn = "9789510096048";
+        let found = lookup_metadata(
+            &state(&db_path, HashMap::from([(isbn.to_string(), book)])),
+            isbn,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(found.isbn, isbn);
+
+        // The same provider without the book still serves it from the cache
+        let empty = state(&db_path, HashMap::new());
+        assert_eq!(lookup_metadata(&empty, isbn).await.unwrap(), Some(found));
+        assert_eq!(
+            lookup_metadata(&empty, "9789510000007").await.unwrap(),
+            None
+        );
+    }
+}
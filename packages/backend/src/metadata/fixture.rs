@@ -0,0 +1,42 @@
+use std::collections::HashMap;
+
+use crate::metadata::{BookMetadata, BoxFuture, MetadataError, MetadataProvider, isbn};
+
+/// Serves metadata from a JSON object keyed by ISBN, for offline use and
+/// tests
+pub struct FixtureProvider {
+    books: HashMap<String, BookMetadata>,
+}
+
+impl FixtureProvider {
+    pub fn new(books: HashMap<String, BookMetadata>) -> Self {
+        let books = books
+            .into_iter()
+            .filter_map(|(key, mut book)| {
+                let normalized = isbn::normalize(&key)?;
+                book.isbn = normalized.clone();
+                Some((normalized, book))
+            })
+            .collect();
+        FixtureProvider { books }
+    }
+
+    pub fn from_file(path: &str) -> Result<Self, MetadataError> {
+        let contents = std::fs::read_to_string(path)?;
+        let books: HashMap<String, BookMetadata> = serde_json::from_str(&contents)?;
+        Ok(FixtureProvider::new(books))
+    }
+}
+
+impl MetadataProvider for FixtureProvider {
+    fn name(&self) -> &'static str {
+        "fixture"
+    }
+
+    fn lookup<'a>(
+        &'a self,
+        isbn: &'a str,
+    ) -> BoxFuture<'a, Result<Option<BookMetadata>, MetadataError>> {
+        Box::pin(async move { Ok(self.books.get(isbn).cloned()) })
+    }
+}
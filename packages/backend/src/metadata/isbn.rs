@@ -0,0 +1,78 @@
+/// Validates an ISBN-10 or ISBN-13 and returns it as a bare ISBN-13.
+/// Hyphens and spaces are ignored, as is the case of a trailing `X`.
+pub fn normalize(value: &str) -> Option<String> {
+    let compact: String = value
+        .chars()
+        .filter(|c| !matches!(c, '-' | ' '))
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+
+    match compact.len() {
+        10 if is_valid_isbn10(&compact) => Some(isbn10_to_isbn13(&compact)),
+        13 if is_valid_isbn13(&compact) => Some(compact),
+        _ => None,
+    }
+}
+
+fn is_valid_isbn10(isbn: &str) -> bool {
+    let mut sum = 0;
+    for (i, c) in isbn.chars().enumerate() {
+        let digit = match c {
+            'X' if i == 9 => 10,
+            c => match c.to_digit(10) {
+                Some(d) => d,
+                None => return false,
+            },
+        };
+        sum += digit * (10 - i as u32);
+    }
+    sum.is_multiple_of(11)
+}
+
+fn is_valid_isbn13(isbn: &str) -> bool {
+    if !isbn.chars().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+    if !isbn.starts_with("978") && !isbn.starts_with("979") {
+        return false;
+    }
+    isbn13_sum(isbn).is_multiple_of(10)
+}
+
+fn isbn13_sum(digits: &str) -> u32 {
+    digits
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .enumerate()
+        .map(|(i, d)| if i % 2 == 0 { d } else { d * 3 })
+        .sum()
+}
+
+fn isbn10_to_isbn13(isbn: &str) -> String {
+    let body = format!("978{}", &isbn[..9]);
+    let check = (10 - isbn13_sum(&body) % 10) % 10;
+    format!("{}{}", body, check)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_isbn10_to_isbn13() {
+        assert_eq!(normalize("951-0-11111-X"), None);
+        assert_eq!(normalize("0-306-40615-2").as_deref(), Some("9780306406157"));
+        assert_eq!(normalize("080442957x").as_deref(), Some("9780804429573"));
+    }
+
+    #[test]
+    fn validates_isbn13_checksum() {
+        assert_eq!(
+            normalize("978-951-0-41249-7").as_deref(),
+            Some("9789510412497")
+        );
+        assert_eq!(normalize("9789510412490"), None);
+        assert_eq!(normalize("1234567890123"), None);
+        assert_eq!(normalize("abc"), None);
+    }
+}
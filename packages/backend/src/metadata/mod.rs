@@ -0,0 +1,58 @@
+use std::{future::Future, pin::Pin, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+
+mod api;
+mod domain;
+mod fixture;
+pub mod isbn;
+mod open_library;
+mod repository;
+
+pub use fixture::FixtureProvider;
+pub use open_library::OpenLibraryProvider;
+
+/// Prefilled fields for a new library item
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookMetadata {
+    #[serde(default)]
+    pub isbn: String,
+    pub title: String,
+    pub author: Option<String>,
+    pub translator: Option<String>,
+    pub page_count: Option<i64>,
+    pub publication_year: Option<i32>,
+}
+
+pub type MetadataError = Box<dyn std::error::Error + Send + Sync>;
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Source of book metadata. Lookups receive a normalized ISBN-13 and
+/// resolve to `None` when the provider doesn't know the book.
+pub trait MetadataProvider: Send + Sync {
+    /// Key of the provider in the lookup cache
+    fn name(&self) -> &'static str;
+
+    fn lookup<'a>(
+        &'a self,
+        isbn: &'a str,
+    ) -> BoxFuture<'a, Result<Option<BookMetadata>, MetadataError>>;
+}
+
+/// Selects the provider with `METADATA_PROVIDER`: `openlibrary` (default)
+/// or `fixture`, which reads `METADATA_FIXTURE_PATH`.
+pub fn provider_from_env() -> Result<Arc<dyn MetadataProvider>, MetadataError> {
+    let provider = std::env::var("METADATA_PROVIDER").unwrap_or_else(|_| "openlibrary".into());
+    match provider.as_str() {
+        "openlibrary" => Ok(Arc::new(OpenLibraryProvider::new()?)),
+        "fixture" => {
+            let path = std::env::var("METADATA_FIXTURE_PATH")
+                .map_err(|_| "METADATA_FIXTURE_PATH must be set for the fixture provider")?;
+            Ok(Arc::new(FixtureProvider::from_file(&path)?))
+        }
+        other => Err(format!("Unknown metadata provider: {}", other).into()),
+    }
+}
+
+pub use api::routes;
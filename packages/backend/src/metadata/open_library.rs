@@ -0,0 +1,152 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::metadata::{BookMetadata, BoxFuture, MetadataError, MetadataProvider};
+
+const OPEN_LIBRARY_URL: &str = "https://openlibrary.org";
+/// Lookups run inside request handlers, so a slow Open Library must not
+/// hold them for long
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Looks books up from the Open Library books API
+pub struct OpenLibraryProvider {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+#[derive(Deserialize)]
+struct BookResponse {
+    details: Edition,
+}
+
+#[derive(Deserialize)]
+struct Edition {
+    title: String,
+    #[serde(default)]
+    authors: Vec<Person>,
+    #[serde(default)]
+    contributors: Vec<Contributor>,
+    /// Free-form credits such as "Translated by ..."
+    #[serde(default)]
+    contributions: Vec<String>,
+    number_of_pages: Option<i64>,
+    publish_date: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Person {
+    name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Contributor {
+    role: Option<String>,
+    name: Option<String>,
+}
+
+impl OpenLibraryProvider {
+    pub fn new() -> Result<Self, MetadataError> {
+        let client = reqwest::Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .timeout(REQUEST_TIMEOUT)
+            .build()?;
+        Ok(OpenLibraryProvider {
+            client,
+            base_url: OPEN_LIBRARY_URL.to_string(),
+        })
+    }
+
+    async fn fetch(&self, isbn: &str) -> Result<Option<BookMetadata>, MetadataError> {
+        let key = format!("ISBN:{}", isbn);
+        let url = format!("{}/api/books", self.base_url);
+        let mut response: std::collections::HashMap<String, BookResponse> = self
+            .client
+            .get(url)
+            .query(&[
+                ("bibkeys", key.as_str()),
+                ("format", "json"),
+                ("jscmd", "details"),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(response
+            .remove(&key)
+            .map(|book| to_metadata(isbn, book.details)))
+    }
+}
+
+fn to_metadata(isbn: &str, edition: Edition) -> BookMetadata {
+    let names: Vec<String> = edition.authors.into_iter().filter_map(|a| a.name).collect();
+
+    let translator = edition
+        .contributors
+        .into_iter()
+        .find(|c| c.role.as_deref() == Some("Translator"))
+        .and_then(|c| c.name)
+        .or_else(|| {
+            edition.contributions.iter().find_map(|credit| {
+                credit
+                    .strip_prefix("Translated by ")
+                    .or_else(|| credit.strip_prefix("translated by "))
+                    .map(|name| name.trim().to_string())
+            })
+        });
+
+    BookMetadata {
+        isbn: isbn.to_string(),
+        title: edition.title,
+        author: (!names.is_empty()).then(|| names.join(", ")),
+        translator,
+        page_count: edition.number_of_pages,
+        publication_year: edition.publish_date.as_deref().and_then(parse_year),
+    }
+}
+
+/// Publish dates are free text ("March 2004", "2004-03-01", "c2004")
+fn parse_year(date: &str) -> Option<i32> {
+    date.as_bytes()
+        .windows(4)
+        .find(|window| window.iter().all(|b| b.is_ascii_digit()))
+        .and_then(|year| std::str::from_utf8(year).ok()?.parse().ok())
+}
+
+impl MetadataProvider for OpenLibraryProvider {
+    fn name(&self) -> &'static str {
+        "openlibrary"
+    }
+
+    fn lookup<'a>(
+        &'a self,
+        isbn: &'a str,
+    ) -> BoxFuture<'a, Result<Option<BookMetadata>, MetadataError>> {
+        Box::pin(self.fetch(isbn))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_edition_details() {
+        let response: BookResponse = serde_json::from_str(
+            r#"{"details":{"title":"Seitsemän veljestä","authors":[{"key":"/a/1","name":"Aleksis Kivi"}],
+                "contributions":["Translated by Richard Impola"],"number_of_pages":336,
+                "publish_date":"March 1991"}}"#,
+        )
+        .unwrap();
+
+        let metadata = to_metadata("9780000000000", response.details);
+
+        assert_eq!(metadata.author.as_deref(), Some("Aleksis Kivi"));
+        assert_eq!(metadata.translator.as_deref(), Some("Richard Impola"));
+        assert_eq!(metadata.page_count, Some(336));
+        assert_eq!(metadata.publication_year, Some(1991));
+    }
+}
@@ -0,0 +1,41 @@
+use rusqlite::{OptionalExtension, Result};
+
+use crate::{database::Database, metadata::BookMetadata};
+
+/// Lookup results stored as JSON per provider, so that switching providers
+/// doesn't serve stale data from another source
+pub struct MetadataCacheRepository {
+    db: Database,
+}
+
+impl MetadataCacheRepository {
+    pub fn new(db: Database) -> Self {
+        MetadataCacheRepository { db }
+    }
+
+    pub fn get(&mut self, provider: &str, isbn: &str) -> Result<Option<BookMetadata>> {
+        let data: Option<String> = self
+            .db
+            .conn
+            .query_row(
+                "SELECT data FROM metadata_cache WHERE provider = ? AND isbn = ?",
+                [provider, isbn],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        // An entry that no longer parses is treated as a miss and refetched
+        Ok(data.and_then(|data| serde_json::from_str(&data).ok()))
+    }
+
+    pub fn put(&mut self, provider: &str, metadata: &BookMetadata, fetched_at: &str) -> Result<()> {
+        let data = serde_json::to_string(metadata)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        self.db.conn.execute(
+            "INSERT OR REPLACE INTO metadata_cache (provider, isbn, data, fetched_at) 
+                VALUES (?, ?, ?, ?)",
+            [provider, &metadata.isbn, &data, fetched_at],
+        )?;
+        Ok(())
+    }
+}
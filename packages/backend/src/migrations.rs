@@ -1,12 +1,15 @@
-use rusqlite::Connection;
+use rusqlite::{Connection, OptionalExtension, functions::FunctionFlags};
 use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
 
+use crate::contributors::normalize_contributor_name;
+
 #[derive(Debug)]
 pub enum MigrationError {
     Sqlite(rusqlite::Error),
     Io(io::Error),
+    ForeignKeyViolation(String),
 }
 
 impl std::fmt::Display for MigrationError {
@@ -14,6 +17,9 @@ impl std::fmt::Display for MigrationError {
         match self {
             MigrationError::Sqlite(e) => write!(f, "SQLite error: {}", e),
             MigrationError::Io(e) => write!(f, "IO error: {}", e),
+            MigrationError::ForeignKeyViolation(table) => {
+                write!(f, "Foreign key violation in table {}", table)
+            }
         }
     }
 }
@@ -23,6 +29,7 @@ impl std::error::Error for MigrationError {
         match self {
             MigrationError::Sqlite(e) => Some(e),
             MigrationError::Io(e) => Some(e),
+            MigrationError::ForeignKeyViolation(_) => None,
         }
     }
 }
@@ -83,6 +90,13 @@ pub struct Migrator {
 impl Migrator {
     pub fn new<P: AsRef<Path>>(db_path: P, migrations_path: P) -> Result<Self, MigrationError> {
         let conn = Connection::open(db_path)?;
+        // Lets migrations normalize names the same way as the application
+        conn.create_scalar_function(
+            "normalize_contributor_name",
+            1,
+            FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+            |ctx| Ok(normalize_contributor_name(&ctx.get::<String>(0)?)),
+        )?;
 
         let migrator = Migrator {
             conn,
@@ -128,6 +142,11 @@ impl Migrator {
         // Sort migrations by version
         migrations.sort_by(|a, b| a.version.cmp(&b.version));
 
+        // Table rebuilds drop tables that other tables reference. With foreign
+        // keys enforced the drop would cascade, so rebuilt tables are checked
+        // after the migration instead.
+        self.conn.pragma_update(None, "foreign_keys", false)?;
+
         // Execute migrations in order
         for migration in migrations {
             if self.is_migration_applied(&migration.version)? {
@@ -147,6 +166,10 @@ impl Migrator {
                 // Execute migration SQL
                 tx.execute_batch(&migration.sql)?;
 
+                for table in rebuilt_tables(&migration.sql) {
+                    check_foreign_keys(&tx, &table)?;
+                }
+
                 // Record successful migration
                 tx.execute(
                     "INSERT INTO changelog (version, name, applied_at) VALUES (?, ?, ?)",
@@ -164,10 +187,59 @@ impl Migrator {
             println!("Successfully applied migration {}", migration.version);
         }
 
+        self.conn.pragma_update(None, "foreign_keys", true)?;
+
         Ok(())
     }
 }
 
+/// Tables renamed into place by `ALTER TABLE ... RENAME TO`, which is how
+/// migrations rebuild a table
+fn rebuilt_tables(sql: &str) -> Vec<String> {
+    let words: Vec<String> = sql
+        .split(|c: char| c.is_whitespace() || c == ';')
+        .filter(|word| !word.is_empty())
+        .map(|word| word.trim_matches('"').to_string())
+        .collect();
+    words
+        .windows(6)
+        .filter(|w| {
+            w[0].eq_ignore_ascii_case("ALTER")
+                && w[1].eq_ignore_ascii_case("TABLE")
+                && w[3].eq_ignore_ascii_case("RENAME")
+                && w[4].eq_ignore_ascii_case("TO")
+        })
+        .map(|w| w[5].clone())
+        .collect()
+}
+
+/// Checks the rows of the table and of the tables referencing it. Violations
+/// elsewhere are left alone so that they don't block unrelated migrations.
+fn check_foreign_keys(conn: &Connection, table: &str) -> Result<(), MigrationError> {
+    let mut stmt = conn.prepare(
+        "SELECT m.name FROM sqlite_master m, pragma_foreign_key_list(m.name) f
+            WHERE m.type = 'table' AND f.\"table\" = ?1
+            UNION SELECT ?1",
+    )?;
+    let tables = stmt
+        .query_map([table], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    for table in tables {
+        let violation: Option<String> = conn
+            .query_row(
+                "SELECT \"table\" FROM pragma_foreign_key_check(?)",
+                [&table],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if let Some(table) = violation {
+            return Err(MigrationError::ForeignKeyViolation(table));
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -214,4 +286,52 @@ mod tests {
 
         Ok(())
     }
+
+    fn write_migration(dir: &Path, file_name: &str, sql: &str) -> io::Result<()> {
+        File::create(dir.join(file_name))?.write_all(sql.as_bytes())
+    }
+
+    #[test]
+    fn checks_foreign_keys_of_rebuilt_tables() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = tempdir()?;
+        let db_path = temp_dir.path().join("test.db");
+        let migrations_dir = temp_dir.path().join("migrations");
+        fs::create_dir(&migrations_dir)?;
+
+        // An orphan row left by an old bug does not block unrelated migrations
+        write_migration(
+            &migrations_dir,
+            "V2025100401__create_tables.sql",
+            "CREATE TABLE parent (id TEXT PRIMARY KEY);
+            CREATE TABLE child (parent_id TEXT REFERENCES parent(id));
+            CREATE TABLE owner (id TEXT PRIMARY KEY);
+            CREATE TABLE owned (owner_id TEXT REFERENCES owner(id));
+            INSERT INTO parent (id) VALUES ('a'), ('b');
+            INSERT INTO child (parent_id) VALUES ('a');
+            INSERT INTO owned (owner_id) VALUES ('missing');",
+        )?;
+        write_migration(
+            &migrations_dir,
+            "V2025100402__add_unrelated.sql",
+            "CREATE TABLE unrelated (id TEXT PRIMARY KEY);",
+        )?;
+        Migrator::new(&db_path, &migrations_dir)?.run_migrations()?;
+
+        // Rebuilding the parent without a row still referenced fails
+        write_migration(
+            &migrations_dir,
+            "V2025100403__rebuild_parent.sql",
+            "CREATE TABLE parent_new (id TEXT PRIMARY KEY);
+            INSERT INTO parent_new (id) SELECT id FROM parent WHERE id != 'a';
+            DROP TABLE parent;
+            ALTER TABLE parent_new RENAME TO parent;",
+        )?;
+        let result = Migrator::new(&db_path, &migrations_dir)?.run_migrations();
+        assert!(matches!(
+            result,
+            Err(MigrationError::ForeignKeyViolation(table)) if table == "child"
+        ));
+
+        Ok(())
+    }
 }
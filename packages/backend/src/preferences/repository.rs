@@ -12,13 +12,12 @@ impl PreferencesRepositoryTrait for PreferencesRepository {
         let result: Option<String> = self
             .db
             .conn
-            .query_row(sql, &[&user_id], |row| row.get(0))
+            .query_row(sql, [&user_id], |row| row.get(0))
             .optional()?;
 
         match result {
             Some(json) => {
-                let preferences: UserPreferences =
-                    serde_json::from_str(&json).unwrap_or_default();
+                let preferences: UserPreferences = serde_json::from_str(&json).unwrap_or_default();
                 Ok(Some(preferences))
             }
             None => Ok(None),
@@ -29,7 +28,9 @@ impl PreferencesRepositoryTrait for PreferencesRepository {
         let json = serde_json::to_string(preferences).unwrap_or_else(|_| "{}".to_string());
         let sql = "INSERT INTO user_preferences (user_id, preferences) VALUES (?, ?)
                    ON CONFLICT(user_id) DO UPDATE SET preferences = excluded.preferences";
-        self.db.conn.execute(sql, rusqlite::params![user_id, json])?;
+        self.db
+            .conn
+            .execute(sql, rusqlite::params![user_id, json])?;
         Ok(())
     }
 }
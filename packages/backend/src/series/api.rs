@@ -0,0 +1,117 @@
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{delete, get, post, put},
+};
+use serde::Serialize;
+
+use crate::{
+    AppState,
+    auth::User,
+    series::{
+        NewSeries, NewSeriesEntry, Series, SeriesProgress,
+        domain::{
+            create_series, delete_series, get_series, get_series_progress, remove_series_entry,
+            set_series_entry, update_series,
+        },
+    },
+    utils::map_to_internal_error,
+    validation::{ApiError, ValidJson, map_validation_error},
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/series", get(get_all_series))
+        .route("/series", post(create_new_series))
+        .route("/series/{id}", put(update_existing_series))
+        .route("/series/{id}", delete(delete_existing_series))
+        .route("/series/{id}/progress", get(get_progress))
+        .route("/series/{id}/entries/{itemId}", put(set_entry))
+        .route("/series/{id}/entries/{itemId}", delete(remove_entry))
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct IdResponse {
+    id: String,
+}
+
+fn map_series_error(err: Box<dyn std::error::Error>) -> StatusCode {
+    let message = err.to_string();
+    if message.contains("not found") {
+        StatusCode::NOT_FOUND
+    } else if message.contains("already exists") {
+        StatusCode::CONFLICT
+    } else if message.contains("Invalid") {
+        StatusCode::UNPROCESSABLE_ENTITY
+    } else {
+        map_to_internal_error(err)
+    }
+}
+
+async fn get_all_series(
+    State(state): State<AppState>,
+    user: User,
+) -> Result<Json<Vec<Series>>, StatusCode> {
+    let series = get_series(&user, &state).map_err(map_to_internal_error)?;
+    Ok(Json(series))
+}
+
+async fn create_new_series(
+    State(state): State<AppState>,
+    user: User,
+    ValidJson(series): ValidJson<NewSeries>,
+) -> Result<Json<IdResponse>, ApiError> {
+    let id = create_series(&user, &state, &series)
+        .map_err(|err| map_validation_error(err, map_series_error))?;
+    Ok(Json(IdResponse { id }))
+}
+
+async fn update_existing_series(
+    State(state): State<AppState>,
+    user: User,
+    Path(id): Path<String>,
+    ValidJson(series): ValidJson<NewSeries>,
+) -> Result<StatusCode, ApiError> {
+    update_series(&user, &state, &id, &series)
+        .map_err(|err| map_validation_error(err, map_series_error))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn delete_existing_series(
+    State(state): State<AppState>,
+    user: User,
+    Path(id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    delete_series(&user, &state, &id).map_err(map_series_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_progress(
+    State(state): State<AppState>,
+    user: User,
+    Path(id): Path<String>,
+) -> Result<Json<SeriesProgress>, StatusCode> {
+    let progress = get_series_progress(&user, &state, &id).map_err(map_series_error)?;
+    Ok(Json(progress))
+}
+
+async fn set_entry(
+    State(state): State<AppState>,
+    user: User,
+    Path((id, item_id)): Path<(String, String)>,
+    Json(entry): Json<NewSeriesEntry>,
+) -> Result<StatusCode, StatusCode> {
+    set_series_entry(&user, &state, &id, &item_id, &entry).map_err(map_series_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn remove_entry(
+    State(state): State<AppState>,
+    user: User,
+    Path((id, item_id)): Path<(String, String)>,
+) -> Result<StatusCode, StatusCode> {
+    remove_series_entry(&user, &state, &id, &item_id).map_err(map_series_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,167 @@
+use crate::{
+    AppState,
+    auth::User,
+    database::{Database, Repository},
+    library::LibraryRepository,
+    series::{
+        NewSeries, NewSeriesEntry, Series, SeriesProgress, calculate_series_progress,
+        normalize_series_name,
+        repository::{SeriesFilter, SeriesRepository},
+    },
+};
+
+pub fn get_series(
+    user: &User,
+    state: &AppState,
+) -> Result<Vec<Series>, Box<dyn std::error::Error>> {
+    let db = Database::new(&state.database_path)?;
+    let mut repo = SeriesRepository::new(db);
+    Ok(repo.search(SeriesFilter::new(&user.id))?)
+}
+
+pub fn create_series(
+    user: &User,
+    state: &AppState,
+    series: &NewSeries,
+) -> Result<String, Box<dyn std::error::Error>> {
+    series.validate().into_result()?;
+    let db = Database::new(&state.database_path)?;
+    let mut repo = SeriesRepository::new(db);
+    ensure_name_available(&mut repo, user, &series.name, None)?;
+
+    let series = Series {
+        id: uuid::Uuid::new_v4().to_string(),
+        user_id: user.id.clone(),
+        name: series.name.clone(),
+        total_count: series.total_count,
+        entry_count: 0,
+        completed_count: 0,
+    };
+    Ok(repo.create(&series)?)
+}
+
+pub fn update_series(
+    user: &User,
+    state: &AppState,
+    id: &str,
+    series: &NewSeries,
+) -> Result<(), Box<dyn std::error::Error>> {
+    series.validate().into_result()?;
+    let db = Database::new(&state.database_path)?;
+    let mut repo = SeriesRepository::new(db);
+    let existing = read_owned_series(&mut repo, user, id)?;
+    ensure_name_available(&mut repo, user, &series.name, Some(id))?;
+
+    repo.update(
+        id,
+        &Series {
+            name: series.name.clone(),
+            total_count: series.total_count,
+            ..existing
+        },
+    )?;
+    Ok(())
+}
+
+pub fn delete_series(
+    user: &User,
+    state: &AppState,
+    id: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db = Database::new(&state.database_path)?;
+    let mut repo = SeriesRepository::new(db);
+    read_owned_series(&mut repo, user, id)?;
+    repo.delete(id)?;
+    Ok(())
+}
+
+pub fn get_series_progress(
+    user: &User,
+    state: &AppState,
+    id: &str,
+) -> Result<SeriesProgress, Box<dyn std::error::Error>> {
+    let db = Database::new(&state.database_path)?;
+    let mut repo = SeriesRepository::new(db);
+    let series = read_owned_series(&mut repo, user, id)?;
+    let entries = repo.entries(id)?;
+    Ok(calculate_series_progress(series, entries))
+}
+
+/// Adds the item to the series, or moves it when it is already there
+pub fn set_series_entry(
+    user: &User,
+    state: &AppState,
+    series_id: &str,
+    item_id: &str,
+    entry: &NewSeriesEntry,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !entry.position.is_finite() || entry.position <= 0.0 {
+        return Err("Invalid position".into());
+    }
+
+    let db = Database::new(&state.database_path)?;
+    let mut repo = SeriesRepository::new(db);
+    read_owned_series(&mut repo, user, series_id)?;
+
+    let mut library_repo = LibraryRepository::new(Database::new(&state.database_path)?);
+    match library_repo.read_by_id(item_id)? {
+        Some(item) if item.user_id == user.id => {}
+        _ => return Err("Library item not found".into()),
+    }
+
+    repo.set_entry(series_id, item_id, entry.position)?;
+    Ok(())
+}
+
+pub fn remove_series_entry(
+    user: &User,
+    state: &AppState,
+    series_id: &str,
+    item_id: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db = Database::new(&state.database_path)?;
+    let mut repo = SeriesRepository::new(db);
+    read_owned_series(&mut repo, user, series_id)?;
+    if !repo.remove_entry(series_id, item_id)? {
+        return Err("Series entry not found".into());
+    }
+    Ok(())
+}
+
+fn read_owned_series(
+    repo: &mut SeriesRepository,
+    user: &User,
+    id: &str,
+) -> Result<Series, Box<dyn std::error::Error>> {
+    match repo.read_by_id(id)? {
+        Some(series) if series.user_id == user.id => Ok(series),
+        _ => Err("Series not found".into()),
+    }
+}
+
+fn ensure_name_available(
+    repo: &mut SeriesRepository,
+    user: &User,
+    name: &str,
+    own_id: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let normalized_name = normalize_series_name(name);
+    if normalized_name.is_empty() {
+        return Err("Invalid series name".into());
+    }
+
+    let filter = SeriesFilter {
+        normalized_name: Some(normalized_name),
+        ..SeriesFilter::new(&user.id)
+    };
+    let taken = repo
+        .search(filter)?
+        .into_iter()
+        .any(|series| Some(series.id.as_str()) != own_id);
+
+    if taken {
+        Err("Series already exists".into())
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{library::ItemStatus, validation::ValidationErrors};
+
+mod api;
+mod domain;
+mod repository;
+
+/// Books or games meant to be read or played in order
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Series {
+    pub id: String,
+    pub user_id: String,
+    pub name: String,
+    /// Entries in the whole series, including ones not in the library
+    pub total_count: Option<i64>,
+    pub entry_count: i64,
+    pub completed_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewSeries {
+    pub name: String,
+    pub total_count: Option<i64>,
+}
+
+/// Upper limit of the total count, also checked by the database
+pub const MAX_TOTAL_COUNT: i64 = 1000;
+
+impl NewSeries {
+    pub fn validate(&self) -> ValidationErrors {
+        let mut errors = ValidationErrors::new();
+        if self
+            .total_count
+            .is_some_and(|count| !(1..=MAX_TOTAL_COUNT).contains(&count))
+        {
+            errors.add(
+                "totalCount",
+                format!("must be between 1 and {}", MAX_TOTAL_COUNT),
+            );
+        }
+        errors
+    }
+}
+
+/// A library item in a series
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SeriesEntry {
+    pub item_id: String,
+    pub title: String,
+    pub position: f64,
+    pub status: ItemStatus,
+    pub completed_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewSeriesEntry {
+    pub position: f64,
+}
+
+/// The entry to read or play next. `item_id` is empty when the entry at
+/// the position is not in the library yet.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct NextEntry {
+    pub position: f64,
+    pub item_id: Option<String>,
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SeriesProgress {
+    pub series: Series,
+    pub entries: Vec<SeriesEntry>,
+    /// Whole positions up to the total count without an item in the library
+    pub missing_positions: Vec<i64>,
+    pub next: Option<NextEntry>,
+}
+
+/// Series names are unique per user regardless of case
+pub fn normalize_series_name(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// Orders the entries and finds the first position that is neither
+/// completed nor abandoned, counting positions missing from the library.
+pub fn calculate_series_progress(series: Series, mut entries: Vec<SeriesEntry>) -> SeriesProgress {
+    entries.sort_by(|a, b| a.position.total_cmp(&b.position));
+
+    let whole_positions: HashSet<i64> = entries
+        .iter()
+        .filter(|e| e.position.fract() == 0.0)
+        .map(|e| e.position as i64)
+        .collect();
+    let missing_positions: Vec<i64> = (1..=series.total_count.unwrap_or(0))
+        .filter(|position| !whole_positions.contains(position))
+        .collect();
+
+    let next_entry = entries
+        .iter()
+        .find(|e| !matches!(e.status, ItemStatus::Completed | ItemStatus::Abandoned))
+        .map(|e| NextEntry {
+            position: e.position,
+            item_id: Some(e.item_id.clone()),
+            title: Some(e.title.clone()),
+        });
+    let next_missing = missing_positions.first().map(|position| NextEntry {
+        position: *position as f64,
+        item_id: None,
+        title: None,
+    });
+    let next = match (next_entry, next_missing) {
+        (Some(entry), Some(missing)) if missing.position < entry.position => Some(missing),
+        (Some(entry), _) => Some(entry),
+        (None, missing) => missing,
+    };
+
+    SeriesProgress {
+        series,
+        entries,
+        missing_positions,
+        next,
+    }
+}
+
+pub use api::routes;
+pub use repository::{ensure_series, link_series_entry};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn series(total_count: Option<i64>) -> Series {
+        Series {
+            id: "s".to_string(),
+            user_id: "u".to_string(),
+            name: "Muumit".to_string(),
+            total_count,
+            entry_count: 0,
+            completed_count: 0,
+        }
+    }
+
+    fn entry(id: &str, position: f64, status: ItemStatus) -> SeriesEntry {
+        SeriesEntry {
+            item_id: id.to_string(),
+            title: format!("Book {}", id),
+            position,
+            status,
+            completed_at: None,
+        }
+    }
+
+    #[test]
+    fn next_is_first_unfinished_entry() {
+        let progress = calculate_series_progress(
+            series(None),
+            vec![
+                entry("3", 3.0, ItemStatus::Planned),
+                entry("1", 1.0, ItemStatus::Completed),
+                entry("2", 2.0, ItemStatus::Abandoned),
+            ],
+        );
+
+        let order: Vec<&str> = progress
+            .entries
+            .iter()
+            .map(|e| e.item_id.as_str())
+            .collect();
+        assert_eq!(order, vec!["1", "2", "3"]);
+        assert_eq!(progress.next.unwrap().item_id.as_deref(), Some("3"));
+    }
+
+    #[test]
+    fn missing_positions_come_before_later_entries() {
+        let progress = calculate_series_progress(
+            series(Some(4)),
+            vec![
+                entry("1", 1.0, ItemStatus::Completed),
+                entry("3", 3.0, ItemStatus::InProgress),
+            ],
+        );
+
+        assert_eq!(progress.missing_positions, vec![2, 4]);
+        assert_eq!(
+            progress.next,
+            Some(NextEntry {
+                position: 2.0,
+                item_id: None,
+                title: None,
+            })
+        );
+    }
+
+    #[test]
+    fn total_count_is_limited() {
+        let new_series = |total_count| NewSeries {
+            name: "Muumit".to_string(),
+            total_count,
+        };
+        assert!(new_series(None).validate().is_empty());
+        assert!(new_series(Some(MAX_TOTAL_COUNT)).validate().is_empty());
+        for count in [0, MAX_TOTAL_COUNT + 1, 1_000_000_000_000] {
+            let errors = new_series(Some(count)).validate();
+            assert_eq!(errors.errors[0].field, "totalCount");
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let db =
+            crate::database::Database::new(&crate::database::migrated_test_database(&dir)).unwrap();
+        let insert = |count: i64| {
+            db.conn.execute(
+                "INSERT INTO series (id, user_id, name, normalized_name, total_count)
+                    VALUES (?1, 'u', ?1, ?1, ?2)",
+                rusqlite::params![count.to_string(), count],
+            )
+        };
+        assert!(insert(MAX_TOTAL_COUNT).is_ok());
+        assert!(insert(MAX_TOTAL_COUNT + 1).is_err());
+    }
+}
@@ -0,0 +1,196 @@
+use rusqlite::{Connection, OptionalExtension, Result};
+
+use crate::database::{Database, Repository, query_in_transation};
+use crate::library::ItemStatus;
+use crate::series::{Series, SeriesEntry, normalize_series_name};
+
+pub struct SeriesFilter {
+    pub user_id: String,
+    pub normalized_name: Option<String>,
+}
+
+impl SeriesFilter {
+    pub fn new(user_id: &str) -> Self {
+        SeriesFilter {
+            user_id: user_id.to_string(),
+            normalized_name: None,
+        }
+    }
+}
+
+pub struct SeriesRepository {
+    db: Database,
+}
+
+// Entry counts selected next to the series columns
+const COUNT_COLUMNS: &str = "(SELECT COUNT(*) FROM series_entry se WHERE se.series_id = s.id),
+    (SELECT COUNT(*) FROM series_entry se JOIN library l ON l.id = se.item_id
+        WHERE se.series_id = s.id AND l.status = 'completed')";
+
+impl SeriesRepository {
+    pub fn new(db: Database) -> Self {
+        SeriesRepository { db }
+    }
+
+    pub fn entries(&mut self, series_id: &str) -> Result<Vec<SeriesEntry>> {
+        let tx = self.transaction()?;
+        let entries = query_in_transation(
+            &tx,
+            "SELECT l.id, l.title, se.position, l.status, l.completed_at FROM series_entry se
+                JOIN library l ON l.id = se.item_id
+                WHERE se.series_id = ?
+                ORDER BY se.position",
+            &[&series_id],
+            |row| {
+                let status: String = row.get(3)?;
+                Ok(SeriesEntry {
+                    item_id: row.get(0)?,
+                    title: row.get(1)?,
+                    position: row.get(2)?,
+                    status: ItemStatus::parse(&status).unwrap_or_default(),
+                    completed_at: row.get(4)?,
+                })
+            },
+        )?;
+        tx.commit()?;
+        Ok(entries)
+    }
+
+    pub fn set_entry(&mut self, series_id: &str, item_id: &str, position: f64) -> Result<()> {
+        let tx = self.transaction()?;
+        link_series_entry(&tx, series_id, item_id, position)?;
+        tx.commit()
+    }
+
+    pub fn remove_entry(&mut self, series_id: &str, item_id: &str) -> Result<bool> {
+        let tx = self.transaction()?;
+        let result = tx.execute(
+            "DELETE FROM series_entry WHERE series_id = ? AND item_id = ?",
+            [series_id, item_id],
+        )?;
+        tx.commit()?;
+        Ok(result == 1)
+    }
+}
+
+/// Id of the user's series with the name, creating the series when missing
+pub fn ensure_series(conn: &Connection, user_id: &str, name: &str) -> Result<String> {
+    let normalized_name = normalize_series_name(name);
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT id FROM series WHERE user_id = ? AND normalized_name = ?",
+            [user_id, &normalized_name],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+
+    let id = uuid::Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT INTO series (id, user_id, name, normalized_name) VALUES (?, ?, ?, ?)",
+        [&id, user_id, name.trim(), &normalized_name],
+    )?;
+    Ok(id)
+}
+
+/// Adds the item to the series or moves it to a new position
+pub fn link_series_entry(
+    conn: &Connection,
+    series_id: &str,
+    item_id: &str,
+    position: f64,
+) -> Result<usize> {
+    conn.execute(
+        "INSERT INTO series_entry (series_id, item_id, position) VALUES (?1, ?2, ?3)
+            ON CONFLICT (series_id, item_id) DO UPDATE SET position = ?3 WHERE position != ?3",
+        rusqlite::params![series_id, item_id, position],
+    )
+}
+
+impl Repository<Series, SeriesFilter> for SeriesRepository {
+    fn conn(&mut self) -> &mut rusqlite::Connection {
+        &mut self.db.conn
+    }
+
+    fn create(&mut self, series: &Series) -> Result<String> {
+        let tx = self.transaction()?;
+        tx.execute(
+            "INSERT INTO series (id, user_id, name, normalized_name, total_count) VALUES (?, ?, ?, ?, ?)",
+            rusqlite::params![
+                series.id,
+                series.user_id,
+                series.name.trim(),
+                normalize_series_name(&series.name),
+                series.total_count
+            ],
+        )?;
+        tx.commit()?;
+        Ok(series.id.clone())
+    }
+
+    fn read_by_id(&mut self, id: &str) -> Result<Option<Series>> {
+        let sql = format!(
+            "SELECT s.id, s.user_id, s.name, s.total_count, {} FROM series s WHERE s.id = ?",
+            COUNT_COLUMNS
+        );
+        self.conn().query_row(&sql, [id], row_to_series).optional()
+    }
+
+    fn search(&mut self, filter: SeriesFilter) -> Result<Vec<Series>> {
+        let mut conditions = vec!["s.user_id = ?"];
+        let mut params: Vec<&dyn rusqlite::ToSql> = vec![&filter.user_id];
+        if let Some(normalized_name) = &filter.normalized_name {
+            conditions.push("s.normalized_name = ?");
+            params.push(normalized_name);
+        }
+
+        let sql = format!(
+            "SELECT s.id, s.user_id, s.name, s.total_count, {}
+            FROM series s
+            WHERE {}
+            ORDER BY s.name COLLATE FINNISH",
+            COUNT_COLUMNS,
+            conditions.join(" AND ")
+        );
+
+        let tx = self.transaction()?;
+        let series = query_in_transation(&tx, &sql, &params, row_to_series)?;
+        tx.commit()?;
+        Ok(series)
+    }
+
+    fn update(&mut self, id: &str, series: &Series) -> Result<bool> {
+        let tx = self.transaction()?;
+        let result = tx.execute(
+            "UPDATE series SET name = ?, normalized_name = ?, total_count = ? WHERE id = ?",
+            rusqlite::params![
+                series.name.trim(),
+                normalize_series_name(&series.name),
+                series.total_count,
+                id
+            ],
+        )?;
+        tx.commit()?;
+        Ok(result == 1)
+    }
+
+    fn delete(&mut self, id: &str) -> Result<bool> {
+        let tx = self.transaction()?;
+        let result = tx.execute("DELETE FROM series WHERE id = ?", [id])?;
+        tx.commit()?;
+        Ok(result == 1)
+    }
+}
+
+fn row_to_series(row: &rusqlite::Row) -> Result<Series> {
+    Ok(Series {
+        id: row.get(0)?,
+        user_id: row.get(1)?,
+        name: row.get(2)?,
+        total_count: row.get(3)?,
+        entry_count: row.get(4)?,
+        completed_count: row.get(5)?,
+    })
+}
@@ -15,6 +15,7 @@ use crate::{
         domain::{get_solutions, upsert_solutions},
     },
     utils::map_to_internal_error,
+    validation::{ApiError, ValidJson, map_validation_error},
 };
 
 pub fn routes() -> Router<AppState> {
@@ -64,6 +65,14 @@ impl ApiQuestionSolution {
     }
 }
 
+fn map_solution_error(err: Box<dyn std::error::Error>) -> StatusCode {
+    if err.to_string().contains("not found") {
+        StatusCode::NOT_FOUND
+    } else {
+        map_to_internal_error(err)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 struct SolutionsList {
     solutions: Vec<ApiQuestionSolution>,
@@ -87,10 +96,7 @@ async fn search_solutions(
 
     // convert to api
     let list = SolutionsList {
-        solutions: results
-            .iter()
-            .map(|s| ApiQuestionSolution::from(s))
-            .collect(),
+        solutions: results.iter().map(ApiQuestionSolution::from).collect(),
     };
 
     Ok(Json(list))
@@ -101,8 +107,8 @@ async fn upsert_solutions_route(
     State(state): State<AppState>,
     Path(challenge_id): Path<Uuid>,
     user: User,
-    Json(list): Json<SolutionsList>,
-) -> Result<Json<SolutionsList>, StatusCode> {
+    ValidJson(list): ValidJson<SolutionsList>,
+) -> Result<Json<SolutionsList>, ApiError> {
     let domain_solutions: Vec<QuestionSolution> = list
         .solutions
         .iter()
@@ -115,7 +121,7 @@ async fn upsert_solutions_route(
         &challenge_id.to_string(),
         &domain_solutions,
     )
-    .map_err(|err| map_to_internal_error(Box::new(err)))?;
+    .map_err(|err| map_validation_error(err, map_solution_error))?;
 
     Ok(Json(SolutionsList {
         solutions: res
@@ -1,9 +1,14 @@
 use crate::auth::User;
+use crate::challenge::{ChallengeRepository, SharedChallenge};
 use crate::database::Database;
 use crate::database::Repository as _RepositoryTrait; // bring trait methods into scope for SolutionRepository
+use crate::library::LibraryRepository;
 use crate::solution::repository::SolutionRepository;
+use crate::validation::ValidationErrors;
 use rusqlite::Result;
 
+pub const SOLUTION_KINDS: &[&str] = &["SinglePartSolution", "MultiPartSolution"];
+
 // TODO: model with a sum type
 #[derive(Debug, Clone)]
 pub struct QuestionSolution {
@@ -25,7 +30,7 @@ pub struct SolutionFilter<'a> {
 impl<'a> SolutionFilter<'a> {
     pub fn new(user_id: &'a str) -> SolutionFilter<'a> {
         SolutionFilter {
-            user_id: user_id,
+            user_id,
             challenge_id: None,
         }
     }
@@ -47,7 +52,13 @@ pub fn upsert_solutions(
     database_path: &str,
     challenge_id: &str,
     solutions: &[QuestionSolution],
-) -> Result<Vec<QuestionSolution>> {
+) -> std::result::Result<Vec<QuestionSolution>, Box<dyn std::error::Error>> {
+    let mut challenge_repo = ChallengeRepository::new(Database::new(database_path)?);
+    let challenge = challenge_repo
+        .read_by_id(challenge_id)?
+        .filter(|challenge| challenge.is_open_to(&user.id))
+        .ok_or("Challenge not found")?;
+
     let db = Database::new(database_path)?;
     let mut repo = SolutionRepository::new(db);
 
@@ -58,7 +69,7 @@ pub fn upsert_solutions(
         .iter()
         .map(|s| {
             let single_answer = s.single_answer_item_id.clone().filter(|a| !a.is_empty());
-            let multi_answer = s.multiple_answer_item_ids.clone().filter(|a| a.len() > 0);
+            let multi_answer = s.multiple_answer_item_ids.clone().filter(|a| !a.is_empty());
             QuestionSolution {
                 single_answer_item_id: single_answer,
                 multiple_answer_item_ids: multi_answer,
@@ -66,6 +77,7 @@ pub fn upsert_solutions(
             }
         })
         .collect::<Vec<_>>();
+    validate_solutions(user, database_path, &challenge, &validated_solutions)?;
 
     // Read existing solutions for this user and challenge
     let existing = repo.search(SolutionFilter::new(&user.id).with_challenge_id(challenge_id))?;
@@ -92,5 +104,58 @@ pub fn upsert_solutions(
     }
 
     // Return current set for the user + challenge
-    repo.search(SolutionFilter::new(&user.id).with_challenge_id(challenge_id))
+    Ok(repo.search(SolutionFilter::new(&user.id).with_challenge_id(challenge_id))?)
+}
+
+/// Solutions must answer questions of the challenge with the user's own
+/// items of the kind the challenge targets
+fn validate_solutions(
+    user: &User,
+    database_path: &str,
+    challenge: &SharedChallenge,
+    solutions: &[QuestionSolution],
+) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let mut library_repo = LibraryRepository::new(Database::new(database_path)?);
+    let mut errors = ValidationErrors::new();
+    for (index, solution) in solutions.iter().enumerate() {
+        let field = |name: &str| format!("solutions[{}].{}", index, name);
+        errors.one_of(&field("kind"), &solution.kind, SOLUTION_KINDS);
+        if !challenge
+            .questions
+            .iter()
+            .any(|question| question.id == solution.question_id)
+        {
+            errors.add(field("questionId"), "is not a question of the challenge");
+        }
+
+        let mut item_ids = Vec::new();
+        if let Some(item_id) = &solution.single_answer_item_id {
+            item_ids.push((field("singleAnswerItemId"), item_id));
+        }
+        for (item_index, item_id) in solution
+            .multiple_answer_item_ids
+            .iter()
+            .flatten()
+            .enumerate()
+        {
+            item_ids.push((
+                format!("{}[{}]", field("multipleAnswerItemIds"), item_index),
+                item_id,
+            ));
+        }
+        for (item_field, item_id) in item_ids {
+            match library_repo.read_by_id(item_id)? {
+                Some(item) if item.user_id != user.id => {
+                    errors.add(item_field, "item does not exist")
+                }
+                Some(item) if item.details.kind() != challenge.target_media => errors.add(
+                    item_field,
+                    format!("item is not a {}", challenge.target_media),
+                ),
+                Some(_) => {}
+                None => errors.add(item_field, "item does not exist"),
+            }
+        }
+    }
+    Ok(errors.into_result()?)
 }
@@ -3,4 +3,4 @@ mod domain;
 mod repository;
 
 pub use api::routes;
-pub use domain::{QuestionSolution, SolutionFilter};
+pub use domain::{QuestionSolution, SolutionFilter, get_solutions};
@@ -72,7 +72,7 @@ impl Repository<QuestionSolution, SolutionFilter<'_>> for SolutionRepository {
         let tx = self.transaction()?;
         let result = tx.execute(
             "UPDATE question_solution SET user_id = ?, challenge_id = ?, 
-                question_id = ?, kind = ?,  = ? WHERE id = ?",
+                question_id = ?, kind = ?, single_answer_item_id = ? WHERE id = ?",
             rusqlite::params![
                 &item.user_id,
                 &item.challenge_id,
@@ -94,7 +94,7 @@ impl Repository<QuestionSolution, SolutionFilter<'_>> for SolutionRepository {
 
     fn delete(&mut self, id: &str) -> Result<bool> {
         let tx = self.transaction()?;
-        let result = tx.execute("DELETE FROM question_solution WHERE id = ?", &[&id])?;
+        let result = tx.execute("DELETE FROM question_solution WHERE id = ?", [&id])?;
         if result == 1 {
             tx.commit()?;
         }
@@ -104,7 +104,7 @@ impl Repository<QuestionSolution, SolutionFilter<'_>> for SolutionRepository {
 
 fn row_to_solution(row: &rusqlite::Row) -> Result<QuestionSolution> {
     let item_ids: Option<String> = row.get(6)?;
-    let multipart_items = item_ids.map(|ids| ids.split(',').map(|x| String::from(x)).collect());
+    let multipart_items = item_ids.map(|ids| ids.split(',').map(String::from).collect());
     //.unwrap_or_default();
 
     Ok(QuestionSolution {
@@ -122,13 +122,13 @@ fn update_multipart_solution(tx: &Transaction, solution: &QuestionSolution) -> R
     if let Some(multipart_solution) = &solution.multiple_answer_item_ids {
         tx.execute(
             "DELETE FROM multipart_solution WHERE solution_id = ?",
-            &[&solution.id],
+            [&solution.id],
         )?;
 
         for item_id in multipart_solution {
             tx.execute(
                 "INSERT INTO multipart_solution(solution_id, item_id) VALUES (?, ?)",
-                &[&solution.id, &item_id],
+                [&solution.id, item_id],
             )?;
         }
     }
@@ -0,0 +1,133 @@
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{delete, get, post, put},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppState,
+    auth::User,
+    tags::{
+        NewTag, Tag,
+        domain::{create_tag, delete_tag, get_tags, merge_tags, rename_tag, set_items_tagged},
+    },
+    utils::map_to_internal_error,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/tags", get(get_all_tags))
+        .route("/tags", post(create_new_tag))
+        .route("/tags/{id}", put(rename_existing_tag))
+        .route("/tags/{id}", delete(delete_existing_tag))
+        .route("/tags/{id}/merge", post(merge_tag))
+        .route("/tags/{id}/items", post(tag_items))
+        .route("/tags/{id}/items", delete(untag_items))
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+struct IdResponse {
+    id: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct MergeRequest {
+    target_tag_id: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct ItemIdsRequest {
+    item_ids: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct ChangedResponse {
+    changed: usize,
+}
+
+fn map_tag_error(err: Box<dyn std::error::Error>) -> StatusCode {
+    let message = err.to_string();
+    if message.contains("not found") {
+        StatusCode::NOT_FOUND
+    } else if message.contains("already exists") {
+        StatusCode::CONFLICT
+    } else if message.contains("Invalid") {
+        StatusCode::UNPROCESSABLE_ENTITY
+    } else {
+        map_to_internal_error(err)
+    }
+}
+
+async fn get_all_tags(
+    State(state): State<AppState>,
+    user: User,
+) -> Result<Json<Vec<Tag>>, StatusCode> {
+    let tags = get_tags(&user, &state).map_err(map_to_internal_error)?;
+    Ok(Json(tags))
+}
+
+async fn create_new_tag(
+    State(state): State<AppState>,
+    user: User,
+    Json(tag): Json<NewTag>,
+) -> Result<Json<IdResponse>, StatusCode> {
+    let id = create_tag(&user, &state, &tag).map_err(map_tag_error)?;
+    Ok(Json(IdResponse { id }))
+}
+
+async fn rename_existing_tag(
+    State(state): State<AppState>,
+    user: User,
+    Path(id): Path<String>,
+    Json(tag): Json<NewTag>,
+) -> Result<StatusCode, StatusCode> {
+    rename_tag(&user, &state, &id, &tag).map_err(map_tag_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn delete_existing_tag(
+    State(state): State<AppState>,
+    user: User,
+    Path(id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    delete_tag(&user, &state, &id).map_err(map_tag_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn merge_tag(
+    State(state): State<AppState>,
+    user: User,
+    Path(id): Path<String>,
+    Json(request): Json<MergeRequest>,
+) -> Result<Json<Tag>, StatusCode> {
+    let tag = merge_tags(&user, &state, &id, &request.target_tag_id).map_err(map_tag_error)?;
+    Ok(Json(tag))
+}
+
+async fn tag_items(
+    State(state): State<AppState>,
+    user: User,
+    Path(id): Path<String>,
+    Json(request): Json<ItemIdsRequest>,
+) -> Result<Json<ChangedResponse>, StatusCode> {
+    let changed =
+        set_items_tagged(&user, &state, &id, &request.item_ids, true).map_err(map_tag_error)?;
+    Ok(Json(ChangedResponse { changed }))
+}
+
+async fn untag_items(
+    State(state): State<AppState>,
+    user: User,
+    Path(id): Path<String>,
+    Json(request): Json<ItemIdsRequest>,
+) -> Result<Json<ChangedResponse>, StatusCode> {
+    let changed =
+        set_items_tagged(&user, &state, &id, &request.item_ids, false).map_err(map_tag_error)?;
+    Ok(Json(ChangedResponse { changed }))
+}
@@ -0,0 +1,292 @@
+use crate::{
+    AppState,
+    auth::User,
+    database::{Database, Repository},
+    tags::{
+        NewTag, Tag, normalize_tag_name,
+        repository::{TagFilter, TagRepository},
+    },
+};
+
+pub fn get_tags(user: &User, state: &AppState) -> Result<Vec<Tag>, Box<dyn std::error::Error>> {
+    let db = Database::new(&state.database_path)?;
+    let mut repo = TagRepository::new(db);
+    Ok(repo.search(TagFilter::new(&user.id))?)
+}
+
+pub fn create_tag(
+    user: &User,
+    state: &AppState,
+    tag: &NewTag,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let db = Database::new(&state.database_path)?;
+    let mut repo = TagRepository::new(db);
+    ensure_name_available(&mut repo, user, &tag.name, None)?;
+
+    let tag = Tag {
+        id: uuid::Uuid::new_v4().to_string(),
+        user_id: user.id.clone(),
+        name: tag.name.clone(),
+        item_count: 0,
+    };
+    Ok(repo.create(&tag)?)
+}
+
+pub fn rename_tag(
+    user: &User,
+    state: &AppState,
+    id: &str,
+    tag: &NewTag,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db = Database::new(&state.database_path)?;
+    let mut repo = TagRepository::new(db);
+    let existing = read_owned_tag(&mut repo, user, id)?;
+    ensure_name_available(&mut repo, user, &tag.name, Some(id))?;
+
+    repo.update(
+        id,
+        &Tag {
+            name: tag.name.clone(),
+            ..existing
+        },
+    )?;
+    Ok(())
+}
+
+pub fn delete_tag(
+    user: &User,
+    state: &AppState,
+    id: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db = Database::new(&state.database_path)?;
+    let mut repo = TagRepository::new(db);
+    read_owned_tag(&mut repo, user, id)?;
+    repo.delete(id)?;
+    Ok(())
+}
+
+/// Moves all items of `source_id` under `target_id` and removes the source tag
+pub fn merge_tags(
+    user: &User,
+    state: &AppState,
+    source_id: &str,
+    target_id: &str,
+) -> Result<Tag, Box<dyn std::error::Error>> {
+    if source_id == target_id {
+        return Err("Invalid merge: a tag cannot be merged into itself".into());
+    }
+
+    let db = Database::new(&state.database_path)?;
+    let mut repo = TagRepository::new(db);
+    read_owned_tag(&mut repo, user, source_id)?;
+    read_owned_tag(&mut repo, user, target_id)?;
+
+    repo.merge(source_id, target_id)?;
+    repo.read_by_id(target_id)?
+        .ok_or_else(|| "Tag not found".into())
+}
+
+pub fn set_items_tagged(
+    user: &User,
+    state: &AppState,
+    tag_id: &str,
+    item_ids: &[String],
+    tagged: bool,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let db = Database::new(&state.database_path)?;
+    let mut repo = TagRepository::new(db);
+    read_owned_tag(&mut repo, user, tag_id)?;
+
+    let mut unique_ids = item_ids.to_vec();
+    unique_ids.sort();
+    unique_ids.dedup();
+    if repo.count_owned_items(&user.id, &unique_ids)? != unique_ids.len() {
+        return Err("Library item not found".into());
+    }
+
+    if tagged {
+        Ok(repo.tag_items(tag_id, &unique_ids)?)
+    } else {
+        Ok(repo.untag_items(tag_id, &unique_ids)?)
+    }
+}
+
+fn read_owned_tag(
+    repo: &mut TagRepository,
+    user: &User,
+    id: &str,
+) -> Result<Tag, Box<dyn std::error::Error>> {
+    match repo.read_by_id(id)? {
+        Some(tag) if tag.user_id == user.id => Ok(tag),
+        _ => Err("Tag not found".into()),
+    }
+}
+
+fn ensure_name_available(
+    repo: &mut TagRepository,
+    user: &User,
+    name: &str,
+    own_id: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let normalized_name = normalize_tag_name(name);
+    if normalized_name.is_empty() {
+        return Err("Invalid tag name".into());
+    }
+
+    let filter = TagFilter {
+        normalized_name: Some(normalized_name),
+        ..TagFilter::new(&user.id)
+    };
+    let taken = repo
+        .search(filter)?
+        .into_iter()
+        .any(|tag| Some(tag.id.as_str()) != own_id);
+
+    if taken {
+        Err("Tag already exists".into())
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        database::migrated_test_database,
+        library::{LibraryRepository, test_support::book},
+    };
+
+    /// State over a migrated database holding the book "a" of "me" and the
+    /// book "b" of "other"
+    fn state(dir: &tempfile::TempDir) -> AppState {
+        let database_path = migrated_test_database(dir);
+        let mut library = LibraryRepository::new(Database::new(&database_path).unwrap());
+        library.create(&book("a", "me", "a", "Author")).unwrap();
+        library.create(&book("b", "other", "b", "Author")).unwrap();
+        AppState::for_tests(&database_path)
+    }
+
+    fn user(id: &str) -> User {
+        User::new(id.to_string())
+    }
+
+    fn new_tag(name: &str) -> NewTag {
+        NewTag {
+            name: name.to_string(),
+        }
+    }
+
+    fn error(result: Result<impl std::fmt::Debug, Box<dyn std::error::Error>>) -> String {
+        result.unwrap_err().to_string()
+    }
+
+    #[test]
+    fn names_must_be_unique_regardless_of_case() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = state(&dir);
+        let me = user("me");
+        let id = create_tag(&me, &state, &new_tag("Kesä")).unwrap();
+
+        assert_eq!(
+            error(create_tag(&me, &state, &new_tag(" KESÄ "))),
+            "Tag already exists"
+        );
+        assert_eq!(
+            error(create_tag(&me, &state, &new_tag("  "))),
+            "Invalid tag name"
+        );
+        create_tag(&user("other"), &state, &new_tag("kesä")).unwrap();
+
+        // Renaming may change the case of the tag's own name
+        rename_tag(&me, &state, &id, &new_tag("KESÄ")).unwrap();
+        let other_id = create_tag(&me, &state, &new_tag("Talvi")).unwrap();
+        assert_eq!(
+            error(rename_tag(&me, &state, &other_id, &new_tag("kesä"))),
+            "Tag already exists"
+        );
+
+        let names: Vec<String> = get_tags(&me, &state)
+            .unwrap()
+            .into_iter()
+            .map(|tag| tag.name)
+            .collect();
+        assert_eq!(names, vec!["KESÄ", "Talvi"]);
+    }
+
+    #[test]
+    fn tags_of_other_users_are_not_found() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = state(&dir);
+        let me = user("me");
+        let own_id = create_tag(&me, &state, &new_tag("oma")).unwrap();
+        let other_id = create_tag(&user("other"), &state, &new_tag("muiden")).unwrap();
+
+        assert_eq!(
+            error(rename_tag(&me, &state, &other_id, &new_tag("x"))),
+            "Tag not found"
+        );
+        assert_eq!(error(delete_tag(&me, &state, &other_id)), "Tag not found");
+        assert_eq!(
+            error(merge_tags(&me, &state, &other_id, &own_id)),
+            "Tag not found"
+        );
+        assert_eq!(
+            error(merge_tags(&me, &state, &own_id, &other_id)),
+            "Tag not found"
+        );
+        assert_eq!(
+            error(set_items_tagged(
+                &me,
+                &state,
+                &other_id,
+                &["a".to_string()],
+                true
+            )),
+            "Tag not found"
+        );
+        assert_eq!(get_tags(&user("other"), &state).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn tags_only_own_items() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = state(&dir);
+        let me = user("me");
+        let id = create_tag(&me, &state, &new_tag("kesä")).unwrap();
+
+        assert_eq!(
+            error(set_items_tagged(
+                &me,
+                &state,
+                &id,
+                &["a".to_string(), "b".to_string()],
+                true
+            )),
+            "Library item not found"
+        );
+        // Repeated ids count once
+        let ids = ["a".to_string(), "a".to_string()];
+        assert_eq!(set_items_tagged(&me, &state, &id, &ids, true).unwrap(), 1);
+        assert_eq!(set_items_tagged(&me, &state, &id, &ids, true).unwrap(), 0);
+        assert_eq!(set_items_tagged(&me, &state, &id, &ids, false).unwrap(), 1);
+    }
+
+    #[test]
+    fn merges_into_another_tag() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = state(&dir);
+        let me = user("me");
+        let source_id = create_tag(&me, &state, &new_tag("kesä")).unwrap();
+        let target_id = create_tag(&me, &state, &new_tag("loma")).unwrap();
+        set_items_tagged(&me, &state, &source_id, &["a".to_string()], true).unwrap();
+
+        assert_eq!(
+            error(merge_tags(&me, &state, &source_id, &source_id)),
+            "Invalid merge: a tag cannot be merged into itself"
+        );
+        let merged = merge_tags(&me, &state, &source_id, &target_id).unwrap();
+        assert_eq!((merged.name.as_str(), merged.item_count), ("loma", 1));
+        assert_eq!(get_tags(&me, &state).unwrap().len(), 1);
+    }
+}
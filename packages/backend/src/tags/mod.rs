@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+mod api;
+mod domain;
+mod repository;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tag {
+    pub id: String,
+    pub user_id: String,
+    pub name: String,
+    pub item_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewTag {
+    pub name: String,
+}
+
+/// Tag names are unique per user regardless of case
+pub fn normalize_tag_name(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+pub use api::routes;
+pub use domain::get_tags;
+pub use repository::ensure_tag;
@@ -0,0 +1,310 @@
+use rusqlite::{Connection, OptionalExtension, Result, types::Value};
+
+use crate::database::{Database, Repository, query_in_transation};
+use crate::tags::{Tag, normalize_tag_name};
+
+pub struct TagFilter {
+    pub user_id: String,
+    pub normalized_name: Option<String>,
+}
+
+impl TagFilter {
+    pub fn new(user_id: &str) -> Self {
+        TagFilter {
+            user_id: user_id.to_string(),
+            normalized_name: None,
+        }
+    }
+}
+
+pub struct TagRepository {
+    db: Database,
+}
+
+impl TagRepository {
+    pub fn new(db: Database) -> Self {
+        TagRepository { db }
+    }
+
+    /// Adds the tag to the items, ignoring items that already have it
+    pub fn tag_items(&mut self, tag_id: &str, item_ids: &[String]) -> Result<usize> {
+        let tx = self.transaction()?;
+        let mut changed = 0;
+        for item_id in item_ids {
+            changed += tx.execute(
+                "INSERT OR IGNORE INTO item_tag (item_id, tag_id) VALUES (?, ?)",
+                [item_id, tag_id],
+            )?;
+        }
+        tx.commit()?;
+        Ok(changed)
+    }
+
+    pub fn untag_items(&mut self, tag_id: &str, item_ids: &[String]) -> Result<usize> {
+        let tx = self.transaction()?;
+        let mut changed = 0;
+        for item_id in item_ids {
+            changed += tx.execute(
+                "DELETE FROM item_tag WHERE item_id = ? AND tag_id = ?",
+                [item_id, tag_id],
+            )?;
+        }
+        tx.commit()?;
+        Ok(changed)
+    }
+
+    /// Moves every item of `source_id` to `target_id` and deletes the source tag
+    pub fn merge(&mut self, source_id: &str, target_id: &str) -> Result<()> {
+        let tx = self.transaction()?;
+        tx.execute(
+            "INSERT OR IGNORE INTO item_tag (item_id, tag_id) 
+                SELECT item_id, ?2 FROM item_tag WHERE tag_id = ?1",
+            [source_id, target_id],
+        )?;
+        tx.execute("DELETE FROM tag WHERE id = ?", [source_id])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Number of the given items that belong to the user
+    pub fn count_owned_items(&mut self, user_id: &str, item_ids: &[String]) -> Result<usize> {
+        let placeholders = vec!["?"; item_ids.len()].join(", ");
+        let sql = format!(
+            "SELECT COUNT(*) FROM library WHERE user_id = ? AND id IN ({})",
+            placeholders
+        );
+        let mut params = vec![Value::Text(user_id.to_string())];
+        params.extend(item_ids.iter().map(|id| Value::Text(id.clone())));
+
+        let count: i64 =
+            self.conn()
+                .query_row(&sql, rusqlite::params_from_iter(params), |row| row.get(0))?;
+        Ok(count as usize)
+    }
+}
+
+/// Id of the user's tag with the name, creating the tag when missing
+pub fn ensure_tag(conn: &Connection, user_id: &str, name: &str) -> Result<String> {
+    let normalized_name = normalize_tag_name(name);
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT id FROM tag WHERE user_id = ? AND normalized_name = ?",
+            [user_id, &normalized_name],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+
+    let id = uuid::Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT INTO tag (id, user_id, name, normalized_name) VALUES (?, ?, ?, ?)",
+        [&id, user_id, name.trim(), &normalized_name],
+    )?;
+    Ok(id)
+}
+
+impl Repository<Tag, TagFilter> for TagRepository {
+    fn conn(&mut self) -> &mut rusqlite::Connection {
+        &mut self.db.conn
+    }
+
+    fn create(&mut self, tag: &Tag) -> Result<String> {
+        let tx = self.transaction()?;
+        tx.execute(
+            "INSERT INTO tag (id, user_id, name, normalized_name) VALUES (?, ?, ?, ?)",
+            rusqlite::params![
+                tag.id,
+                tag.user_id,
+                tag.name.trim(),
+                normalize_tag_name(&tag.name)
+            ],
+        )?;
+        tx.commit()?;
+        Ok(tag.id.clone())
+    }
+
+    fn read_by_id(&mut self, id: &str) -> Result<Option<Tag>> {
+        let sql = "SELECT t.id, t.user_id, t.name, 
+                (SELECT COUNT(*) FROM item_tag it WHERE it.tag_id = t.id) 
+            FROM tag t 
+            WHERE t.id = ?";
+        self.conn().query_row(sql, [id], row_to_tag).optional()
+    }
+
+    fn search(&mut self, filter: TagFilter) -> Result<Vec<Tag>> {
+        let mut conditions = vec!["t.user_id = ?"];
+        let mut params: Vec<&dyn rusqlite::ToSql> = vec![&filter.user_id];
+        if let Some(normalized_name) = &filter.normalized_name {
+            conditions.push("t.normalized_name = ?");
+            params.push(normalized_name);
+        }
+
+        let sql = format!(
+            "SELECT t.id, t.user_id, t.name, 
+                (SELECT COUNT(*) FROM item_tag it WHERE it.tag_id = t.id) 
+            FROM tag t 
+            WHERE {}
+            ORDER BY t.name COLLATE FINNISH",
+            conditions.join(" AND ")
+        );
+
+        let tx = self.transaction()?;
+        let tags = query_in_transation(&tx, &sql, &params, row_to_tag)?;
+        tx.commit()?;
+        Ok(tags)
+    }
+
+    fn update(&mut self, id: &str, tag: &Tag) -> Result<bool> {
+        let tx = self.transaction()?;
+        let result = tx.execute(
+            "UPDATE tag SET name = ?, normalized_name = ? WHERE id = ?",
+            rusqlite::params![tag.name.trim(), normalize_tag_name(&tag.name), id],
+        )?;
+        tx.commit()?;
+        Ok(result == 1)
+    }
+
+    fn delete(&mut self, id: &str) -> Result<bool> {
+        let tx = self.transaction()?;
+        let result = tx.execute("DELETE FROM tag WHERE id = ?", [id])?;
+        tx.commit()?;
+        Ok(result == 1)
+    }
+}
+
+fn row_to_tag(row: &rusqlite::Row) -> Result<Tag> {
+    Ok(Tag {
+        id: row.get(0)?,
+        user_id: row.get(1)?,
+        name: row.get(2)?,
+        item_count: row.get(3)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::migrated_test_database;
+    use crate::library::{LibraryRepository, test_support::book};
+
+    fn tag(id: &str, user_id: &str, name: &str) -> Tag {
+        Tag {
+            id: id.to_string(),
+            user_id: user_id.to_string(),
+            name: name.to_string(),
+            item_count: 0,
+        }
+    }
+
+    /// Tag repository over a migrated database holding the books "a" and "b"
+    /// of "me" and the book "c" of "other"
+    fn repository(dir: &tempfile::TempDir) -> TagRepository {
+        let db_path = migrated_test_database(dir);
+        let mut library = LibraryRepository::new(Database::new(&db_path).unwrap());
+        for (id, user_id) in [("a", "me"), ("b", "me"), ("c", "other")] {
+            library.create(&book(id, user_id, id, "Author")).unwrap();
+        }
+        TagRepository::new(Database::new(&db_path).unwrap())
+    }
+
+    fn ids(items: &[&str]) -> Vec<String> {
+        items.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn stores_trimmed_names_unique_regardless_of_case() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut repo = repository(&dir);
+        repo.create(&tag("t1", "me", "  Kesä ")).unwrap();
+        repo.create(&tag("t2", "me", "Ärsyttävät")).unwrap();
+        repo.create(&tag("t3", "other", "kesä")).unwrap();
+        assert!(repo.create(&tag("t4", "me", "KESÄ")).is_err());
+
+        let names: Vec<String> = repo
+            .search(TagFilter::new("me"))
+            .unwrap()
+            .into_iter()
+            .map(|tag| tag.name)
+            .collect();
+        assert_eq!(names, vec!["Kesä", "Ärsyttävät"]);
+
+        let found = repo
+            .search(TagFilter {
+                normalized_name: Some(normalize_tag_name("KESÄ")),
+                ..TagFilter::new("me")
+            })
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, "t1");
+
+        assert_eq!(ensure_tag(repo.conn(), "me", " kesä").unwrap(), "t1");
+        let created = ensure_tag(repo.conn(), "me", "Talvi").unwrap();
+        assert_eq!(repo.read_by_id(&created).unwrap().unwrap().name, "Talvi");
+    }
+
+    #[test]
+    fn renames_and_deletes() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut repo = repository(&dir);
+        repo.create(&tag("t1", "me", "kesä")).unwrap();
+        repo.tag_items("t1", &ids(&["a"])).unwrap();
+
+        assert!(repo.update("t1", &tag("t1", "me", " Talvi ")).unwrap());
+        assert_eq!(repo.read_by_id("t1").unwrap().unwrap().name, "Talvi");
+        assert!(!repo.update("missing", &tag("missing", "me", "x")).unwrap());
+
+        assert!(repo.delete("t1").unwrap());
+        assert!(repo.read_by_id("t1").unwrap().is_none());
+        assert!(!repo.delete("t1").unwrap());
+        let links: i64 = repo
+            .conn()
+            .query_row("SELECT COUNT(*) FROM item_tag", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(links, 0);
+    }
+
+    #[test]
+    fn tags_and_untags_items() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut repo = repository(&dir);
+        repo.create(&tag("t1", "me", "kesä")).unwrap();
+
+        assert_eq!(repo.tag_items("t1", &ids(&["a"])).unwrap(), 1);
+        // Items that already have the tag are not counted again
+        assert_eq!(repo.tag_items("t1", &ids(&["a", "b"])).unwrap(), 1);
+        assert_eq!(repo.read_by_id("t1").unwrap().unwrap().item_count, 2);
+
+        assert_eq!(repo.untag_items("t1", &ids(&["a", "c"])).unwrap(), 1);
+        assert_eq!(repo.read_by_id("t1").unwrap().unwrap().item_count, 1);
+    }
+
+    #[test]
+    fn merge_moves_items_and_deletes_the_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut repo = repository(&dir);
+        repo.create(&tag("source", "me", "kesä")).unwrap();
+        repo.create(&tag("target", "me", "loma")).unwrap();
+        repo.tag_items("source", &ids(&["a", "b"])).unwrap();
+        repo.tag_items("target", &ids(&["a"])).unwrap();
+
+        repo.merge("source", "target").unwrap();
+
+        assert!(repo.read_by_id("source").unwrap().is_none());
+        assert_eq!(repo.read_by_id("target").unwrap().unwrap().item_count, 2);
+    }
+
+    #[test]
+    fn counts_only_items_of_the_user() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut repo = repository(&dir);
+
+        assert_eq!(repo.count_owned_items("me", &ids(&["a", "b"])).unwrap(), 2);
+        assert_eq!(
+            repo.count_owned_items("me", &ids(&["a", "c", "missing"]))
+                .unwrap(),
+            1
+        );
+    }
+}
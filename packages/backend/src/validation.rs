@@ -0,0 +1,255 @@
+use axum::{
+    Json,
+    extract::{FromRequest, rejection::JsonRejection},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+
+/// Problem with a single field of a request body. `field` is the camelCase
+/// path of the field as sent, e.g. `questions[2].kind`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+/// Every problem found in a request body, returned as a 422 response
+#[derive(Serialize, Debug, Default, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationErrors {
+    pub errors: Vec<FieldError>,
+}
+
+impl ValidationErrors {
+    pub fn new() -> Self {
+        ValidationErrors::default()
+    }
+
+    pub fn add(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.errors.push(FieldError {
+            field: field.into(),
+            message: message.into(),
+        });
+    }
+
+    pub fn require(&mut self, field: &str, value: &str) {
+        if value.trim().is_empty() {
+            self.add(field, "is required");
+        }
+    }
+
+    pub fn one_of(&mut self, field: &str, value: &str, allowed: &[&str]) {
+        if !allowed.contains(&value) {
+            self.add(field, format!("must be one of {}", allowed.join(", ")));
+        }
+    }
+
+    pub fn date(&mut self, field: &str, value: Option<&str>) {
+        if let Some(value) = value
+            && !is_valid_date(value)
+        {
+            self.add(field, "must be an RFC 3339 date or date-time");
+        }
+    }
+
+    pub fn year(&mut self, field: &str, value: Option<i32>) {
+        if let Some(value) = value
+            && !YEARS.contains(&value)
+        {
+            self.add(
+                field,
+                format!("must be between {} and {}", YEARS.start(), YEARS.end()),
+            );
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn into_result(self) -> Result<(), ValidationErrors> {
+        if self.is_empty() { Ok(()) } else { Err(self) }
+    }
+}
+
+impl std::fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let errors: Vec<String> = self
+            .errors
+            .iter()
+            .map(|e| format!("{} {}", e.field, e.message))
+            .collect();
+        write!(f, "Invalid request: {}", errors.join(", "))
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+impl IntoResponse for ValidationErrors {
+    fn into_response(self) -> Response {
+        (StatusCode::UNPROCESSABLE_ENTITY, Json(self)).into_response()
+    }
+}
+
+/// Years accepted in requests, four digits so that dates compare as strings
+pub const YEARS: std::ops::RangeInclusive<i32> = 1900..=9999;
+
+/// Full dates (`2025-01-31`) and date-times with an offset
+/// (`2025-01-31T12:00:00Z`), the two forms the frontend sends
+pub fn is_valid_date(value: &str) -> bool {
+    parse_date(value).is_some()
+}
+
+/// Day of a full date or a date-time with an offset. Dates are stored and
+/// compared as strings, so only the zero padded form is accepted, not the
+/// `2025-1-1` or ` 2025-01-01` chrono would otherwise parse.
+pub fn parse_date(value: &str) -> Option<chrono::NaiveDate> {
+    if value.len() == 10 {
+        return parse_day(value);
+    }
+    chrono::DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|date_time| date_time.date_naive())
+}
+
+/// Date written exactly as `YYYY-MM-DD`
+pub fn parse_day(value: &str) -> Option<chrono::NaiveDate> {
+    let padded = value.len() == 10
+        && value.bytes().enumerate().all(|(index, byte)| match index {
+            4 | 7 => byte == b'-',
+            _ => byte.is_ascii_digit(),
+        });
+    if !padded {
+        return None;
+    }
+    chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()
+}
+
+/// Error of handlers that validate their input: validation failures have a
+/// body with the field errors, everything else is a bare status
+#[derive(Debug)]
+pub enum ApiError {
+    Status(StatusCode),
+    Invalid(ValidationErrors),
+}
+
+impl From<StatusCode> for ApiError {
+    fn from(status: StatusCode) -> Self {
+        ApiError::Status(status)
+    }
+}
+
+impl From<ValidationErrors> for ApiError {
+    fn from(errors: ValidationErrors) -> Self {
+        ApiError::Invalid(errors)
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        match rejection {
+            JsonRejection::JsonDataError(err) => {
+                let (field, message) = split_serde_path(&err.body_text());
+                let mut errors = ValidationErrors::new();
+                errors.add(field, message);
+                ApiError::Invalid(errors)
+            }
+            other => ApiError::Status(other.status()),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        match self {
+            ApiError::Status(status) => status.into_response(),
+            ApiError::Invalid(errors) => errors.into_response(),
+        }
+    }
+}
+
+/// Maps domain errors to responses: validation errors become 422 with the
+/// field errors, anything else the given mapping decides
+pub fn map_validation_error(
+    err: Box<dyn std::error::Error>,
+    otherwise: impl FnOnce(Box<dyn std::error::Error>) -> StatusCode,
+) -> ApiError {
+    match err.downcast::<ValidationErrors>() {
+        Ok(errors) => ApiError::Invalid(*errors),
+        Err(err) => ApiError::Status(otherwise(err)),
+    }
+}
+
+/// JSON body extractor whose deserialization errors, such as an unknown
+/// enum value, are reported like other validation errors
+#[derive(FromRequest)]
+#[from_request(via(Json), rejection(ApiError))]
+pub struct ValidJson<T>(pub T);
+
+/// Splits the path off a serde error like "Failed to deserialize the JSON
+/// body into the target type: status: unknown variant `x`". The path is
+/// missing when the error concerns the body as a whole.
+fn split_serde_path(text: &str) -> (String, String) {
+    let detail = text.split_once("target type: ").map_or(text, |(_, d)| d);
+    match detail.split_once(": ") {
+        Some((path, message)) if !path.is_empty() && !path.contains(' ') && path != "." => {
+            (path.to_string(), message.to_string())
+        }
+        _ => ("body".to_string(), detail.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_dates_and_date_times() {
+        assert!(is_valid_date("2025-01-31"));
+        assert!(is_valid_date("2025-01-31T12:00:00Z"));
+        assert!(is_valid_date("2025-01-31T12:00:00.123+02:00"));
+        assert!(!is_valid_date("2025-02-30"));
+        assert!(!is_valid_date("31.1.2025"));
+        assert!(!is_valid_date("2025-01-31T12:00:00"));
+    }
+
+    #[test]
+    fn rejects_dates_that_do_not_sort_as_strings() {
+        for value in [
+            "2025-1-1",
+            "2025-01-1",
+            "+2025-01-01",
+            " 2025-01-01",
+            "2025-01-01 ",
+        ] {
+            assert!(!is_valid_date(value), "{}", value);
+        }
+        assert_eq!(
+            parse_date("2025-01-31T23:30:00-02:00"),
+            chrono::NaiveDate::from_ymd_opt(2025, 1, 31)
+        );
+    }
+
+    #[test]
+    fn splits_field_path_from_serde_errors() {
+        assert_eq!(
+            split_serde_path(
+                "Failed to deserialize the JSON body into the target type: questions[0].kind: \
+                 invalid type: integer `1`, expected a string at line 1 column 40"
+            ),
+            (
+                "questions[0].kind".to_string(),
+                "invalid type: integer `1`, expected a string at line 1 column 40".to_string()
+            )
+        );
+        assert_eq!(
+            split_serde_path(
+                "Failed to deserialize the JSON body into the target type: missing field `title`"
+            )
+            .0,
+            "body"
+        );
+    }
+}
@@ -7,12 +7,12 @@ export abstract class BaseApiClient<
   T extends z.ZodType<{ id: string }>,
   TNew extends z.ZodTypeAny,
 > {
-  private readonly baseUrl: string
+  protected readonly baseUrl: string
   constructor(
     private readonly schema: T,
     private readonly newSchema: TNew,
     urlSuffix: string,
-    private readonly proxy: HttpProxy,
+    protected readonly proxy: HttpProxy,
   ) {
     this.baseUrl = `${API_URL}/${urlSuffix}`
   }
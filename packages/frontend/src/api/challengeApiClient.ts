@@ -1,3 +1,4 @@
+import { z } from "zod"
 import { challengeSchema, type Challenge } from "@/models/challenge"
 import { BaseApiClient } from "./baseApiClient"
 import type { HttpProxy } from "./HttpProxy"
@@ -5,13 +6,32 @@ import { useHttpApi } from "@/plugins/HttpPlugin"
 
 const newChallengeSchema = challengeSchema.omit({ id: true })
 
+// Challenges can also target kinds this client cannot show yet, such as
+// movies and board games. Those are left out like the library items of those
+// kinds instead of failing the whole response.
+const DISPLAYED_MEDIA: readonly string[] = challengeSchema.shape.targetMedia.options
+
+function isDisplayedMedia(challenge: unknown): boolean {
+  return (
+    typeof challenge === "object" &&
+    challenge !== null &&
+    "targetMedia" in challenge &&
+    DISPLAYED_MEDIA.includes(String(challenge.targetMedia))
+  )
+}
+
+const challengeListSchema = z.preprocess(
+  (challenges) => (Array.isArray(challenges) ? challenges.filter(isDisplayedMedia) : challenges),
+  challengeSchema.array(),
+)
+
 class ChallengeApiClient extends BaseApiClient<typeof challengeSchema, typeof newChallengeSchema> {
   constructor(proxy: HttpProxy) {
     super(challengeSchema, newChallengeSchema, "challenge", proxy)
   }
 
   async fetchChallenges(): Promise<Challenge[]> {
-    return this.fetchEntities(new URLSearchParams({}))
+    return this.proxy.get(this.baseUrl, new URLSearchParams({}), challengeListSchema)
   }
 
   async addChallenge(challenge: Omit<Challenge, "id">): Promise<string> {
@@ -4,6 +4,8 @@ import type { Book, Game, LibraryItem } from "@/models/LibraryItem"
 import type { HttpProxy } from "./HttpProxy"
 import { useHttpApi } from "@/plugins/HttpPlugin"
 
+const itemStatusSchema = z.enum(["planned", "inProgress", "completed", "abandoned"])
+
 const libraryApiItemSchema = z.discriminatedUnion("kind", [
   z.object({
     kind: z.literal("Book"),
@@ -13,17 +15,21 @@ const libraryApiItemSchema = z.discriminatedUnion("kind", [
     translator: z.string().optional(),
     activatedChallengeIds: z.string().array(),
     favorite: z.boolean(),
-    completedAt: z.string(),
+    status: itemStatusSchema.optional(),
+    completedAt: z.string().nullable(),
     addedAt: z.string().datetime({ offset: true }),
   }),
   z.object({
     kind: z.literal("Game"),
     id: z.string(),
     title: z.string(),
-    author: z.string(),
+    creator: z.string(),
+    platform: z.string().nullish(),
+    playtimeMinutes: z.number().int().nullish(),
     activatedChallengeIds: z.string().array(),
     favorite: z.boolean(),
-    completedAt: z.string(),
+    status: itemStatusSchema.optional(),
+    completedAt: z.string().nullable(),
     addedAt: z.string().datetime({ offset: true }),
   }),
 ])
@@ -36,20 +42,45 @@ const newlibraryApiItemSchema = z.discriminatedUnion("kind", [
     translator: z.string().optional(),
     activatedChallengeIds: z.string().array(),
     favorite: z.boolean(),
-    completedAt: z.string(),
+    status: itemStatusSchema.optional(),
+    completedAt: z.string().nullable(),
     addedAt: z.string().datetime({ offset: true }),
   }),
   z.object({
     kind: z.literal("Game"),
     title: z.string(),
-    author: z.string(),
+    creator: z.string(),
     activatedChallengeIds: z.string().array(),
     favorite: z.boolean(),
-    completedAt: z.string(),
+    status: itemStatusSchema.optional(),
+    completedAt: z.string().nullable(),
     addedAt: z.string().datetime({ offset: true }),
   }),
 ])
 
+// The backend also stores kinds this client cannot show yet, such as movies
+// and board games. Those are left out instead of failing the whole response.
+const DISPLAYED_KINDS: readonly string[] = ["Book", "Game"]
+
+function isDisplayedKind(item: unknown): boolean {
+  return (
+    typeof item === "object" &&
+    item !== null &&
+    "kind" in item &&
+    DISPLAYED_KINDS.includes(String(item.kind))
+  )
+}
+
+const libraryApiListSchema = z.preprocess(
+  (items) => (Array.isArray(items) ? items.filter(isDisplayedKind) : items),
+  libraryApiItemSchema.array(),
+)
+
+const libraryApiSingleItemSchema = z.preprocess(
+  (item) => (isDisplayedKind(item) ? item : undefined),
+  libraryApiItemSchema.optional(),
+)
+
 type ApiLibraryItem = z.infer<typeof libraryApiItemSchema>
 
 type NewApiLibraryItem = z.infer<typeof newlibraryApiItemSchema>
@@ -65,6 +96,7 @@ function mapFromApi(item: ApiLibraryItem): LibraryItem {
         translator: item.translator,
         title: item.title,
         author: item.author,
+        status: item.status,
         completedAt: item.completedAt,
         addedAt: item.addedAt,
       }
@@ -77,7 +109,8 @@ function mapFromApi(item: ApiLibraryItem): LibraryItem {
         id: item.id,
         activatedChallengeIds: item.activatedChallengeIds,
         title: item.title,
-        creator: item.author,
+        creator: item.creator,
+        status: item.status,
         completedAt: item.completedAt,
         addedAt: item.addedAt,
       }
@@ -96,6 +129,7 @@ function mapToApi(item: LibraryItem): NewApiLibraryItem {
         translator: item.translator,
         activatedChallengeIds: item.activatedChallengeIds,
         favorite: item.favorite,
+        status: item.status,
         completedAt: item.completedAt,
         addedAt: item.addedAt ?  new Date(item.addedAt).toISOString() : new Date().toISOString(),
       }
@@ -103,9 +137,10 @@ function mapToApi(item: LibraryItem): NewApiLibraryItem {
       return {
         kind: "Game",
         title: item.title,
-        author: item.creator,
+        creator: item.creator,
         activatedChallengeIds: item.activatedChallengeIds,
         favorite: item.favorite,
+        status: item.status,
         completedAt: item.completedAt,
         addedAt: item.addedAt ?  new Date(item.addedAt).toISOString() : new Date().toISOString(),
       }
@@ -121,7 +156,7 @@ class LibraryApiClient extends BaseApiClient<
   }
 
   async fetchLibraryItems(): Promise<LibraryItem[]> {
-    const items = await this.fetchEntities(new URLSearchParams())
+    const items = await this.proxy.get(this.baseUrl, new URLSearchParams(), libraryApiListSchema)
     return items.map(mapFromApi)
   }
 
@@ -141,7 +176,7 @@ class LibraryApiClient extends BaseApiClient<
   }
 
   async getLibraryItem(id: string): Promise<LibraryItem | undefined> {
-    const item = await this.fetchEntity(id)
+    const item = await this.proxy.get(`${this.baseUrl}/${id}`, undefined, libraryApiSingleItemSchema)
     if (item === undefined) {
       return undefined
     }
@@ -1,8 +1,12 @@
+export type ItemStatus = "planned" | "inProgress" | "completed" | "abandoned"
+
 type BaseLibraryItem = {
   id: string
   activatedChallengeIds: string[]
   favorite: boolean
-  completedAt: string
+  status?: ItemStatus
+  /** Missing for planned, in progress and abandoned items */
+  completedAt: string | null
   addedAt: string
 }
 